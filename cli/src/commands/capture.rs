@@ -0,0 +1,135 @@
+// Capture command
+//
+// Command for forwarding a pod port with HTTP inspection on and exporting the captured
+// traffic as a HAR file.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use roro_core::api::kubernetes::portforwarding::{CaptureConfig, HttpExchange};
+use roro_core::api::kubernetes::{get_or_init, ContextManager, PortForwardingConfig};
+
+use super::Command;
+
+/// How often newly captured exchanges are printed
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What to forward and how to capture it
+#[derive(Debug, Clone)]
+pub struct CaptureOptions {
+    /// Kubernetes context to forward in (defaults to the current context)
+    pub context: Option<String>,
+    pub namespace: String,
+    /// Pod name, or a prefix of it
+    pub pod: String,
+    pub remote_port: u16,
+    /// Local port to listen on (defaults to the remote port)
+    pub local_port: Option<u16>,
+    /// HAR file to write (defaults to `~/.roro/captures/<forward id>.har`)
+    pub har: Option<PathBuf>,
+    /// Keep request and response bodies
+    pub bodies: bool,
+    /// Exchanges kept before the oldest are dropped
+    pub max_entries: usize,
+    /// Terminate TLS on the local port with the local development CA
+    pub tls: bool,
+    /// How long to wait for a matching pod to become ready (fails right away when unset)
+    pub wait: Option<Duration>,
+}
+
+/// Capture command - forwards a port, prints each HTTP exchange, and writes a HAR on exit
+///
+/// The forward lives in this process, so the command stays in the foreground until Ctrl-C,
+/// then exports what was captured and stops the forward.
+pub struct CaptureCommand {
+    options: CaptureOptions,
+}
+
+impl CaptureCommand {
+    /// Create a new capture command
+    #[must_use]
+    pub fn new(options: CaptureOptions) -> Self {
+        Self { options }
+    }
+}
+
+fn describe(exchange: &HttpExchange) -> String {
+    format!(
+        "{} {} -> {} ({} ms, {} B)",
+        exchange.method,
+        exchange.target,
+        exchange.status,
+        exchange.latency.as_millis(),
+        exchange.response_body_size
+    )
+}
+
+#[async_trait::async_trait]
+impl Command for CaptureCommand {
+    async fn execute(&self) -> Result<(), String> {
+        let options = &self.options;
+        let context = match &options.context {
+            Some(context) => context.clone(),
+            None => ContextManager::current_context_name().map_err(|e| format!("Error: {e}"))?,
+        };
+        let manager = get_or_init(&context)
+            .await
+            .map_err(|e| format!("Error: {e}"))?;
+
+        let capture = CaptureConfig::default()
+            .with_max_entries(options.max_entries)
+            .with_bodies(options.bodies);
+        let local_port = options.local_port.unwrap_or(options.remote_port);
+        let forward_id = manager
+            .start_forward(PortForwardingConfig {
+                namespace: options.namespace.clone(),
+                pod: options.pod.clone(),
+                remote_port: options.remote_port,
+                local_port,
+                instance_id: "capture".to_string(),
+                capture: Some(capture),
+                tls: options.tls,
+                ready_timeout: options.wait,
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| format!("Error: {e}"))?;
+
+        let scheme = if options.tls { "https" } else { "http" };
+        println!(
+            "Capturing HTTP traffic on {scheme}://localhost:{local_port} -> {}/{}:{}. Press Ctrl-C to stop.",
+            options.namespace, options.pod, options.remote_port
+        );
+
+        let mut last_seen = 0;
+        let mut poll = tokio::time::interval(POLL_INTERVAL);
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+        loop {
+            tokio::select! {
+                result = &mut ctrl_c => {
+                    result.map_err(|e| format!("Error: failed to wait for Ctrl-C: {e}"))?;
+                    break;
+                }
+                _ = poll.tick() => {
+                    let exchanges = manager
+                        .captured_exchanges(&forward_id)
+                        .await
+                        .map_err(|e| format!("Error: {e}"))?;
+                    for exchange in exchanges.iter().skip_while(|e| e.id <= last_seen) {
+                        println!("{}", describe(exchange));
+                    }
+                    last_seen = exchanges.last().map_or(last_seen, |e| e.id);
+                }
+            }
+        }
+
+        let exported = manager
+            .export_har(&forward_id, options.har.as_deref())
+            .await;
+        let _ = manager.stop_forward(&forward_id).await;
+        let (path, count) = exported.map_err(|e| format!("Error: {e}"))?;
+        println!("Wrote {count} exchange(s) to {}", path.display());
+        Ok(())
+    }
+}
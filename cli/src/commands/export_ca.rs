@@ -0,0 +1,43 @@
+// Export CA command
+//
+// Command for exporting the local development CA certificate used by TLS forwards.
+
+use std::path::PathBuf;
+
+use roro_core::api::kubernetes::export_ca;
+
+use super::Command;
+
+/// Export CA command - writes the local CA certificate, creating the CA if needed
+///
+/// Without an output path it prints where the certificate is stored, so it can be added
+/// to a trust store directly.
+pub struct ExportCaCommand {
+    out: Option<PathBuf>,
+}
+
+impl ExportCaCommand {
+    /// Create a new export CA command
+    ///
+    /// # Arguments
+    /// * `out` - File to write the certificate to
+    #[must_use]
+    pub fn new(out: Option<PathBuf>) -> Self {
+        Self { out }
+    }
+}
+
+#[async_trait::async_trait]
+impl Command for ExportCaCommand {
+    async fn execute(&self) -> Result<(), String> {
+        let path = export_ca(self.out.as_deref())
+            .await
+            .map_err(|e| format!("Error: {e}"))?;
+        match &self.out {
+            Some(_) => println!("Wrote CA certificate to {}", path.display()),
+            None => println!("CA certificate: {}", path.display()),
+        }
+        println!("Trust it in your browser or OS to use forwards with TLS enabled.");
+        Ok(())
+    }
+}
@@ -3,9 +3,21 @@
 // This module contains all CLI command implementations.
 // Each command is a thin controller that delegates to the Core layer.
 
+pub mod capture;
+pub mod export_ca;
+pub mod port;
+pub mod restore;
+pub mod shape;
+pub mod socks;
 pub mod status;
 pub mod sync;
 
+pub use capture::{CaptureCommand, CaptureOptions};
+pub use export_ca::ExportCaCommand;
+pub use port::{is_confirmation, PortCommand, PortOptions};
+pub use restore::RestoreCommand;
+pub use shape::{apply_shape_line, ShapeCommand, ShapeOptions};
+pub use socks::SocksCommand;
 pub use status::StatusCommand;
 pub use sync::SyncCommand;
 
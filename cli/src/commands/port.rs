@@ -0,0 +1,115 @@
+// Port command
+//
+// Command for finding out which local process holds a port, with the next free port as
+// an alternative or, once confirmed, terminating the holder.
+
+use std::time::Duration;
+
+use roro_core::api::kubernetes::portforwarding::{
+    check_bind_available, find_available_port, find_port_holder, terminate_port_holder,
+};
+use roro_core::api::kubernetes::BindAddress;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use super::Command;
+
+/// How long a terminated holder gets to release the port
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Which port to inspect and what to do about its holder
+#[derive(Debug, Clone)]
+pub struct PortOptions {
+    pub port: u16,
+    /// Address the port would be bound on
+    pub bind_address: BindAddress,
+    /// Terminate the process holding the port
+    pub terminate: bool,
+    /// Terminate without asking for confirmation
+    pub yes: bool,
+}
+
+/// Port command - reports the process holding a port and how to get around it
+pub struct PortCommand {
+    options: PortOptions,
+}
+
+impl PortCommand {
+    /// Create a new port command
+    #[must_use]
+    pub fn new(options: PortOptions) -> Self {
+        Self { options }
+    }
+}
+
+/// Whether an answer to a yes/no prompt agrees; anything but yes declines
+#[must_use]
+pub fn is_confirmation(answer: &str) -> bool {
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+#[async_trait::async_trait]
+impl Command for PortCommand {
+    async fn execute(&self) -> Result<(), String> {
+        let PortOptions {
+            port,
+            bind_address,
+            terminate,
+            yes,
+        } = self.options;
+        if check_bind_available(bind_address, port).is_ok() {
+            println!("Port {port} is free on {bind_address}");
+            return Ok(());
+        }
+
+        let holder = find_port_holder(port);
+        match &holder {
+            Some(holder) => println!("Port {port} is in use by {holder}"),
+            None => println!(
+                "Port {port} is in use by a process that can't be identified (it may belong to another user)"
+            ),
+        }
+        if let Ok(free) = find_available_port(bind_address, port) {
+            println!("Next free port: {free}");
+        }
+
+        let Some(holder) = holder else {
+            return Ok(());
+        };
+        if !terminate {
+            println!("Run again with --terminate to end PID {}.", holder.pid);
+            return Ok(());
+        }
+
+        if !yes {
+            println!("Terminate {holder}? [y/N]");
+            let mut answer = String::new();
+            BufReader::new(tokio::io::stdin())
+                .read_line(&mut answer)
+                .await
+                .map_err(|e| format!("Error: failed to read the answer: {e}"))?;
+            if !is_confirmation(&answer) {
+                println!("Left PID {} running", holder.pid);
+                return Ok(());
+            }
+        }
+
+        terminate_port_holder(&holder).map_err(|e| format!("Error: {e}"))?;
+        let released = tokio::time::timeout(RELEASE_TIMEOUT, async {
+            while check_bind_available(bind_address, port).is_err() {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await;
+        match released {
+            Ok(()) => {
+                println!("Port {port} is free now");
+                Ok(())
+            }
+            Err(_) => Err(format!(
+                "Error: PID {} still holds port {port} after {}s",
+                holder.pid,
+                RELEASE_TIMEOUT.as_secs()
+            )),
+        }
+    }
+}
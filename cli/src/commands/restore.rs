@@ -0,0 +1,149 @@
+// Restore command
+//
+// Command for restoring port forwards saved by a previous session.
+
+use std::path::PathBuf;
+
+use roro_core::api::kubernetes::{get_or_init, ContextManager, StopMode, StopSummary};
+use roro_core::{DesiredState, ForwardRecord};
+
+use super::Command;
+
+/// Restore command - restarts saved port forwards and keeps them running
+///
+/// Forwards live in this process, so the command stays in the foreground until Ctrl-C, which
+/// drains open connections before exiting; a second Ctrl-C stops without waiting.
+/// Exiting does not mark the forwards as stopped, so they are offered again next launch.
+/// With a hosts file, the names of services restored on dedicated addresses are written to
+/// the context's block in it while the forwards run.
+pub struct RestoreCommand {
+    context: Option<String>,
+    list_only: bool,
+    /// Hosts file to publish service names in
+    hosts_file: Option<PathBuf>,
+}
+
+impl RestoreCommand {
+    /// Create a new restore command
+    ///
+    /// # Arguments
+    /// * `context` - Kubernetes context to restore forwards for (defaults to the current context)
+    /// * `list_only` - Only list the saved forwards without starting them
+    #[must_use]
+    pub fn new(context: Option<String>, list_only: bool) -> Self {
+        Self {
+            context,
+            list_only,
+            hosts_file: None,
+        }
+    }
+
+    /// Publish the names of services on dedicated addresses in the hosts file at `path`
+    #[must_use]
+    pub fn with_hosts_file(mut self, path: Option<PathBuf>) -> Self {
+        self.hosts_file = path;
+        self
+    }
+}
+
+fn describe(record: &ForwardRecord) -> String {
+    format!(
+        "{}/{}:{} -> localhost:{}",
+        record.namespace, record.pod, record.remote_port, record.local_port
+    )
+}
+
+#[async_trait::async_trait]
+impl Command for RestoreCommand {
+    async fn execute(&self) -> Result<(), String> {
+        let context = match &self.context {
+            Some(context) => context.clone(),
+            None => ContextManager::current_context_name().map_err(|e| format!("Error: {e}"))?,
+        };
+        let manager = get_or_init(&context)
+            .await
+            .map_err(|e| format!("Error: {e}"))?;
+
+        if self.list_only {
+            let saved = manager
+                .saved_forwards()
+                .await
+                .map_err(|e| format!("Error: {e}"))?;
+            if saved.is_empty() {
+                println!("No saved port forwards for context '{context}'");
+            }
+            for record in saved {
+                let state = match record.desired_state {
+                    DesiredState::Running => "running",
+                    DesiredState::Stopped => "stopped",
+                };
+                println!("{} [{state}]", describe(&record));
+            }
+            return Ok(());
+        }
+
+        let outcomes = manager
+            .restore_forwards()
+            .await
+            .map_err(|e| format!("Error: {e}"))?;
+
+        let mut restored = 0;
+        for (record, result) in &outcomes {
+            match result {
+                Ok(_) => {
+                    restored += 1;
+                    println!("Restored {}", describe(record));
+                }
+                Err(e) => eprintln!("Failed to restore {}: {e}", describe(record)),
+            }
+        }
+
+        if restored == 0 {
+            println!("No port forwards restored for context '{context}'");
+            return Ok(());
+        }
+
+        let hosts_file = self.hosts_file.as_deref();
+        if let Some(path) = hosts_file {
+            match manager.write_hosts_file(Some(path)).await {
+                Ok(written) => println!("Service names written to {}", written.display()),
+                Err(e) => eprintln!("Failed to write service names to the hosts file: {e}"),
+            }
+        }
+
+        println!("Forwarding {restored} port(s). Press Ctrl-C to exit.");
+        let waited = wait_for_ctrl_c().await;
+        if let Some(path) = hosts_file {
+            if let Err(e) = manager.clear_hosts_file(Some(path)).await {
+                eprintln!("Failed to remove service names from the hosts file: {e}");
+            }
+        }
+        waited?;
+
+        println!("Draining open connections. Press Ctrl-C again to stop now.");
+        tokio::select! {
+            summary = manager.shutdown(StopMode::Drain) => report_stop(summary),
+            result = wait_for_ctrl_c() => {
+                result?;
+                report_stop(manager.shutdown(StopMode::Now).await);
+                println!("Stopped without waiting for open connections");
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn wait_for_ctrl_c() -> Result<(), String> {
+    tokio::signal::ctrl_c()
+        .await
+        .map_err(|e| format!("Error: failed to wait for Ctrl-C: {e}"))
+}
+
+fn report_stop(summary: StopSummary) {
+    if summary.drained > 0 {
+        println!("{} connection(s) finished while draining", summary.drained);
+    }
+    if summary.force_closed > 0 {
+        println!("{} connection(s) force-closed", summary.force_closed);
+    }
+}
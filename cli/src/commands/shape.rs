@@ -0,0 +1,173 @@
+// Shape command
+//
+// Command for forwarding a pod port through added latency, a bandwidth cap and injected
+// faults, adjustable while the forward runs.
+
+use std::time::Duration;
+
+use roro_core::api::kubernetes::{
+    get_or_init, ContextManager, PortForwardingConfig, TrafficShaping,
+};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use super::Command;
+
+/// What to forward and how to degrade it
+#[derive(Debug, Clone)]
+pub struct ShapeOptions {
+    /// Kubernetes context to forward in (defaults to the current context)
+    pub context: Option<String>,
+    pub namespace: String,
+    /// Pod name, or a prefix of it
+    pub pod: String,
+    pub remote_port: u16,
+    /// Local port to listen on (defaults to the remote port)
+    pub local_port: Option<u16>,
+    /// Shaping applied from the start
+    pub shaping: TrafficShaping,
+    /// How long to wait for a matching pod to become ready (fails right away when unset)
+    pub wait: Option<Duration>,
+}
+
+/// Shape command - forwards a port with traffic shaping and fault injection
+///
+/// The forward lives in this process, so the command stays in the foreground until Ctrl-C.
+/// Lines typed on stdin change the shaping while it runs, see `apply_shape_line`.
+pub struct ShapeCommand {
+    options: ShapeOptions,
+}
+
+impl ShapeCommand {
+    /// Create a new shape command
+    #[must_use]
+    pub fn new(options: ShapeOptions) -> Self {
+        Self { options }
+    }
+}
+
+/// Apply one line of runtime input to `shaping`
+///
+/// Lines are `latency <ms>`, `jitter <ms>`, `bandwidth <bytes per second | off>`,
+/// `resets <probability>`, `blackhole <on | off>`, and `clear` to remove all shaping.
+///
+/// # Errors
+/// Returns a message for the user if the line is not understood or the value is invalid
+pub fn apply_shape_line(shaping: &TrafficShaping, line: &str) -> Result<TrafficShaping, String> {
+    let mut words = line.split_whitespace();
+    let (Some(setting), value) = (words.next(), words.next()) else {
+        return Ok(shaping.clone());
+    };
+    if words.next().is_some() {
+        return Err(format!("Expected a setting and one value, got \"{line}\""));
+    }
+
+    let value = |name: &str| value.ok_or_else(|| format!("Missing value for {name}"));
+    let millis = |name: &str| {
+        value(name)?
+            .parse::<u64>()
+            .map(Duration::from_millis)
+            .map_err(|_| format!("{name} must be a number of milliseconds"))
+    };
+
+    let shaping = shaping.clone();
+    let updated = match setting {
+        "latency" => shaping.with_latency(millis("latency")?),
+        "jitter" => shaping.with_jitter(millis("jitter")?),
+        "bandwidth" => match value("bandwidth")? {
+            "off" => shaping.with_bandwidth(None),
+            rate => shaping
+                .with_bandwidth(Some(rate.parse().map_err(|_| {
+                    "bandwidth must be bytes per second or \"off\"".to_string()
+                })?)),
+        },
+        "resets" => shaping.with_reset_probability(
+            value("resets")?
+                .parse()
+                .map_err(|_| "resets must be a probability from 0 to 1".to_string())?,
+        ),
+        "blackhole" => match value("blackhole")? {
+            "on" => shaping.with_blackhole(true),
+            "off" => shaping.with_blackhole(false),
+            _ => return Err("blackhole must be \"on\" or \"off\"".to_string()),
+        },
+        "clear" => TrafficShaping::default(),
+        _ => return Err(format!("Unknown setting \"{setting}\"")),
+    };
+    updated.validate().map_err(|e| e.to_string())?;
+    Ok(updated)
+}
+
+#[async_trait::async_trait]
+impl Command for ShapeCommand {
+    async fn execute(&self) -> Result<(), String> {
+        let options = &self.options;
+        options
+            .shaping
+            .validate()
+            .map_err(|e| format!("Error: {e}"))?;
+        let context = match &options.context {
+            Some(context) => context.clone(),
+            None => ContextManager::current_context_name().map_err(|e| format!("Error: {e}"))?,
+        };
+        let manager = get_or_init(&context)
+            .await
+            .map_err(|e| format!("Error: {e}"))?;
+
+        let local_port = options.local_port.unwrap_or(options.remote_port);
+        let forward_id = manager
+            .start_forward(PortForwardingConfig {
+                namespace: options.namespace.clone(),
+                pod: options.pod.clone(),
+                remote_port: options.remote_port,
+                local_port,
+                instance_id: "shape".to_string(),
+                ready_timeout: options.wait,
+                shaping: Some(options.shaping.clone()),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| format!("Error: {e}"))?;
+
+        println!(
+            "Forwarding localhost:{local_port} -> {}/{}:{} with traffic shaping: {}",
+            options.namespace, options.pod, options.remote_port, options.shaping
+        );
+        println!(
+            "Type \"latency <ms>\", \"jitter <ms>\", \"bandwidth <B/s|off>\", \"resets <0-1>\", \"blackhole on|off\" or \"clear\" to change it. Press Ctrl-C to stop."
+        );
+
+        let mut shaping = options.shaping.clone();
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+        let result = loop {
+            tokio::select! {
+                result = &mut ctrl_c => {
+                    break result.map_err(|e| format!("Error: failed to wait for Ctrl-C: {e}"));
+                }
+                line = lines.next_line() => {
+                    // Without stdin the shaping just stays as it is until Ctrl-C
+                    let Ok(Some(line)) = line else {
+                        break (&mut ctrl_c)
+                            .await
+                            .map_err(|e| format!("Error: failed to wait for Ctrl-C: {e}"));
+                    };
+                    match apply_shape_line(&shaping, &line) {
+                        Ok(updated) => {
+                            let applied = manager.set_shaping(&forward_id, Some(updated.clone())).await;
+                            if let Err(e) = applied {
+                                break Err(format!("Error: {e}"));
+                            }
+                            shaping = updated;
+                            println!("Traffic shaping: {shaping}");
+                        }
+                        Err(e) => eprintln!("{e}"),
+                    }
+                }
+            }
+        };
+
+        let _ = manager.stop_forward(&forward_id).await;
+        result
+    }
+}
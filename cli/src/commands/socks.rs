@@ -0,0 +1,62 @@
+// SOCKS command
+//
+// Command for running a SOCKS5 proxy that reaches any service in a kube context by its
+// cluster DNS name.
+
+use roro_core::api::kubernetes::{get_or_init, BindAddress, ContextManager, SocksConfig};
+
+use super::Command;
+
+/// SOCKS command - serves the SOCKS5 proxy in the foreground until Ctrl-C
+pub struct SocksCommand {
+    context: Option<String>,
+    config: SocksConfig,
+}
+
+impl SocksCommand {
+    /// Create a new SOCKS command
+    ///
+    /// # Arguments
+    /// * `context` - Kubernetes context to reach services in (defaults to the current context)
+    /// * `config` - Where the proxy listens and the namespace of single-label names
+    #[must_use]
+    pub fn new(context: Option<String>, config: SocksConfig) -> Self {
+        Self { context, config }
+    }
+}
+
+#[async_trait::async_trait]
+impl Command for SocksCommand {
+    async fn execute(&self) -> Result<(), String> {
+        let context = match &self.context {
+            Some(context) => context.clone(),
+            None => ContextManager::current_context_name().map_err(|e| format!("Error: {e}"))?,
+        };
+        let manager = get_or_init(&context)
+            .await
+            .map_err(|e| format!("Error: {e}"))?;
+
+        manager
+            .start_socks_proxy(self.config.clone())
+            .await
+            .map_err(|e| format!("Error: {e}"))?;
+        let host = match self.config.bind_address {
+            BindAddress::Localhost => "localhost".to_string(),
+            BindAddress::Ip(ip) => ip.to_string(),
+        };
+        println!(
+            "SOCKS5 proxy for context '{context}' on {host}:{}. Press Ctrl-C to stop.",
+            self.config.port
+        );
+        println!(
+            "Example: curl --socks5-hostname {host}:{} http://<service>.<namespace>:<port>/",
+            self.config.port
+        );
+
+        tokio::signal::ctrl_c()
+            .await
+            .map_err(|e| format!("Error: failed to wait for Ctrl-C: {e}"))?;
+        manager.stop_socks_proxy().await;
+        Ok(())
+    }
+}
@@ -4,4 +4,8 @@
 
 pub mod commands;
 
-pub use commands::{Command, StatusCommand, SyncCommand};
+pub use commands::{
+    apply_shape_line, is_confirmation, CaptureCommand, CaptureOptions, Command, ExportCaCommand,
+    PortCommand, PortOptions, RestoreCommand, ShapeCommand, ShapeOptions, SocksCommand,
+    StatusCommand, SyncCommand,
+};
@@ -4,7 +4,15 @@
 // It provides a thin controller layer that delegates to the Core layer.
 
 use clap::Parser;
-use roro_cli::{Command, StatusCommand, SyncCommand};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use roro_cli::{
+    CaptureCommand, CaptureOptions, Command, ExportCaCommand, PortCommand, PortOptions,
+    RestoreCommand, ShapeCommand, ShapeOptions, SocksCommand, StatusCommand, SyncCommand,
+};
+use roro_core::api::kubernetes::portforwarding::DEFAULT_SOCKS_PORT;
+use roro_core::api::kubernetes::{BindAddress, SocksConfig, TrafficShaping};
 use roro_core::load_workstation_config;
 
 /// Roro Kube - Docker Compose for Kubernetes
@@ -26,6 +34,179 @@ pub enum Commands {
         /// The name of the app configuration to sync
         name: String,
     },
+    /// Restore port forwards saved by a previous session
+    Restore(RestoreArgs),
+    /// Forward a pod port and record its HTTP traffic as a HAR file
+    Capture {
+        /// Pod name, or a prefix of it
+        pod: String,
+        /// Port on the pod
+        #[arg(long)]
+        remote_port: u16,
+        /// Local port to listen on (defaults to the remote port)
+        #[arg(long)]
+        local_port: Option<u16>,
+        /// Namespace of the pod
+        #[arg(long, short, default_value = "default")]
+        namespace: String,
+        /// Kubernetes context to forward in (defaults to the current context)
+        #[arg(long)]
+        context: Option<String>,
+        /// HAR file to write on exit (defaults to ~/.roro/captures/<forward id>.har)
+        #[arg(long)]
+        har: Option<PathBuf>,
+        /// Keep request and response bodies, not just metadata
+        #[arg(long)]
+        bodies: bool,
+        /// Exchanges kept before the oldest are dropped
+        #[arg(long, default_value_t = 200)]
+        max_entries: usize,
+        /// Serve HTTPS locally, with certificates from the local development CA
+        #[arg(long)]
+        tls: bool,
+        /// Seconds to wait for a matching pod to become ready
+        #[arg(long, value_name = "SECONDS")]
+        wait: Option<u64>,
+    },
+    /// Forward a pod port with added latency, a bandwidth cap or injected faults
+    Shape(ShapeArgs),
+    /// Serve a SOCKS5 proxy that reaches cluster services by name, e.g. `api.dev:8080`
+    Socks(SocksArgs),
+    /// Show which process holds a local port and the next free port
+    Port {
+        /// Local port to inspect
+        port: u16,
+        /// Address the port would be bound on: "localhost" or an IP address
+        #[arg(long, default_value = "localhost")]
+        bind: String,
+        /// Terminate the process holding the port, after confirmation
+        #[arg(long)]
+        terminate: bool,
+        /// Don't ask before terminating
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Export the local development CA certificate so browsers and tools can trust it
+    ExportCa {
+        /// File to write the certificate to (defaults to printing where it is stored)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Arguments of the `restore` command
+#[derive(clap::Args, Debug)]
+pub struct RestoreArgs {
+    /// Kubernetes context to restore forwards for (defaults to the current context)
+    #[arg(long)]
+    context: Option<String>,
+    /// List saved port forwards without starting them
+    #[arg(long)]
+    list: bool,
+    /// Write the names of services on dedicated addresses to this hosts file while
+    /// running, e.g. /etc/hosts (which usually needs administrator rights)
+    #[arg(long, value_name = "PATH")]
+    hosts_file: Option<PathBuf>,
+}
+
+impl RestoreArgs {
+    fn into_command(self) -> RestoreCommand {
+        RestoreCommand::new(self.context, self.list).with_hosts_file(self.hosts_file)
+    }
+}
+
+/// Arguments of the `socks` command
+#[derive(clap::Args, Debug)]
+pub struct SocksArgs {
+    /// Port to listen on
+    #[arg(long, default_value_t = DEFAULT_SOCKS_PORT)]
+    port: u16,
+    /// Address to listen on: "localhost" or an IP address
+    #[arg(long, default_value = "localhost")]
+    bind: String,
+    /// Allow a bind address other machines can reach; the proxy has no authentication
+    #[arg(long)]
+    expose: bool,
+    /// Namespace of service names given without one
+    #[arg(long, short, default_value = "default")]
+    namespace: String,
+    /// Kubernetes context to reach services in (defaults to the current context)
+    #[arg(long)]
+    context: Option<String>,
+}
+
+impl SocksArgs {
+    fn into_command(self) -> Result<SocksCommand, String> {
+        let bind_address = self
+            .bind
+            .parse::<BindAddress>()
+            .map_err(|e| format!("Error: {e}"))?;
+        Ok(SocksCommand::new(
+            self.context,
+            SocksConfig {
+                bind_address,
+                port: self.port,
+                default_namespace: self.namespace,
+                expose_to_network: self.expose,
+            },
+        ))
+    }
+}
+
+/// Arguments of the `shape` command
+#[derive(clap::Args, Debug)]
+pub struct ShapeArgs {
+    /// Pod name, or a prefix of it
+    pod: String,
+    /// Port on the pod
+    #[arg(long)]
+    remote_port: u16,
+    /// Local port to listen on (defaults to the remote port)
+    #[arg(long)]
+    local_port: Option<u16>,
+    /// Namespace of the pod
+    #[arg(long, short, default_value = "default")]
+    namespace: String,
+    /// Kubernetes context to forward in (defaults to the current context)
+    #[arg(long)]
+    context: Option<String>,
+    /// Milliseconds added to every chunk in each direction
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    latency: u64,
+    /// Up to this many extra milliseconds, drawn at random per chunk
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    jitter: u64,
+    /// Bytes per second allowed in each direction of a connection
+    #[arg(long, value_name = "BYTES")]
+    bandwidth: Option<u64>,
+    /// Chance from 0 to 1 that a chunk resets its connection
+    #[arg(long, value_name = "PROBABILITY", default_value_t = 0.0)]
+    reset_probability: f64,
+    /// Accept connections but discard everything sent through them
+    #[arg(long)]
+    blackhole: bool,
+    /// Seconds to wait for a matching pod to become ready
+    #[arg(long, value_name = "SECONDS")]
+    wait: Option<u64>,
+}
+
+impl ShapeArgs {
+    fn into_options(self) -> ShapeOptions {
+        ShapeOptions {
+            context: self.context,
+            namespace: self.namespace,
+            pod: self.pod,
+            remote_port: self.remote_port,
+            local_port: self.local_port,
+            shaping: TrafficShaping::default()
+                .with_latency(Duration::from_millis(self.latency))
+                .with_jitter(Duration::from_millis(self.jitter))
+                .with_bandwidth(self.bandwidth)
+                .with_reset_probability(self.reset_probability)
+                .with_blackhole(self.blackhole),
+            wait: self.wait.map(Duration::from_secs),
+        }
+    }
 }
 
 #[tokio::main]
@@ -51,6 +232,65 @@ async fn main() {
             let cmd = SyncCommand::new(name, workstation_config);
             cmd.execute().await
         }
+        Some(Commands::Restore(args)) => {
+            let cmd = args.into_command();
+            cmd.execute().await
+        }
+        Some(Commands::Capture {
+            pod,
+            remote_port,
+            local_port,
+            namespace,
+            context,
+            har,
+            bodies,
+            max_entries,
+            tls,
+            wait,
+        }) => {
+            let cmd = CaptureCommand::new(CaptureOptions {
+                context,
+                namespace,
+                pod,
+                remote_port,
+                local_port,
+                har,
+                bodies,
+                max_entries,
+                tls,
+                wait: wait.map(Duration::from_secs),
+            });
+            cmd.execute().await
+        }
+        Some(Commands::Shape(args)) => {
+            let cmd = ShapeCommand::new(args.into_options());
+            cmd.execute().await
+        }
+        Some(Commands::Socks(args)) => match args.into_command() {
+            Ok(cmd) => cmd.execute().await,
+            Err(e) => Err(e),
+        },
+        Some(Commands::Port {
+            port,
+            bind,
+            terminate,
+            yes,
+        }) => match bind.parse::<BindAddress>() {
+            Ok(bind_address) => {
+                let cmd = PortCommand::new(PortOptions {
+                    port,
+                    bind_address,
+                    terminate,
+                    yes,
+                });
+                cmd.execute().await
+            }
+            Err(e) => Err(format!("Error: {e}")),
+        },
+        Some(Commands::ExportCa { out }) => {
+            let cmd = ExportCaCommand::new(out);
+            cmd.execute().await
+        }
         None => {
             // No command provided, show help
             Cli::parse_from(vec!["roro-kube", "--help"]);
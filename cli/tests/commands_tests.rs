@@ -2,8 +2,14 @@
 //
 // Tests for CLI command implementations to verify they work correctly with core layer APIs.
 
-use roro_cli::commands::{Command, StatusCommand, SyncCommand};
+use roro_cli::commands::{
+    apply_shape_line, is_confirmation, CaptureCommand, CaptureOptions, Command, PortCommand,
+    PortOptions, RestoreCommand, ShapeCommand, ShapeOptions, SocksCommand, StatusCommand,
+    SyncCommand,
+};
+use roro_core::api::kubernetes::{BindAddress, SocksConfig, TrafficShaping};
 use roro_domain::{AppReference, WorkstationConfig};
+use std::time::Duration;
 
 #[tokio::test]
 async fn test_status_command_executes() {
@@ -89,3 +95,162 @@ fn test_sync_command_new() {
     // Just verify it can be created
     let _ = cmd;
 }
+
+#[tokio::test]
+async fn test_restore_command_unknown_context() {
+    let cmd = RestoreCommand::new(Some("nonexistent-context".to_string()), true);
+    let result = cmd.execute().await;
+
+    let Err(error_msg) = result else {
+        panic!("Expected error for unknown context");
+    };
+    assert!(error_msg.starts_with("Error: "));
+}
+
+#[tokio::test]
+async fn test_capture_command_unknown_context() {
+    let cmd = CaptureCommand::new(CaptureOptions {
+        context: Some("nonexistent-context".to_string()),
+        namespace: "default".to_string(),
+        pod: "api".to_string(),
+        remote_port: 8080,
+        local_port: None,
+        har: None,
+        bodies: false,
+        max_entries: 200,
+        tls: false,
+        wait: None,
+    });
+    let result = cmd.execute().await;
+
+    let Err(error_msg) = result else {
+        panic!("Expected error for unknown context");
+    };
+    assert!(error_msg.starts_with("Error: "));
+}
+
+#[tokio::test]
+async fn test_socks_command_unknown_context() {
+    let cmd = SocksCommand::new(
+        Some("nonexistent-context".to_string()),
+        SocksConfig::default(),
+    );
+    let result = cmd.execute().await;
+
+    let Err(error_msg) = result else {
+        panic!("Expected error for unknown context");
+    };
+    assert!(error_msg.starts_with("Error: "));
+}
+
+#[tokio::test]
+async fn test_shape_command_unknown_context() {
+    let cmd = ShapeCommand::new(ShapeOptions {
+        context: Some("nonexistent-context".to_string()),
+        namespace: "default".to_string(),
+        pod: "api".to_string(),
+        remote_port: 8080,
+        local_port: None,
+        shaping: TrafficShaping::default().with_latency(Duration::from_millis(200)),
+        wait: None,
+    });
+    let result = cmd.execute().await;
+
+    let Err(error_msg) = result else {
+        panic!("Expected error for unknown context");
+    };
+    assert!(error_msg.starts_with("Error: "));
+}
+
+#[tokio::test]
+async fn test_shape_command_rejects_invalid_shaping() {
+    let cmd = ShapeCommand::new(ShapeOptions {
+        context: Some("nonexistent-context".to_string()),
+        namespace: "default".to_string(),
+        pod: "api".to_string(),
+        remote_port: 8080,
+        local_port: None,
+        shaping: TrafficShaping::default().with_reset_probability(1.5),
+        wait: None,
+    });
+    let result = cmd.execute().await;
+
+    let Err(error_msg) = result else {
+        panic!("Expected error for invalid reset probability");
+    };
+    assert!(error_msg.contains("Reset probability"));
+}
+
+#[test]
+fn test_apply_shape_line_updates_settings() {
+    let shaping = [
+        "latency 200",
+        "jitter 50",
+        "bandwidth 64000",
+        "resets 0.25",
+        "blackhole on",
+    ]
+    .iter()
+    .try_fold(TrafficShaping::default(), |shaping, line| {
+        apply_shape_line(&shaping, line)
+    });
+
+    let expected = TrafficShaping::default()
+        .with_latency(Duration::from_millis(200))
+        .with_jitter(Duration::from_millis(50))
+        .with_bandwidth(Some(64000))
+        .with_reset_probability(0.25)
+        .with_blackhole(true);
+    assert_eq!(shaping, Ok(expected.clone()));
+
+    assert_eq!(
+        apply_shape_line(&expected, "bandwidth off"),
+        Ok(expected.clone().with_bandwidth(None))
+    );
+    assert_eq!(
+        apply_shape_line(&expected, "clear"),
+        Ok(TrafficShaping::default())
+    );
+    assert_eq!(apply_shape_line(&expected, "  "), Ok(expected));
+}
+
+#[test]
+fn test_apply_shape_line_rejects_bad_input() {
+    let shaping = TrafficShaping::default();
+
+    assert!(apply_shape_line(&shaping, "latency").is_err());
+    assert!(apply_shape_line(&shaping, "latency soon").is_err());
+    assert!(apply_shape_line(&shaping, "resets 2").is_err());
+    assert!(apply_shape_line(&shaping, "bandwidth 0").is_err());
+    assert!(apply_shape_line(&shaping, "blackhole maybe").is_err());
+    assert!(apply_shape_line(&shaping, "latency 10 20").is_err());
+    assert!(apply_shape_line(&shaping, "speed 10").is_err());
+}
+
+#[tokio::test]
+async fn test_port_command_reports_held_port_without_terminating() {
+    let Ok(listener) = std::net::TcpListener::bind("127.0.0.1:0") else {
+        panic!("Failed to bind a test listener");
+    };
+    let Ok(address) = listener.local_addr() else {
+        panic!("Test listener has no address");
+    };
+
+    let cmd = PortCommand::new(PortOptions {
+        port: address.port(),
+        bind_address: BindAddress::default(),
+        terminate: false,
+        yes: false,
+    });
+    assert_eq!(cmd.execute().await, Ok(()));
+    drop(listener);
+}
+
+#[test]
+fn test_is_confirmation_accepts_only_yes() {
+    assert!(is_confirmation("y\n"));
+    assert!(is_confirmation(" Yes "));
+    assert!(!is_confirmation("\n"));
+    assert!(!is_confirmation("no"));
+    assert!(!is_confirmation("yep"));
+}
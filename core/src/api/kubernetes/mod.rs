@@ -1,11 +1,15 @@
 pub mod client;
 pub mod context;
 pub mod portforwarding;
-pub mod portforwarding_singleton;
+pub mod portforwarding_registry;
 
 pub use client::KubernetesClient;
 pub use context::ContextManager;
 pub use portforwarding::{
-    PortForwardingConfig, PortForwardingManager, PortForwardingState, PortForwardingStatus,
+    export_ca, BindAddress, CaptureConfig, ForwardTarget, HttpExchange, PortForwardingConfig,
+    PortForwardingEvent, PortForwardingManager, PortForwardingState, PortForwardingStatus,
+    PortMapping, ProxyConfig, ProxyRoute, SocksConfig, StopMode, StopSummary, TrafficShaping,
+};
+pub use portforwarding_registry::{
+    all_managers, contexts, get, get_or_init, initialize, is_initialized, list_all_forwards,
 };
-pub use portforwarding_singleton::{get, get_or_init, initialize, is_initialized};
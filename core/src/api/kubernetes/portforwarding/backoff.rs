@@ -0,0 +1,118 @@
+// Reconnection backoff
+//
+// This module defines the policy a forward's supervisor follows between reconnection
+// attempts: exponential delays with jitter, an optional attempt limit, and a reset once
+// the forward has run healthily for a while.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How a supervised forward waits between reconnection attempts
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackoffPolicy {
+    /// Delay before the first retry
+    pub initial: Duration,
+    /// Upper bound on any single delay, jitter included
+    pub max: Duration,
+    /// Factor applied to the delay after each failed attempt; values below 1.0 act as 1.0
+    pub multiplier: f64,
+    /// Fraction of each delay randomised in both directions, from 0.0 (none) to 1.0
+    pub jitter: f64,
+    /// Attempts allowed before the forward is marked failed; `None` retries forever
+    pub max_attempts: Option<u32>,
+    /// A forward that ran this long before failing starts its backoff over
+    pub reset_after: Duration,
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_mins(1),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: Some(5),
+            reset_after: Duration::from_mins(1),
+        }
+    }
+}
+
+impl BackoffPolicy {
+    #[must_use]
+    pub fn with_initial(mut self, initial: Duration) -> Self {
+        self.initial = initial;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max(mut self, max: Duration) -> Self {
+        self.max = max;
+        self
+    }
+
+    #[must_use]
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    #[must_use]
+    pub fn with_reset_after(mut self, reset_after: Duration) -> Self {
+        self.reset_after = reset_after;
+        self
+    }
+
+    /// Whether the 1-based `attempt` may run
+    #[must_use]
+    pub fn allows(&self, attempt: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempt <= max)
+    }
+
+    /// Delay before the 1-based `attempt`, with jitter drawn at random
+    #[must_use]
+    pub fn next_delay(&self, attempt: u32) -> Duration {
+        self.delay(attempt, random_unit())
+    }
+
+    /// Delay before the 1-based `attempt` for a given `random` value in `[0.0, 1.0]`
+    ///
+    /// The result is `initial * multiplier^(attempt - 1)`, scaled by a factor between
+    /// `1 - jitter` and `1 + jitter`, and capped at `max`.
+    #[must_use]
+    pub fn delay(&self, attempt: u32, random: f64) -> Duration {
+        let max_secs = self.max.as_secs_f64();
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let base = self.initial.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = 1.0 - jitter + 2.0 * jitter * random.clamp(0.0, 1.0);
+        let secs = (base.min(max_secs) * factor).min(max_secs);
+        if secs.is_finite() && secs > 0.0 {
+            Duration::from_secs_f64(secs)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+/// A random value in `[0.0, 1.0)` from the standard library's per-process hasher keys
+///
+/// Each `RandomState` is keyed differently, which is random enough for backoff jitter and
+/// fault injection but not for anything secret.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
@@ -0,0 +1,126 @@
+// Bind addresses for port forwarding
+//
+// This module resolves the local address a forward listens on, checks port availability
+// against that address, and binds the listeners.
+
+use crate::errors::CoreError;
+use futures::future::select_all;
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use tokio::net::{TcpListener, TcpStream};
+
+/// Local address a port forward listens on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BindAddress {
+    /// Both `127.0.0.1` and `::1`, so clients resolving `localhost` to either family connect
+    ///
+    /// The IPv6 listener is skipped on hosts without IPv6 loopback.
+    #[default]
+    Localhost,
+    /// A single address: loopback, a specific interface, or unspecified (`0.0.0.0`, `::`)
+    Ip(IpAddr),
+}
+
+impl BindAddress {
+    /// Addresses to bind, in order; only the first is required to succeed
+    #[must_use]
+    pub fn addresses(&self) -> Vec<IpAddr> {
+        match self {
+            Self::Localhost => vec![
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                IpAddr::V6(Ipv6Addr::LOCALHOST),
+            ],
+            Self::Ip(ip) => vec![*ip],
+        }
+    }
+
+    /// Whether the forward is reachable from other machines
+    #[must_use]
+    pub fn is_exposed(&self) -> bool {
+        match self {
+            Self::Localhost => false,
+            Self::Ip(ip) => !ip.is_loopback(),
+        }
+    }
+}
+
+impl fmt::Display for BindAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Localhost => f.write_str("localhost"),
+            Self::Ip(ip) => write!(f, "{ip}"),
+        }
+    }
+}
+
+impl FromStr for BindAddress {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("localhost") {
+            return Ok(Self::Localhost);
+        }
+        s.trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+            .map(Self::Ip)
+            .map_err(|_| CoreError::Validation(format!("Invalid bind address '{s}'")))
+    }
+}
+
+/// Optional addresses may fail to bind when their address family is unavailable
+fn is_family_unavailable(e: &io::Error) -> bool {
+    e.kind() != io::ErrorKind::AddrInUse && e.kind() != io::ErrorKind::PermissionDenied
+}
+
+/// Check that `port` is free on every address `bind` listens on
+///
+/// # Errors
+/// Returns `CoreError::PortConflict` if the port is in use on any of the addresses
+pub fn check_bind_available(bind: BindAddress, port: u16) -> Result<(), CoreError> {
+    for (index, ip) in bind.addresses().into_iter().enumerate() {
+        if let Err(e) = std::net::TcpListener::bind(SocketAddr::new(ip, port)) {
+            if index == 0 || !is_family_unavailable(&e) {
+                return Err(CoreError::PortConflict(port));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Find the lowest port from `start_port` up that is free on every address `bind` listens on
+///
+/// # Errors
+/// Returns `CoreError::PortForwarding` if no port from `start_port` up is free
+pub fn find_available_port(bind: BindAddress, start_port: u16) -> Result<u16, CoreError> {
+    (start_port..=u16::MAX)
+        .find(|port| check_bind_available(bind, *port).is_ok())
+        .ok_or_else(|| CoreError::PortForwarding("No available ports found".to_string()))
+}
+
+/// Bind listeners for `port` on every address `bind` listens on
+///
+/// # Errors
+/// Returns an error if the required address, or an available optional one, cannot be bound
+pub async fn bind_listeners(bind: BindAddress, port: u16) -> io::Result<Vec<TcpListener>> {
+    let mut listeners = Vec::new();
+    for (index, ip) in bind.addresses().into_iter().enumerate() {
+        match TcpListener::bind(SocketAddr::new(ip, port)).await {
+            Ok(listener) => listeners.push(listener),
+            Err(e) if index > 0 && is_family_unavailable(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(listeners)
+}
+
+/// Accept the next connection on any of `listeners`
+///
+/// # Errors
+/// Returns the first accept error
+pub async fn accept_any(listeners: &[TcpListener]) -> io::Result<(TcpStream, SocketAddr)> {
+    let (result, _, _) = select_all(listeners.iter().map(|l| Box::pin(l.accept()))).await;
+    result
+}
@@ -0,0 +1,326 @@
+// HTTP traffic capture
+//
+// This module records HTTP/1.1 exchanges flowing through a forward when inspection is
+// turned on. Each connection gets a tap that parses both directions of the copy loop and
+// pairs requests with responses; finished exchanges go into a bounded ring buffer shared
+// through the forward state.
+
+use crate::api::kubernetes::portforwarding::http::{HttpHead, HttpParser, MessageKind, ParseEvent};
+use crate::api::kubernetes::portforwarding::metrics::Direction;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How a forward's HTTP traffic is captured
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureConfig {
+    /// Exchanges kept before the oldest are dropped
+    pub max_entries: usize,
+    /// Keep request and response bodies as well as metadata
+    pub capture_bodies: bool,
+    /// Bytes kept of each body when bodies are captured
+    pub max_body_bytes: usize,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 200,
+            capture_bodies: false,
+            max_body_bytes: 64 * 1024,
+        }
+    }
+}
+
+impl CaptureConfig {
+    #[must_use]
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    #[must_use]
+    pub fn with_bodies(mut self, capture_bodies: bool) -> Self {
+        self.capture_bodies = capture_bodies;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+}
+
+/// One request and its response as seen through a forward
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpExchange {
+    /// Position in the forward's capture, increasing from 1
+    pub id: u64,
+    pub started_at: SystemTime,
+    pub method: String,
+    /// Request target as sent, usually a path and query
+    pub target: String,
+    pub http_version: String,
+    pub request_headers: Vec<(String, String)>,
+    pub request_body_size: u64,
+    /// Captured request body, truncated to the body limit; `None` unless bodies are captured
+    pub request_body: Option<Vec<u8>>,
+    pub status: u16,
+    pub status_text: String,
+    pub response_headers: Vec<(String, String)>,
+    pub response_body_size: u64,
+    pub response_body: Option<Vec<u8>>,
+    /// From the request head to the response head
+    pub wait: Duration,
+    /// From the request head to the end of the response
+    pub latency: Duration,
+}
+
+impl HttpExchange {
+    /// First value of a request header, matched case-insensitively
+    #[must_use]
+    pub fn request_header(&self, name: &str) -> Option<&str> {
+        find_header(&self.request_headers, name)
+    }
+
+    /// First value of a response header, matched case-insensitively
+    #[must_use]
+    pub fn response_header(&self, name: &str) -> Option<&str> {
+        find_header(&self.response_headers, name)
+    }
+
+    /// Absolute URL of the request, using its `Host` header
+    #[must_use]
+    pub fn url(&self) -> String {
+        if self.target.starts_with("http://") || self.target.starts_with("https://") {
+            return self.target.clone();
+        }
+        let host = self.request_header("host").unwrap_or("localhost");
+        format!("http://{host}{}", self.target)
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+#[derive(Debug, Default)]
+struct CaptureBuffer {
+    config: Option<CaptureConfig>,
+    exchanges: VecDeque<HttpExchange>,
+    recorded: u64,
+}
+
+/// A forward's captured exchanges; capture is off while no config is set
+#[derive(Debug, Default)]
+pub struct HttpCapture {
+    buffer: Mutex<CaptureBuffer>,
+}
+
+impl HttpCapture {
+    #[must_use]
+    pub fn new(config: Option<CaptureConfig>) -> Self {
+        Self {
+            buffer: Mutex::new(CaptureBuffer {
+                config,
+                ..CaptureBuffer::default()
+            }),
+        }
+    }
+
+    /// Turn capture on with `config`, or off with `None`; captured exchanges are kept
+    pub fn set_config(&self, config: Option<CaptureConfig>) {
+        let mut buffer = self.lock();
+        if let Some(config) = &config {
+            let excess = buffer.exchanges.len().saturating_sub(config.max_entries);
+            buffer.exchanges.drain(..excess);
+        }
+        buffer.config = config;
+    }
+
+    #[must_use]
+    pub fn config(&self) -> Option<CaptureConfig> {
+        self.lock().config.clone()
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.lock().config.is_some()
+    }
+
+    /// Add a finished exchange, dropping the oldest once the buffer is full
+    ///
+    /// Exchanges finishing after capture was turned off are discarded.
+    pub fn record(&self, mut exchange: HttpExchange) {
+        let mut buffer = self.lock();
+        let Some(max_entries) = buffer.config.as_ref().map(|c| c.max_entries) else {
+            return;
+        };
+        buffer.recorded += 1;
+        exchange.id = buffer.recorded;
+        buffer.exchanges.push_back(exchange);
+        let excess = buffer.exchanges.len().saturating_sub(max_entries);
+        buffer.exchanges.drain(..excess);
+    }
+
+    /// Captured exchanges, oldest first
+    #[must_use]
+    pub fn exchanges(&self) -> Vec<HttpExchange> {
+        self.lock().exchanges.iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.lock().exchanges.clear();
+    }
+
+    /// Start tapping a new connection, or `None` while capture is off
+    #[must_use]
+    pub fn tap(self: &Arc<Self>) -> Option<ConnectionTap> {
+        let config = self.config()?;
+        Some(ConnectionTap::new(Arc::clone(self), &config))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CaptureBuffer> {
+        // A panic while holding the lock can't leave the buffer inconsistent
+        self.buffer
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// A request seen on a connection whose response hasn't finished yet
+#[derive(Debug)]
+struct PendingExchange {
+    started_at: SystemTime,
+    started: Instant,
+    request: HttpHead,
+    request_body: Option<(Vec<u8>, u64)>,
+    response: Option<(HttpHead, Duration)>,
+}
+
+/// Parses both directions of one connection and records its finished exchanges
+///
+/// Connections that turn out not to carry HTTP/1.1 are passed through unrecorded.
+#[derive(Debug)]
+pub struct ConnectionTap {
+    capture: Arc<HttpCapture>,
+    capture_bodies: bool,
+    requests: HttpParser,
+    responses: HttpParser,
+    pending: VecDeque<PendingExchange>,
+}
+
+impl ConnectionTap {
+    fn new(capture: Arc<HttpCapture>, config: &CaptureConfig) -> Self {
+        let max_body = if config.capture_bodies {
+            config.max_body_bytes
+        } else {
+            0
+        };
+        Self {
+            capture,
+            capture_bodies: config.capture_bodies,
+            requests: HttpParser::new(MessageKind::Request, max_body),
+            responses: HttpParser::new(MessageKind::Response, max_body),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Feed bytes copied in `direction`
+    pub fn observe(&mut self, direction: Direction, data: &[u8]) {
+        match direction {
+            Direction::Sent => {
+                for event in self.requests.feed(data) {
+                    self.on_request(event);
+                }
+            }
+            Direction::Received => {
+                for event in self.responses.feed(data) {
+                    self.on_response(event);
+                }
+            }
+        }
+    }
+
+    /// The connection closed: finish a response that was delimited by the close
+    pub fn finish(&mut self) {
+        if let Some(event) = self.responses.finish() {
+            self.on_response(event);
+        }
+    }
+
+    fn on_request(&mut self, event: ParseEvent) {
+        match event {
+            ParseEvent::Head(head) => {
+                let is_head = head
+                    .request_line()
+                    .is_some_and(|(method, _, _)| method.eq_ignore_ascii_case("HEAD"));
+                self.responses.expect_response(is_head);
+                self.pending.push_back(PendingExchange {
+                    started_at: SystemTime::now(),
+                    started: Instant::now(),
+                    request: head,
+                    request_body: None,
+                    response: None,
+                });
+            }
+            ParseEvent::Complete { body, size } => {
+                if let Some(pending) = self.pending.iter_mut().find(|p| p.request_body.is_none()) {
+                    pending.request_body = Some((body, size));
+                }
+            }
+        }
+    }
+
+    fn on_response(&mut self, event: ParseEvent) {
+        match event {
+            ParseEvent::Head(head) => {
+                if let Some(pending) = self.pending.iter_mut().find(|p| p.response.is_none()) {
+                    let wait = pending.started.elapsed();
+                    pending.response = Some((head, wait));
+                }
+            }
+            ParseEvent::Complete { body, size } => {
+                let Some(pending) = self.pending.pop_front() else {
+                    return;
+                };
+                if let Some(exchange) = self.exchange(pending, body, size) {
+                    self.capture.record(exchange);
+                }
+            }
+        }
+    }
+
+    fn exchange(
+        &self,
+        pending: PendingExchange,
+        response_body: Vec<u8>,
+        response_body_size: u64,
+    ) -> Option<HttpExchange> {
+        let (method, target, http_version) = pending.request.request_line()?;
+        let (response, wait) = pending.response?;
+        let (_, status, status_text) = response.status_line()?;
+        let (request_body, request_body_size) = pending.request_body.unwrap_or_default();
+        Some(HttpExchange {
+            id: 0,
+            started_at: pending.started_at,
+            method: method.to_string(),
+            target: target.to_string(),
+            http_version: http_version.to_string(),
+            request_body_size,
+            request_body: self.capture_bodies.then_some(request_body),
+            status,
+            status_text: status_text.to_string(),
+            response_body_size,
+            response_body: self.capture_bodies.then_some(response_body),
+            wait,
+            latency: pending.started.elapsed(),
+            response_headers: response.headers,
+            request_headers: pending.request.headers,
+        })
+    }
+}
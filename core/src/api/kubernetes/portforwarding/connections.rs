@@ -0,0 +1,63 @@
+// Connection tracking for port forwarding
+//
+// This module keeps the connection tasks of a forward together so stopping the forward
+// can let them finish, up to a drain timeout, before closing the rest.
+
+use crate::api::kubernetes::portforwarding::types::StopSummary;
+use std::future::Future;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+/// Connection tasks in flight on a forward
+#[derive(Debug, Default)]
+pub struct ConnectionSet {
+    tasks: Mutex<JoinSet<()>>,
+}
+
+impl ConnectionSet {
+    /// Run a connection task, tracking it until it finishes
+    pub fn spawn<F>(&self, connection: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut tasks = self.tasks.lock().unwrap_or_else(PoisonError::into_inner);
+        // Reap finished connections so long-running forwards don't accumulate them
+        while tasks.try_join_next().is_some() {}
+        tasks.spawn(connection);
+    }
+
+    /// Number of connections still running
+    #[must_use]
+    pub fn len(&self) -> usize {
+        let mut tasks = self.tasks.lock().unwrap_or_else(PoisonError::into_inner);
+        while tasks.try_join_next().is_some() {}
+        tasks.len()
+    }
+
+    /// Whether no connections are running
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Wait up to `timeout` for running connections to finish, then abort the rest
+    pub async fn drain(&self, timeout: Duration) -> StopSummary {
+        let mut tasks =
+            std::mem::take(&mut *self.tasks.lock().unwrap_or_else(PoisonError::into_inner));
+        while tasks.try_join_next().is_some() {}
+        let open = tasks.len();
+
+        let _ = tokio::time::timeout(timeout, async {
+            while tasks.join_next().await.is_some() {}
+        })
+        .await;
+
+        let force_closed = tasks.len();
+        tasks.abort_all();
+        StopSummary {
+            drained: open - force_closed,
+            force_closed,
+        }
+    }
+}
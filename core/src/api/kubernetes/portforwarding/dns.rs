@@ -0,0 +1,217 @@
+// Local DNS responder for service names
+//
+// This module answers DNS queries over UDP for the host names of services forwarded on
+// dedicated addresses, so clients configured for in-cluster names (`postgres.db.svc`) reach
+// the local forwards unchanged. Only A records are served; other names get NXDOMAIN, so the
+// responder should only be consulted for cluster names, e.g. through a resolver
+// configuration for `svc.cluster.local` or a split-DNS setting. One responder serves every
+// context; it starts on `DEFAULT_DNS_ADDRESS` with the first forward on a dedicated address.
+
+use crate::api::kubernetes::portforwarding::loopback::{service_hosts, ServiceHost};
+use crate::api::kubernetes::portforwarding_registry::list_all_forwards;
+use crate::errors::CoreError;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Mutex, PoisonError};
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+/// Address the DNS responder listens on unless configured otherwise
+pub const DEFAULT_DNS_ADDRESS: SocketAddr =
+    SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::LOCALHOST), 1053);
+
+/// Seconds clients may cache answers; short because forwards come and go
+const ANSWER_TTL: u32 = 5;
+
+const HEADER_LEN: usize = 12;
+const TYPE_A: u16 = 1;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_AUTHORITATIVE: u16 = 0x0400;
+const FLAG_RECURSION_DESIRED: u16 = 0x0100;
+const OPCODE_MASK: u16 = 0x7800;
+const RCODE_FORMAT_ERROR: u16 = 1;
+const RCODE_NAME_ERROR: u16 = 3;
+const RCODE_NOT_IMPLEMENTED: u16 = 4;
+
+/// Build the response to a DNS query from the known `hosts`
+///
+/// Returns `None` for packets that are responses or too short to answer, which are dropped.
+#[must_use]
+pub fn dns_response(query: &[u8], hosts: &[ServiceHost]) -> Option<Vec<u8>> {
+    if query.len() < HEADER_LEN {
+        return None;
+    }
+    let flags = u16::from_be_bytes([query[2], query[3]]);
+    if flags & FLAG_RESPONSE != 0 {
+        return None;
+    }
+    let reply_flags =
+        FLAG_RESPONSE | FLAG_AUTHORITATIVE | (flags & (OPCODE_MASK | FLAG_RECURSION_DESIRED));
+    let error = |rcode: u16| {
+        let mut response = query[..HEADER_LEN].to_vec();
+        response[2..4].copy_from_slice(&(reply_flags | rcode).to_be_bytes());
+        // No question, answer, authority or additional records
+        response[4..12].fill(0);
+        Some(response)
+    };
+
+    let question_count = u16::from_be_bytes([query[4], query[5]]);
+    if flags & OPCODE_MASK != 0 {
+        return error(RCODE_NOT_IMPLEMENTED);
+    }
+    if question_count != 1 {
+        return error(RCODE_FORMAT_ERROR);
+    }
+    let Some((name, question_end)) = read_name(query, HEADER_LEN) else {
+        return error(RCODE_FORMAT_ERROR);
+    };
+    let Some(fixed) = query.get(question_end..question_end + 4) else {
+        return error(RCODE_FORMAT_ERROR);
+    };
+    let query_type = u16::from_be_bytes([fixed[0], fixed[1]]);
+    let query_class = u16::from_be_bytes([fixed[2], fixed[3]]);
+    let question = &query[HEADER_LEN..question_end + 4];
+
+    let name = name.trim_end_matches('.');
+    let Some(host) = hosts
+        .iter()
+        .find(|host| host.name.eq_ignore_ascii_case(name))
+    else {
+        return error(RCODE_NAME_ERROR).map(|mut response| {
+            // Echo the question so resolvers match the answer to it
+            response[4..6].copy_from_slice(&1u16.to_be_bytes());
+            response.extend_from_slice(question);
+            response
+        });
+    };
+
+    // Known names without an A record of the asked type get an empty answer
+    let answers =
+        u16::from((query_type == TYPE_A || query_type == TYPE_ANY) && query_class == CLASS_IN);
+    let mut response = Vec::with_capacity(HEADER_LEN + question.len() + 16);
+    response.extend_from_slice(&query[..2]);
+    response.extend_from_slice(&reply_flags.to_be_bytes());
+    for count in [1, answers, 0, 0] {
+        response.extend_from_slice(&count.to_be_bytes());
+    }
+    response.extend_from_slice(question);
+    if answers == 1 {
+        // Pointer to the name in the question
+        response.extend_from_slice(&[0xc0, 0x0c]);
+        response.extend_from_slice(&TYPE_A.to_be_bytes());
+        response.extend_from_slice(&CLASS_IN.to_be_bytes());
+        response.extend_from_slice(&ANSWER_TTL.to_be_bytes());
+        response.extend_from_slice(&4u16.to_be_bytes());
+        response.extend_from_slice(&host.ip.octets());
+    }
+    Some(response)
+}
+
+/// Read an uncompressed name starting at `offset`, returning it and the offset after it
+fn read_name(packet: &[u8], mut offset: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    loop {
+        let len = usize::from(*packet.get(offset)?);
+        offset += 1;
+        if len == 0 {
+            return Some((labels.join("."), offset));
+        }
+        // Questions are never compressed, and labels are at most 63 bytes
+        if len > 63 {
+            return None;
+        }
+        let label = packet.get(offset..offset + len)?;
+        labels.push(String::from_utf8(label.to_vec()).ok()?);
+        offset += len;
+    }
+}
+
+/// The running responder, if any; it serves the forwards of every context
+static DNS_SERVER: Mutex<Option<RunningDnsServer>> = Mutex::new(None);
+
+/// A DNS responder serving in the background
+struct RunningDnsServer {
+    address: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+/// Host names of the dedicated-address service forwards running in every context
+pub async fn current_service_hosts() -> Vec<ServiceHost> {
+    service_hosts(&list_all_forwards().await)
+}
+
+/// Start answering DNS queries for service host names on `address`
+///
+/// Returns the bound address, which differs from `address` when it asks for port 0.
+///
+/// # Errors
+/// Returns an error if the responder is already running or `address` cannot be bound
+pub async fn start_dns_server(address: SocketAddr) -> Result<SocketAddr, CoreError> {
+    if dns_server_address().is_some() {
+        return Err(CoreError::PortForwarding(
+            "DNS responder is already running".to_string(),
+        ));
+    }
+    let socket = UdpSocket::bind(address).await.map_err(|e| {
+        CoreError::PortForwarding(format!("Failed to bind DNS responder on {address}: {e}"))
+    })?;
+    let address = socket.local_addr().unwrap_or(address);
+
+    let task = tokio::spawn(async move {
+        let mut buffer = [0u8; 512];
+        loop {
+            let (len, peer) = match socket.recv_from(&mut buffer).await {
+                Ok(received) => received,
+                Err(e) => {
+                    eprintln!("[PortForward] DNS responder failed to receive: {e}");
+                    continue;
+                }
+            };
+            let hosts = current_service_hosts().await;
+            if let Some(response) = dns_response(&buffer[..len], &hosts) {
+                if let Err(e) = socket.send_to(&response, peer).await {
+                    eprintln!("[PortForward] DNS responder failed to reply to {peer}: {e}");
+                }
+            }
+        }
+    });
+
+    let mut running = DNS_SERVER.lock().unwrap_or_else(PoisonError::into_inner);
+    if running.is_some() {
+        // Another caller started the responder while this one was binding
+        task.abort();
+        return Err(CoreError::PortForwarding(
+            "DNS responder is already running".to_string(),
+        ));
+    }
+    println!("[PortForward] DNS responder listening on {address}");
+    *running = Some(RunningDnsServer { address, task });
+    Ok(address)
+}
+
+/// Stop the DNS responder; returns whether it was running
+pub fn stop_dns_server() -> bool {
+    let running = DNS_SERVER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+    match running {
+        Some(running) => {
+            running.task.abort();
+            println!("[PortForward] DNS responder stopped");
+            true
+        }
+        None => false,
+    }
+}
+
+/// Address the DNS responder listens on, or `None` if it is not running
+#[must_use]
+pub fn dns_server_address() -> Option<SocketAddr> {
+    DNS_SERVER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map(|running| running.address)
+}
@@ -0,0 +1,70 @@
+// Port forwarding events
+//
+// This module defines the typed events broadcast by the PortForwardingManager so that
+// the GUI, tray and CLI can react to forward changes instead of polling.
+
+use crate::api::kubernetes::portforwarding::types::{PortForwardingState, PortForwardingStatus};
+use tokio::sync::broadcast;
+
+/// Number of events buffered per subscriber before slow subscribers start lagging
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+pub type EventSender = broadcast::Sender<PortForwardingEvent>;
+
+/// Change to a port forward, broadcast to all subscribers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortForwardingEvent {
+    /// A forward was registered and its task spawned
+    Started { forward_id: String },
+    /// A forward's status changed
+    StatusChanged {
+        forward_id: String,
+        status: PortForwardingStatus,
+    },
+    /// A local client connection was tunnelled to the pod
+    ConnectionOpened { forward_id: String },
+    /// A tunnelled client connection finished
+    ConnectionClosed { forward_id: String },
+    /// A forward was moved to a replacement pod
+    Retargeted {
+        forward_id: String,
+        from_pod: String,
+        to_pod: String,
+    },
+    /// A forward's traffic shaping was changed; `active` while traffic is degraded
+    ShapingChanged { forward_id: String, active: bool },
+    /// A forward was stopped and removed
+    Stopped { forward_id: String },
+}
+
+impl PortForwardingEvent {
+    /// Id of the forward this event relates to
+    #[must_use]
+    pub fn forward_id(&self) -> &str {
+        match self {
+            Self::Started { forward_id }
+            | Self::StatusChanged { forward_id, .. }
+            | Self::ConnectionOpened { forward_id }
+            | Self::ConnectionClosed { forward_id }
+            | Self::Retargeted { forward_id, .. }
+            | Self::ShapingChanged { forward_id, .. }
+            | Self::Stopped { forward_id } => forward_id,
+        }
+    }
+}
+
+/// Set a forward's status, broadcasting `StatusChanged` when it actually changes
+pub fn set_status(
+    state: &mut PortForwardingState,
+    status: PortForwardingStatus,
+    events: &EventSender,
+) {
+    if state.status != status {
+        state.status = status.clone();
+        // Sending only fails when nobody is subscribed
+        let _ = events.send(PortForwardingEvent::StatusChanged {
+            forward_id: state.id.clone(),
+            status,
+        });
+    }
+}
@@ -0,0 +1,272 @@
+// Pod failover for port forwarding
+//
+// This module watches the pods behind a port forward and retargets the forward to a
+// replacement ready pod when the current one goes away, keeping the local listener bound.
+
+use crate::api::kubernetes::portforwarding::events::{
+    set_status, EventSender, PortForwardingEvent,
+};
+use crate::api::kubernetes::portforwarding::pods::label_selector;
+use crate::api::kubernetes::portforwarding::types::{
+    ForwardTarget, PortForwardingConfig, PortForwardingState, PortForwardingStatus,
+};
+use crate::api::kubernetes::portforwarding::workload::{ready_pod_names, workload_selector};
+use futures::StreamExt;
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet};
+use k8s_openapi::api::core::v1::{Pod, Service};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{LabelSelector, ObjectMeta, OwnerReference};
+use kube::api::{Api, ListParams};
+use kube::runtime::watcher::Event;
+use kube::runtime::{watcher, WatchStreamExt};
+use kube::Client;
+use roro_domain::ResourceKind;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+pub type FailoverTaskMap = Arc<RwLock<HashMap<String, JoinHandle<()>>>>;
+
+/// Outcome of comparing a forward's current pod against the pods that could replace it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailoverDecision {
+    /// The current pod is still ready
+    Keep,
+    /// The current pod is gone or unready; switch to the named ready pod
+    Retarget(String),
+    /// No ready pod is available yet
+    Unavailable,
+}
+
+/// Decide which pod a forward should use given the pods currently matching its selector
+#[must_use]
+pub fn failover_decision<'a>(
+    current_pod: &str,
+    pods: impl IntoIterator<Item = &'a Pod>,
+) -> FailoverDecision {
+    let ready = ready_pod_names(pods);
+
+    if ready.iter().any(|pod| pod == current_pod) {
+        return FailoverDecision::Keep;
+    }
+
+    ready
+        .into_iter()
+        .next()
+        .map_or(FailoverDecision::Unavailable, FailoverDecision::Retarget)
+}
+
+/// Determine the label selector identifying replacement pods for a forward
+///
+/// Service targets use the service selector. Pod targets use the selector of the pod's
+/// controlling workload, walking from a `ReplicaSet` up to its `Deployment` so rollouts
+/// (which change the pod-template hash) are followed. Standalone pods have no selector.
+/// Workload targets use the workload's selector, or only their pod for a `StatefulSet`
+/// ordinal; pod name prefixes are followed like pod targets.
+pub async fn failover_selector(client: &Client, config: &PortForwardingConfig) -> Option<String> {
+    let namespace = config.namespace.as_str();
+    let labels = match &config.target {
+        ForwardTarget::Workload {
+            kind,
+            name,
+            ordinal,
+            ..
+        } if *kind != ResourceKind::Pod => {
+            return workload_selector(client, namespace, *kind, name, *ordinal)
+                .await
+                .ok()
+                .flatten();
+        }
+        ForwardTarget::Service { name, .. } => {
+            let services: Api<Service> = Api::namespaced(client.clone(), namespace);
+            services.get(name).await.ok()?.spec?.selector?
+        }
+        ForwardTarget::Pod | ForwardTarget::Workload { .. } => {
+            let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+            let pod = pods.get(&config.pod).await.ok()?;
+            let owner = controller_of(&pod.metadata)?;
+            owner_selector(client, namespace, owner)
+                .await?
+                .match_labels?
+        }
+    };
+
+    (!labels.is_empty()).then(|| label_selector(&labels))
+}
+
+fn controller_of(metadata: &ObjectMeta) -> Option<&OwnerReference> {
+    metadata
+        .owner_references
+        .as_ref()?
+        .iter()
+        .find(|owner| owner.controller == Some(true))
+}
+
+async fn owner_selector(
+    client: &Client,
+    namespace: &str,
+    owner: &OwnerReference,
+) -> Option<LabelSelector> {
+    match owner.kind.as_str() {
+        "ReplicaSet" => {
+            let replica_sets: Api<ReplicaSet> = Api::namespaced(client.clone(), namespace);
+            let replica_set = replica_sets.get(&owner.name).await.ok()?;
+            match controller_of(&replica_set.metadata) {
+                Some(parent) if parent.kind == "Deployment" => {
+                    let deployments: Api<Deployment> = Api::namespaced(client.clone(), namespace);
+                    Some(deployments.get(&parent.name).await.ok()?.spec?.selector)
+                }
+                _ => Some(replica_set.spec?.selector),
+            }
+        }
+        "StatefulSet" => {
+            let stateful_sets: Api<StatefulSet> = Api::namespaced(client.clone(), namespace);
+            Some(stateful_sets.get(&owner.name).await.ok()?.spec?.selector)
+        }
+        "DaemonSet" => {
+            let daemon_sets: Api<DaemonSet> = Api::namespaced(client.clone(), namespace);
+            Some(daemon_sets.get(&owner.name).await.ok()?.spec?.selector)
+        }
+        _ => None,
+    }
+}
+
+/// Spawn a watcher that keeps a forward pointed at a ready pod matching `selector`
+///
+/// `forward_id` may name a forward group, in which case all members move together.
+/// The watcher exits once the forward, or every member of the group, is removed from `forwards`.
+pub fn spawn_failover_watcher(
+    client: Client,
+    forwards: Arc<RwLock<HashMap<String, PortForwardingState>>>,
+    events: EventSender,
+    forward_id: String,
+    namespace: String,
+    selector: String,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let pods: Api<Pod> = Api::namespaced(client, &namespace);
+        let stream = watcher(pods, watcher::Config::default().labels(&selector)).default_backoff();
+        let mut stream = std::pin::pin!(stream);
+
+        let mut known: HashMap<String, Pod> = HashMap::new();
+        let mut synced = false;
+
+        while let Some(event) = stream.next().await {
+            match event {
+                Ok(Event::Init) => {
+                    known.clear();
+                    synced = false;
+                }
+                Ok(Event::InitApply(pod) | Event::Apply(pod)) => {
+                    if let Some(name) = pod.metadata.name.clone() {
+                        known.insert(name, pod);
+                    }
+                }
+                Ok(Event::Delete(pod)) => {
+                    if let Some(name) = &pod.metadata.name {
+                        known.remove(name);
+                    }
+                }
+                Ok(Event::InitDone) => synced = true,
+                Err(e) => {
+                    eprintln!("[PortForward] Pod watch for {forward_id} failed: {e}");
+                    continue;
+                }
+            }
+
+            // Wait for the initial listing so a partially populated view doesn't trigger a retarget
+            if synced && !apply_failover(&forwards, &events, &forward_id, &known).await {
+                return;
+            }
+        }
+    })
+}
+
+/// Move a forward to a ready pod after its tunnel failed a health check
+///
+/// Members of a group move the whole group. Does nothing for forwards to standalone pods,
+/// which have no replacement selector.
+pub async fn recover_forward(
+    client: &Client,
+    forwards: &Arc<RwLock<HashMap<String, PortForwardingState>>>,
+    events: &EventSender,
+    forward_id: &str,
+) {
+    let Some(config) = forwards
+        .read()
+        .await
+        .get(forward_id)
+        .map(|state| state.config.clone())
+    else {
+        return;
+    };
+    let Some(selector) = failover_selector(client, &config).await else {
+        return;
+    };
+
+    let pods: Api<Pod> = Api::namespaced(client.clone(), &config.namespace);
+    match pods.list(&ListParams::default().labels(&selector)).await {
+        Ok(pod_list) => {
+            let known = pod_list
+                .items
+                .into_iter()
+                .filter_map(|pod| Some((pod.metadata.name.clone()?, pod)))
+                .collect();
+            let target = config.group_id.as_deref().unwrap_or(forward_id);
+            apply_failover(forwards, events, target, &known).await;
+        }
+        Err(e) => eprintln!("[PortForward] Failed to list pods to recover {forward_id}: {e}"),
+    }
+}
+
+/// Apply a failover decision to a forward, or to every member of a group
+///
+/// Returns `false` once no matching forward remains.
+async fn apply_failover(
+    forwards: &Arc<RwLock<HashMap<String, PortForwardingState>>>,
+    events: &EventSender,
+    target: &str,
+    known: &HashMap<String, Pod>,
+) -> bool {
+    let mut f = forwards.write().await;
+    let mut members: Vec<&mut PortForwardingState> = f
+        .values_mut()
+        .filter(|state| state.id == target || state.config.group_id.as_deref() == Some(target))
+        .collect();
+    let Some(current_pod) = members.first().map(|state| state.config.pod.clone()) else {
+        return false;
+    };
+
+    let decision = failover_decision(&current_pod, known.values());
+    let ready = ready_pod_names(known.values());
+    for state in &mut members {
+        if state.config.target.is_round_robin() {
+            state.ready_pods.clone_from(&ready);
+        }
+        match &decision {
+            FailoverDecision::Keep => {}
+            FailoverDecision::Retarget(pod) => {
+                println!(
+                    "[PortForward] Retargeting {} from {} to {pod}",
+                    state.id, state.config.pod
+                );
+                let from_pod = std::mem::replace(&mut state.config.pod, pod.clone());
+                let _ = events.send(PortForwardingEvent::Retargeted {
+                    forward_id: state.id.clone(),
+                    from_pod,
+                    to_pod: pod.clone(),
+                });
+                if state.status == PortForwardingStatus::Reconnecting {
+                    set_status(state, PortForwardingStatus::Active, events);
+                }
+            }
+            FailoverDecision::Unavailable => {
+                if state.status == PortForwardingStatus::Active {
+                    set_status(state, PortForwardingStatus::Reconnecting, events);
+                }
+            }
+        }
+    }
+
+    true
+}
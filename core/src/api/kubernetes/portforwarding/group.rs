@@ -0,0 +1,87 @@
+// Forward groups
+//
+// This module defines the port mappings of a forward group, which forwards several remote
+// ports of one pod under a single id, and how a group's status is summarised.
+
+use crate::api::kubernetes::portforwarding::types::PortForwardingStatus;
+use crate::errors::CoreError;
+use std::collections::HashSet;
+
+/// One remote port of a forward group and the local port it is reachable on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortMapping {
+    pub remote_port: u16,
+    pub local_port: u16,
+}
+
+impl PortMapping {
+    #[must_use]
+    pub fn new(remote_port: u16, local_port: u16) -> Self {
+        Self {
+            remote_port,
+            local_port,
+        }
+    }
+}
+
+/// Check that a group has ports and that no local or remote port appears twice
+///
+/// # Errors
+/// Returns `CoreError::Validation` describing the first problem found
+pub fn validate_port_mappings(ports: &[PortMapping]) -> Result<(), CoreError> {
+    if ports.is_empty() {
+        return Err(CoreError::Validation(
+            "A forward group needs at least one port".to_string(),
+        ));
+    }
+
+    let mut local_ports = HashSet::new();
+    let mut remote_ports = HashSet::new();
+    for mapping in ports {
+        if mapping.local_port == 0 || mapping.remote_port == 0 {
+            return Err(CoreError::Validation(
+                "Forward group ports must be greater than 0".to_string(),
+            ));
+        }
+        if !local_ports.insert(mapping.local_port) {
+            return Err(CoreError::Validation(format!(
+                "Local port {} is mapped more than once",
+                mapping.local_port
+            )));
+        }
+        if !remote_ports.insert(mapping.remote_port) {
+            return Err(CoreError::Validation(format!(
+                "Remote port {} is mapped more than once",
+                mapping.remote_port
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Id of the group forwarding `ports` of `pod` for an instance
+#[must_use]
+pub fn group_id(instance_id: &str, pod: &str, ports: &[PortMapping]) -> String {
+    let local_ports: Vec<String> = ports
+        .iter()
+        .map(|mapping| mapping.local_port.to_string())
+        .collect();
+    format!("{instance_id}-{pod}-group-{}", local_ports.join("-"))
+}
+
+/// Summarise member statuses, reporting the least healthy one
+///
+/// Returns `None` for a group without members.
+#[must_use]
+pub fn group_status<'a>(
+    statuses: impl IntoIterator<Item = &'a PortForwardingStatus>,
+) -> Option<PortForwardingStatus> {
+    let severity = |status: &PortForwardingStatus| match status {
+        PortForwardingStatus::Active => 0,
+        PortForwardingStatus::Idle => 1,
+        PortForwardingStatus::Connecting => 2,
+        PortForwardingStatus::Reconnecting => 3,
+        PortForwardingStatus::Failed => 4,
+    };
+    statuses.into_iter().max_by_key(|s| severity(s)).cloned()
+}
@@ -0,0 +1,143 @@
+// HAR export
+//
+// This module converts captured HTTP exchanges into an HTTP Archive (HAR 1.2) log that
+// browsers and HTTP tools can import.
+
+use crate::api::kubernetes::portforwarding::capture::HttpExchange;
+use serde_json::{json, Value};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Build a HAR 1.2 document from captured exchanges
+///
+/// Bodies are included as text when they were captured and are valid UTF-8.
+#[must_use]
+pub fn har_log(exchanges: &[HttpExchange]) -> Value {
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": {
+                "name": "roro-kube",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "entries": exchanges.iter().map(har_entry).collect::<Vec<_>>(),
+        }
+    })
+}
+
+fn har_entry(exchange: &HttpExchange) -> Value {
+    let wait = millis(exchange.wait);
+    let mut request = json!({
+        "method": exchange.method,
+        "url": exchange.url(),
+        "httpVersion": exchange.http_version,
+        "cookies": [],
+        "headers": har_headers(&exchange.request_headers),
+        "queryString": har_query(&exchange.target),
+        "headersSize": -1,
+        "bodySize": exchange.request_body_size,
+    });
+    if let Some(text) = body_text(exchange.request_body.as_deref()) {
+        request["postData"] = json!({
+            "mimeType": exchange.request_header("content-type").unwrap_or_default(),
+            "text": text,
+        });
+    }
+
+    let mut content = json!({
+        "size": exchange.response_body_size,
+        "mimeType": exchange.response_header("content-type").unwrap_or_default(),
+    });
+    if let Some(text) = body_text(exchange.response_body.as_deref()) {
+        content["text"] = json!(text);
+    }
+
+    json!({
+        "startedDateTime": rfc3339(exchange.started_at),
+        "time": millis(exchange.latency),
+        "request": request,
+        "response": {
+            "status": exchange.status,
+            "statusText": exchange.status_text,
+            "httpVersion": exchange.http_version,
+            "cookies": [],
+            "headers": har_headers(&exchange.response_headers),
+            "content": content,
+            "redirectURL": exchange.response_header("location").unwrap_or_default(),
+            "headersSize": -1,
+            "bodySize": exchange.response_body_size,
+        },
+        "cache": {},
+        "timings": {
+            "send": 0,
+            "wait": wait,
+            "receive": (millis(exchange.latency) - wait).max(0.0),
+        },
+    })
+}
+
+fn har_headers(headers: &[(String, String)]) -> Vec<Value> {
+    headers
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+/// Query parameters of a request target, left percent-encoded
+fn har_query(target: &str) -> Vec<Value> {
+    let Some((_, query)) = target.split_once('?') else {
+        return Vec::new();
+    };
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            json!({ "name": name, "value": value })
+        })
+        .collect()
+}
+
+fn body_text(body: Option<&[u8]>) -> Option<&str> {
+    body.filter(|b| !b.is_empty())
+        .and_then(|b| std::str::from_utf8(b).ok())
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Format a time as an RFC 3339 UTC timestamp with millisecond precision
+#[must_use]
+pub fn rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let days = i64::try_from(secs / 86_400).unwrap_or(i64::MAX);
+    let (year, month, day) = civil_from_days(days);
+    let time_of_day = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Convert days since the Unix epoch to a proleptic Gregorian date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (
+        year,
+        u32::try_from(month).unwrap_or(1),
+        u32::try_from(day).unwrap_or(1),
+    )
+}
@@ -1,32 +1,187 @@
 // Health checking for port forwarding
 //
-// This module provides health checking functionality for port forwarding connections.
-
-use crate::api::kubernetes::portforwarding::types::{PortForwardingState, PortForwardingStatus};
-use std::collections::HashMap;
-use std::net::TcpStream;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-
-/// Check if a port forward is healthy by attempting to connect to the local port
-pub async fn health_check_forward(
-    forwards: &Arc<RwLock<HashMap<String, PortForwardingState>>>,
-    forward_id: &str,
+// This module checks port forwards end to end by opening a stream to the remote port
+// through the Kubernetes API and optionally running an HTTP probe over it. Service forwards
+// relaying through the service proxy are checked with a request through the proxy instead.
+
+use crate::api::kubernetes::portforwarding::events::{set_status, EventSender};
+use crate::api::kubernetes::portforwarding::service_proxy::ServiceProxy;
+use crate::api::kubernetes::portforwarding::types::{
+    ForwardTarget, HealthStatus, PortForwardingConfig, PortForwardingState, PortForwardingStatus,
+};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::Api;
+use kube::Client;
+use roro_domain::{ForwardTransport, HealthCheckConfig};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Largest HTTP response head read while looking for the status line
+const MAX_STATUS_LINE: usize = 1024;
+
+/// Record a health check result on a forward; returns whether it needs failover
+///
+/// A failed check is counted in `health_failures` and marks the forward `Reconnecting`. A
+/// healthy check makes it `Active` again only if failed checks put it there: the check
+/// opens a tunnel of its own, so it says nothing about the listener of a forward the
+/// supervisor is restarting, and that forward's status is left to the supervisor.
+pub fn record_health(
+    state: &mut PortForwardingState,
+    health: HealthStatus,
+    events: &EventSender,
 ) -> bool {
-    let state = {
-        let f = forwards.read().await;
-        f.get(forward_id).cloned()
+    let restarting =
+        state.status == PortForwardingStatus::Reconnecting && state.health_failures == 0;
+    state.health = Some(health.clone());
+    match health {
+        HealthStatus::Healthy => {
+            if state.health_failures > 0 {
+                state.health_failures = 0;
+                set_status(state, PortForwardingStatus::Active, events);
+            }
+            false
+        }
+        HealthStatus::Unhealthy { reason } => {
+            eprintln!(
+                "[PortForward] Health check failed for {}: {reason}",
+                state.id
+            );
+            state.last_error = Some(reason);
+            if !restarting {
+                state.health_failures += 1;
+                set_status(state, PortForwardingStatus::Reconnecting, events);
+            }
+            true
+        }
+    }
+}
+
+/// Check a forward by tunnelling to its current pod and running its probe
+///
+/// Forwards without a probe configured are healthy once the stream to the remote port opens.
+pub async fn health_check_forward(client: &Client, config: &PortForwardingConfig) -> HealthStatus {
+    let probe = config.health_check.clone().unwrap_or_default();
+    let timeout = Duration::from_secs(probe.timeout_seconds);
+
+    let check = async {
+        match &config.target {
+            ForwardTarget::Service { name, port }
+                if config.transport == ForwardTransport::ServiceProxy =>
+            {
+                let proxy = ServiceProxy::new(client.clone(), &config.namespace, name, port);
+                check_service_proxy(&proxy, &probe).await
+            }
+            _ => check_tunnel(client, config, &probe).await,
+        }
     };
+    tokio::time::timeout(timeout, check)
+        .await
+        .unwrap_or_else(|_| HealthStatus::Unhealthy {
+            reason: format!("Health check timed out after {}s", probe.timeout_seconds),
+        })
+}
 
-    if let Some(state) = state {
-        if state.status != PortForwardingStatus::Active {
-            return false;
+async fn check_tunnel(
+    client: &Client,
+    config: &PortForwardingConfig,
+    probe: &HealthCheckConfig,
+) -> HealthStatus {
+    let pods: Api<Pod> = Api::namespaced(client.clone(), &config.namespace);
+    let mut forwarder = match pods.portforward(&config.pod, &[config.remote_port]).await {
+        Ok(forwarder) => forwarder,
+        Err(e) => {
+            return HealthStatus::Unhealthy {
+                reason: format!("Failed to open tunnel to pod {}: {e}", config.pod),
+            }
         }
+    };
 
-        if TcpStream::connect(format!("127.0.0.1:{}", state.config.local_port)).is_ok() {
-            return true;
+    let Some(mut stream) = forwarder.take_stream(config.remote_port) else {
+        return HealthStatus::Unhealthy {
+            reason: format!("No stream for remote port {}", config.remote_port),
+        };
+    };
+
+    match &probe.path {
+        Some(path) => http_probe(&mut stream, path, probe.expected_status).await,
+        None => HealthStatus::Healthy,
+    }
+}
+
+/// Run a forward's probe through the service proxy
+///
+/// Without a probe path the root is requested, and any status but the ones the API server
+/// answers with when it can't reach the service counts as healthy.
+async fn check_service_proxy(proxy: &ServiceProxy, probe: &HealthCheckConfig) -> HealthStatus {
+    let path = probe.path.as_deref().unwrap_or("/");
+    match proxy.probe(path).await {
+        Ok(status) if probe.path.is_some() && status != probe.expected_status => {
+            HealthStatus::Unhealthy {
+                reason: format!(
+                    "HTTP probe {path} returned {status}, expected {}",
+                    probe.expected_status
+                ),
+            }
+        }
+        Ok(status @ (403 | 502 | 503 | 504)) if probe.path.is_none() => HealthStatus::Unhealthy {
+            reason: format!("Service proxy returned {status}"),
+        },
+        Ok(_) => HealthStatus::Healthy,
+        Err(e) => HealthStatus::Unhealthy {
+            reason: e.to_string(),
+        },
+    }
+}
+
+/// Send an HTTP GET for `path` over `stream` and compare the response status
+pub async fn http_probe<S>(stream: &mut S, path: &str, expected_status: u16) -> HealthStatus
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    if let Err(e) = stream.write_all(http_probe_request(path).as_bytes()).await {
+        return HealthStatus::Unhealthy {
+            reason: format!("Failed to send HTTP probe: {e}"),
+        };
+    }
+
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 256];
+    while !head.contains(&b'\n') && head.len() < MAX_STATUS_LINE {
+        match stream.read(&mut buffer).await {
+            Ok(0) => break,
+            Ok(read) => head.extend_from_slice(&buffer[..read]),
+            Err(e) => {
+                return HealthStatus::Unhealthy {
+                    reason: format!("Failed to read HTTP probe response: {e}"),
+                }
+            }
         }
     }
 
-    false
+    match parse_status_code(&String::from_utf8_lossy(&head)) {
+        Some(status) if status == expected_status => HealthStatus::Healthy,
+        Some(status) => HealthStatus::Unhealthy {
+            reason: format!("HTTP probe {path} returned {status}, expected {expected_status}"),
+        },
+        None => HealthStatus::Unhealthy {
+            reason: format!("HTTP probe {path} returned no valid status line"),
+        },
+    }
+}
+
+/// Build the request sent by an HTTP probe
+#[must_use]
+pub fn http_probe_request(path: &str) -> String {
+    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nUser-Agent: roro-kube\r\nConnection: close\r\n\r\n")
+}
+
+/// Parse the status code from the first line of an HTTP response
+#[must_use]
+pub fn parse_status_code(response: &str) -> Option<u16> {
+    let status_line = response.lines().next()?;
+    let mut parts = status_line.split_whitespace();
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+    parts.next()?.parse().ok()
 }
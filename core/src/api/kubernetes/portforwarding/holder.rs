@@ -0,0 +1,219 @@
+// Port holder lookup
+//
+// This module finds the local process listening on a port a forward wants, so a conflict
+// can name it. On Linux the listening socket's inode is read from `/proc/net/tcp` and
+// `/proc/net/tcp6` and matched against the socket links under each process's
+// `/proc/<pid>/fd`; elsewhere holders are not identified.
+
+use crate::errors::CoreError;
+use std::fmt;
+
+/// `st` value of a listening socket in `/proc/net/tcp`
+const TCP_LISTEN: &str = "0A";
+
+/// What kind of process holds a port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HolderKind {
+    /// Another roro process, such as a second GUI or a CLI forward
+    Roro,
+    /// A `kubectl port-forward`, often left over from a terminal session
+    KubectlPortForward,
+    /// Any other process
+    Other,
+}
+
+impl HolderKind {
+    /// Classify a process by its command line
+    #[must_use]
+    pub fn from_command(command: &str) -> Self {
+        let mut args = command.split_whitespace();
+        let program = args
+            .next()
+            .and_then(|path| path.rsplit(['/', '\\']).next())
+            .unwrap_or_default();
+        if program.starts_with("roro") {
+            Self::Roro
+        } else if program.trim_end_matches(".exe") == "kubectl"
+            && args.any(|arg| arg == "port-forward")
+        {
+            Self::KubectlPortForward
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// A local process listening on a port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortHolder {
+    pub pid: u32,
+    /// Command line with arguments separated by spaces
+    pub command: String,
+    pub kind: HolderKind,
+}
+
+impl PortHolder {
+    #[must_use]
+    pub fn new(pid: u32, command: String) -> Self {
+        let kind = HolderKind::from_command(&command);
+        Self { pid, command, kind }
+    }
+}
+
+impl fmt::Display for PortHolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            HolderKind::Roro => write!(
+                f,
+                "another roro process (PID {}: {})",
+                self.pid, self.command
+            ),
+            HolderKind::KubectlPortForward => write!(
+                f,
+                "a kubectl port-forward (PID {}: {})",
+                self.pid, self.command
+            ),
+            HolderKind::Other => write!(f, "PID {} ({})", self.pid, self.command),
+        }
+    }
+}
+
+/// Inodes of the sockets listening on `port` in the contents of `/proc/net/tcp` or
+/// `/proc/net/tcp6`
+#[must_use]
+pub fn listening_inodes(table: &str, port: u16) -> Vec<u64> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (_, local_port) = fields.get(1)?.rsplit_once(':')?;
+            let listening =
+                u16::from_str_radix(local_port, 16).ok()? == port && *fields.get(3)? == TCP_LISTEN;
+            let inode: u64 = fields.get(9)?.parse().ok()?;
+            (listening && inode != 0).then_some(inode)
+        })
+        .collect()
+}
+
+/// Inode of a `/proc/<pid>/fd` link target such as `socket:[12345]`
+#[must_use]
+pub fn socket_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// Command line from the contents of `/proc/<pid>/cmdline`, whose arguments end in NULs
+#[must_use]
+pub fn parse_cmdline(cmdline: &[u8]) -> String {
+    cmdline
+        .split(|byte| *byte == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(String::from_utf8_lossy)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Find the process listening on `port`
+///
+/// Returns `None` when nothing listens on the port, when the listener belongs to a process
+/// whose file descriptors this user can't read, or on platforms other than Linux.
+#[cfg(target_os = "linux")]
+#[must_use]
+pub fn find_port_holder(port: u16) -> Option<PortHolder> {
+    use std::fs;
+
+    let inodes: Vec<u64> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|table| listening_inodes(&table, port))
+        .collect();
+    if inodes.is_empty() {
+        return None;
+    }
+
+    fs::read_dir("/proc").ok()?.flatten().find_map(|entry| {
+        let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+        let holds_port = fs::read_dir(entry.path().join("fd"))
+            .ok()?
+            .flatten()
+            .filter_map(|fd| fs::read_link(fd.path()).ok())
+            .filter_map(|link| socket_inode(link.to_str()?))
+            .any(|inode| inodes.contains(&inode));
+        if !holds_port {
+            return None;
+        }
+
+        // Processes that cleared their arguments still have a name
+        let command = fs::read(entry.path().join("cmdline"))
+            .map(|cmdline| parse_cmdline(&cmdline))
+            .ok()
+            .filter(|command| !command.is_empty())
+            .or_else(|| {
+                fs::read_to_string(entry.path().join("comm"))
+                    .ok()
+                    .map(|comm| comm.trim().to_string())
+            })
+            .unwrap_or_default();
+        Some(PortHolder::new(pid, command))
+    })
+}
+
+/// Find the process listening on `port`
+///
+/// Holders can only be identified on Linux.
+#[cfg(not(target_os = "linux"))]
+#[must_use]
+pub fn find_port_holder(_port: u16) -> Option<PortHolder> {
+    None
+}
+
+/// Name the process behind a `CoreError::PortConflict` as `CoreError::PortHeld`
+///
+/// Other errors, conflicts with this process's own listeners and conflicts whose holder
+/// can't be found are returned unchanged. Walking `/proc` can take a while on a busy host,
+/// so the lookup runs on the blocking thread pool.
+pub async fn identify_port_holder(error: CoreError) -> CoreError {
+    let CoreError::PortConflict(port) = error else {
+        return error;
+    };
+    tokio::task::spawn_blocking(move || find_port_holder(port))
+        .await
+        .ok()
+        .flatten()
+        .filter(|holder| holder.pid != std::process::id())
+        .map_or(error, |holder| CoreError::PortHeld(port, holder))
+}
+
+/// Ask the process holding a port to exit with SIGTERM
+///
+/// Callers should have the user confirm first; this process is never terminated, its
+/// forwards are stopped through the manager instead.
+///
+/// # Errors
+/// Returns `CoreError::Validation` if `holder` is this process, or
+/// `CoreError::PortForwarding` if the signal could not be sent
+pub fn terminate_port_holder(holder: &PortHolder) -> Result<(), CoreError> {
+    if holder.pid == std::process::id() {
+        return Err(CoreError::Validation(
+            "The port is held by this roro process; stop its forward instead".to_string(),
+        ));
+    }
+
+    let status = std::process::Command::new("kill")
+        .arg("-TERM")
+        .arg(holder.pid.to_string())
+        .status()
+        .map_err(|e| CoreError::PortForwarding(format!("Failed to run kill: {e}")))?;
+    if status.success() {
+        println!("[PortForward] Sent SIGTERM to {holder}");
+        Ok(())
+    } else {
+        Err(CoreError::PortForwarding(format!(
+            "Failed to terminate PID {}",
+            holder.pid
+        )))
+    }
+}
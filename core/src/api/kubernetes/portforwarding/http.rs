@@ -0,0 +1,307 @@
+// HTTP/1.1 stream parsing
+//
+// This module incrementally parses one direction of an HTTP/1.1 connection as bytes flow
+// through a forward, reporting each message head and the end of its body. Traffic that
+// isn't HTTP/1.1 puts the parser into a failed state where further bytes are ignored.
+
+use std::collections::VecDeque;
+
+/// Largest message head accepted before the stream is treated as not HTTP
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+
+/// Which side of the exchange a parser reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    Request,
+    Response,
+}
+
+/// Start line and headers of a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpHead {
+    pub start_line: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl HttpHead {
+    /// First value of a header, matched case-insensitively
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Method, request target and version of a request head
+    #[must_use]
+    pub fn request_line(&self) -> Option<(&str, &str, &str)> {
+        let mut parts = self.start_line.split(' ');
+        let method = parts.next().filter(|m| !m.is_empty())?;
+        let target = parts.next().filter(|t| !t.is_empty())?;
+        let version = parts.next().filter(|v| v.starts_with("HTTP/1."))?;
+        parts.next().is_none().then_some((method, target, version))
+    }
+
+    /// Version, status code and reason phrase of a response head
+    #[must_use]
+    pub fn status_line(&self) -> Option<(&str, u16, &str)> {
+        let mut parts = self.start_line.splitn(3, ' ');
+        let version = parts.next().filter(|v| v.starts_with("HTTP/1."))?;
+        let code = parts.next().filter(|c| c.len() == 3)?.parse().ok()?;
+        Some((version, code, parts.next().unwrap_or_default()))
+    }
+
+    fn is_chunked(&self) -> bool {
+        self.header("transfer-encoding")
+            .is_some_and(|te| te.to_ascii_lowercase().contains("chunked"))
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        self.header("content-length")?.trim().parse().ok()
+    }
+}
+
+/// Something a parser found in the bytes it was fed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseEvent {
+    /// A message head was read; its body, if any, follows
+    Head(HttpHead),
+    /// The current message ended; `body` holds at most the parser's body limit of `size` bytes
+    Complete { body: Vec<u8>, size: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Head,
+    Fixed(u64),
+    ChunkSize,
+    ChunkData(u64),
+    ChunkDataEnd,
+    Trailers,
+    UntilClose,
+    Failed,
+}
+
+/// Incremental parser for one direction of an HTTP/1.1 connection
+#[derive(Debug)]
+pub struct HttpParser {
+    kind: MessageKind,
+    state: State,
+    buffer: Vec<u8>,
+    body: Vec<u8>,
+    body_size: u64,
+    max_body: usize,
+    /// For responses: whether each outstanding request expects a response without a body
+    bodyless: VecDeque<bool>,
+}
+
+impl HttpParser {
+    /// Create a parser keeping up to `max_body` bytes of each body
+    #[must_use]
+    pub fn new(kind: MessageKind, max_body: usize) -> Self {
+        Self {
+            kind,
+            state: State::Head,
+            buffer: Vec::new(),
+            body: Vec::new(),
+            body_size: 0,
+            max_body,
+            bodyless: VecDeque::new(),
+        }
+    }
+
+    /// Tell a response parser about the next request, e.g. that a `HEAD` gets no body
+    pub fn expect_response(&mut self, bodyless: bool) {
+        self.bodyless.push_back(bodyless);
+    }
+
+    /// Whether the stream turned out not to be HTTP/1.1, or switched protocols
+    #[must_use]
+    pub fn is_failed(&self) -> bool {
+        self.state == State::Failed
+    }
+
+    /// Parse `data`, returning the events it completes
+    pub fn feed(&mut self, data: &[u8]) -> Vec<ParseEvent> {
+        let mut events = Vec::new();
+        if self.state == State::Failed {
+            return events;
+        }
+        self.buffer.extend_from_slice(data);
+
+        loop {
+            let progressed = match self.state {
+                State::Head => self.parse_head(&mut events),
+                State::Fixed(remaining) => {
+                    let remaining = self.take_body(remaining);
+                    self.state = State::Fixed(remaining);
+                    if remaining == 0 {
+                        events.push(self.complete());
+                    }
+                    remaining == 0
+                }
+                State::ChunkSize => self.take_line().is_some_and(|line| {
+                    let size = line.split(';').next().unwrap_or_default().trim();
+                    self.state = match u64::from_str_radix(size, 16) {
+                        Ok(0) => State::Trailers,
+                        Ok(size) => State::ChunkData(size),
+                        Err(_) => State::Failed,
+                    };
+                    true
+                }),
+                State::ChunkData(remaining) => {
+                    let remaining = self.take_body(remaining);
+                    self.state = if remaining == 0 {
+                        State::ChunkDataEnd
+                    } else {
+                        State::ChunkData(remaining)
+                    };
+                    remaining == 0
+                }
+                State::ChunkDataEnd => self.take_line().is_some_and(|line| {
+                    self.state = if line.is_empty() {
+                        State::ChunkSize
+                    } else {
+                        State::Failed
+                    };
+                    true
+                }),
+                State::Trailers => self.take_line().is_some_and(|line| {
+                    if line.is_empty() {
+                        events.push(self.complete());
+                    }
+                    true
+                }),
+                State::UntilClose => {
+                    let buffered = self.buffer.len() as u64;
+                    self.take_body(buffered);
+                    false
+                }
+                State::Failed => {
+                    self.buffer.clear();
+                    false
+                }
+            };
+            if !progressed {
+                return events;
+            }
+        }
+    }
+
+    /// End of stream: completes a response whose body runs until the connection closes
+    pub fn finish(&mut self) -> Option<ParseEvent> {
+        (self.state == State::UntilClose).then(|| self.complete())
+    }
+
+    fn parse_head(&mut self, events: &mut Vec<ParseEvent>) -> bool {
+        let Some(end) = self.buffer.windows(4).position(|w| w == b"\r\n\r\n") else {
+            if self.buffer.len() > MAX_HEAD_SIZE {
+                self.fail();
+            }
+            return false;
+        };
+        let raw: Vec<u8> = self.buffer.drain(..end + 4).collect();
+        let Some(head) = parse_head(&raw[..end]) else {
+            self.fail();
+            return false;
+        };
+
+        let has_body = match self.kind {
+            MessageKind::Request => {
+                if head.request_line().is_none() {
+                    self.fail();
+                    return false;
+                }
+                true
+            }
+            MessageKind::Response => {
+                let Some((_, status, _)) = head.status_line() else {
+                    self.fail();
+                    return false;
+                };
+                // Interim responses precede the real one; skip them
+                if (100..200).contains(&status) && status != 101 {
+                    return true;
+                }
+                let bodyless = self.bodyless.pop_front().unwrap_or(false);
+                !(bodyless || status == 101 || status == 204 || status == 304)
+            }
+        };
+
+        self.state = if !has_body {
+            State::Head
+        } else if head.is_chunked() {
+            State::ChunkSize
+        } else if let Some(length) = head.content_length() {
+            State::Fixed(length)
+        } else if self.kind == MessageKind::Response {
+            State::UntilClose
+        } else {
+            State::Head
+        };
+        let upgraded = self.kind == MessageKind::Response
+            && head
+                .status_line()
+                .is_some_and(|(_, status, _)| status == 101);
+
+        events.push(ParseEvent::Head(head));
+        if matches!(self.state, State::Head | State::Fixed(0)) {
+            events.push(self.complete());
+        }
+        // Whatever follows a protocol switch is no longer HTTP
+        if upgraded {
+            self.fail();
+            return false;
+        }
+        true
+    }
+
+    /// Consume up to `remaining` body bytes from the buffer, returning how many are left
+    fn take_body(&mut self, remaining: u64) -> u64 {
+        let available = usize::try_from(remaining)
+            .unwrap_or(usize::MAX)
+            .min(self.buffer.len());
+        let keep = available.min(self.max_body.saturating_sub(self.body.len()));
+        self.body.extend_from_slice(&self.buffer[..keep]);
+        self.buffer.drain(..available);
+        self.body_size += available as u64;
+        remaining - available as u64
+    }
+
+    fn take_line(&mut self) -> Option<String> {
+        let end = self.buffer.windows(2).position(|w| w == b"\r\n")?;
+        let line: Vec<u8> = self.buffer.drain(..end + 2).collect();
+        Some(String::from_utf8_lossy(&line[..end]).into_owned())
+    }
+
+    fn complete(&mut self) -> ParseEvent {
+        self.state = State::Head;
+        ParseEvent::Complete {
+            body: std::mem::take(&mut self.body),
+            size: std::mem::take(&mut self.body_size),
+        }
+    }
+
+    fn fail(&mut self) {
+        self.state = State::Failed;
+        self.buffer.clear();
+    }
+}
+
+/// Parse a message head without its terminating blank line
+fn parse_head(raw: &[u8]) -> Option<HttpHead> {
+    let text = std::str::from_utf8(raw).ok()?;
+    let mut lines = text.split("\r\n");
+    let start_line = lines.next()?.to_string();
+    let headers = lines
+        .map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(HttpHead {
+        start_line,
+        headers,
+    })
+}
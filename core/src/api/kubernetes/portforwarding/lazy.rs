@@ -0,0 +1,112 @@
+// Lazy port forwards
+//
+// This module supports forwards that listen without a tunnel until a connection arrives.
+// When woken, a lazy forward resolves its target to a ready pod afresh, since the pod it
+// used last may be gone; once its connections have been idle for the idle timeout, the
+// tunnel is torn down again and the forward goes back to `Idle`.
+
+use crate::api::kubernetes::portforwarding::metrics::ForwardMetricsSnapshot;
+use crate::api::kubernetes::portforwarding::pods::find_ready_pod;
+use crate::api::kubernetes::portforwarding::service::resolve_service_target;
+use crate::api::kubernetes::portforwarding::types::{ForwardTarget, PortForwardingConfig};
+use crate::api::kubernetes::portforwarding::workload::resolve_workload_target;
+use crate::errors::CoreError;
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::Api;
+use kube::Client;
+use roro_domain::PortValue;
+use std::time::{Duration, SystemTime};
+
+/// Where a woken lazy forward opens its tunnel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WakeTarget {
+    pub pod: String,
+    pub remote_port: u16,
+    /// Ready pods a round-robin forward rotates across; empty for other forwards
+    pub ready_pods: Vec<String>,
+}
+
+/// Resolve a lazy forward's target to a ready pod when a connection wakes it
+///
+/// `config` is the forward's config as requested: pod targets are matched by the requested
+/// name or prefix, services are mapped through their `targetPort` again, and workloads pick
+/// a pod by their selection with the already resolved container port.
+///
+/// # Errors
+/// Returns an error if no ready pod matches the target
+pub async fn resolve_wake_target(
+    client: &Client,
+    config: &PortForwardingConfig,
+) -> Result<WakeTarget, CoreError> {
+    let namespace = config.namespace.as_str();
+    match &config.target {
+        ForwardTarget::Pod => {
+            let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+            let pod = find_ready_pod(&pods, namespace, &config.pod)
+                .await?
+                .map_err(CoreError::PortForwarding)?;
+            Ok(WakeTarget {
+                pod,
+                remote_port: config.remote_port,
+                ready_pods: Vec::new(),
+            })
+        }
+        ForwardTarget::Service { name, port } => {
+            let resolved = resolve_service_target(client, namespace, name, port).await?;
+            Ok(WakeTarget {
+                pod: resolved.pod,
+                remote_port: resolved.remote_port,
+                ready_pods: Vec::new(),
+            })
+        }
+        ForwardTarget::Workload {
+            kind,
+            name,
+            selection,
+            ordinal,
+        } => {
+            let resolved = resolve_workload_target(
+                client,
+                namespace,
+                *kind,
+                name,
+                *selection,
+                *ordinal,
+                &PortValue::Numeric(config.remote_port),
+            )
+            .await?;
+            Ok(WakeTarget {
+                pod: resolved.pod,
+                remote_port: resolved.remote_port,
+                ready_pods: if config.target.is_round_robin() {
+                    resolved.ready_pods
+                } else {
+                    Vec::new()
+                },
+            })
+        }
+    }
+}
+
+/// Time left before a woken forward counts as idle, or `None` once it has been idle for
+/// `idle_timeout`
+///
+/// Idle time runs from the last connection or traffic. While connections are open the
+/// whole timeout is left.
+#[must_use]
+pub fn idle_remaining(
+    metrics: &ForwardMetricsSnapshot,
+    idle_timeout: Duration,
+    now: SystemTime,
+) -> Option<Duration> {
+    if metrics.open_connections > 0 {
+        return Some(idle_timeout);
+    }
+    let idle = metrics
+        .last_activity
+        .and_then(|at| now.duration_since(at).ok())
+        .unwrap_or_default();
+    idle_timeout
+        .checked_sub(idle)
+        .filter(|remaining| !remaining.is_zero())
+}
@@ -0,0 +1,116 @@
+// Dedicated loopback addresses for services
+//
+// This module gives each service forwarded with a dedicated address its own loopback IP, so
+// services sharing a port (two databases on 5432) can both listen on it, and lists the
+// in-cluster host names that should resolve to those addresses. Addresses come from
+// 127.1.0.1 upwards, leaving 127.0.0.0/16 to the system. Linux routes all of 127.0.0.0/8 to
+// the loopback interface; macOS needs an alias per address (`ifconfig lo0 alias 127.1.0.1`).
+
+use crate::api::kubernetes::portforwarding::bind::BindAddress;
+use crate::api::kubernetes::portforwarding::types::{ForwardTarget, PortForwardingState};
+use crate::errors::CoreError;
+use std::collections::{BTreeMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{Mutex, PoisonError};
+
+/// First address handed out
+pub const FIRST_SERVICE_IP: Ipv4Addr = Ipv4Addr::new(127, 1, 0, 1);
+
+/// Last address handed out
+const LAST_SERVICE_IP: Ipv4Addr = Ipv4Addr::new(127, 254, 255, 254);
+
+/// A host name and the dedicated address it resolves to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceHost {
+    pub name: String,
+    pub ip: Ipv4Addr,
+}
+
+/// Whether `ip` is in the range dedicated addresses are allocated from
+#[must_use]
+pub fn is_service_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip >= FIRST_SERVICE_IP && ip <= LAST_SERVICE_IP,
+        IpAddr::V6(_) => false,
+    }
+}
+
+/// Names in-cluster clients use for a service, shortest first
+#[must_use]
+pub fn service_host_names(service: &str, namespace: &str) -> Vec<String> {
+    vec![
+        service.to_string(),
+        format!("{service}.{namespace}"),
+        format!("{service}.{namespace}.svc"),
+        format!("{service}.{namespace}.svc.cluster.local"),
+    ]
+}
+
+/// Host names of the service forwards listening on dedicated addresses
+///
+/// A short name shared by services in several namespaces resolves to the first one listed;
+/// the namespaced names stay unique.
+#[must_use]
+pub fn service_hosts(forwards: &[PortForwardingState]) -> Vec<ServiceHost> {
+    let mut seen = HashSet::new();
+    let mut hosts = Vec::new();
+    for state in forwards {
+        let ForwardTarget::Service { name, .. } = &state.config.target else {
+            continue;
+        };
+        let BindAddress::Ip(IpAddr::V4(ip)) = state.config.bind_address else {
+            continue;
+        };
+        if !is_service_ip(IpAddr::V4(ip)) {
+            continue;
+        }
+        for host in service_host_names(name, &state.config.namespace) {
+            if seen.insert(host.clone()) {
+                hosts.push(ServiceHost { name: host, ip });
+            }
+        }
+    }
+    hosts
+}
+
+/// Dedicated addresses assigned to services by context, namespace and name
+///
+/// The table is shared by every context's manager, so services of different clusters never
+/// get the same address, and kept for the process's lifetime so a service gets the same
+/// address each time it is forwarded.
+static SERVICE_IPS: Mutex<BTreeMap<(String, String, String), Ipv4Addr>> =
+    Mutex::new(BTreeMap::new());
+
+/// The dedicated address of a service in `context`, assigning the lowest free one on first
+/// use
+///
+/// `in_use` holds addresses forwards are listening on, e.g. ones restored from a previous
+/// session, which are skipped when assigning.
+///
+/// # Errors
+/// Returns an error if every address in the range is taken
+pub fn allocate_service_ip(
+    context: &str,
+    namespace: &str,
+    service: &str,
+    in_use: &[Ipv4Addr],
+) -> Result<Ipv4Addr, CoreError> {
+    let mut assigned = SERVICE_IPS.lock().unwrap_or_else(PoisonError::into_inner);
+    let key = (
+        context.to_string(),
+        namespace.to_string(),
+        service.to_string(),
+    );
+    if let Some(ip) = assigned.get(&key) {
+        return Ok(*ip);
+    }
+    let taken: HashSet<Ipv4Addr> = assigned.values().copied().collect();
+    let ip = (u32::from(FIRST_SERVICE_IP)..=u32::from(LAST_SERVICE_IP))
+        .map(Ipv4Addr::from)
+        .find(|ip| !taken.contains(ip) && !in_use.contains(ip))
+        .ok_or_else(|| {
+            CoreError::PortForwarding("No dedicated loopback addresses left".to_string())
+        })?;
+    assigned.insert(key, ip);
+    Ok(ip)
+}
@@ -0,0 +1,115 @@
+// Dedicated service addresses
+//
+// This module assigns dedicated loopback addresses to service forwards and publishes their
+// in-cluster host names through the local DNS responder, started with the first such
+// forward, or a hosts file block.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::bind::BindAddress;
+use crate::api::kubernetes::portforwarding::dns::{
+    dns_server_address, start_dns_server, DEFAULT_DNS_ADDRESS,
+};
+use crate::api::kubernetes::portforwarding::loopback::{
+    allocate_service_ip, is_service_ip, service_hosts, ServiceHost,
+};
+use crate::api::kubernetes::portforwarding::types::PortForwardingConfig;
+use crate::api::kubernetes::portforwarding_registry::list_all_forwards;
+use crate::errors::CoreError;
+use roro_persistence::{apply_hosts_block, default_hosts_path};
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+
+impl PortForwardingManager {
+    /// Host names of services forwarded on dedicated addresses, with their addresses
+    pub async fn service_hosts(&self) -> Vec<ServiceHost> {
+        let mut forwards = self.list_forwards().await;
+        forwards.sort_by(|a, b| a.id.cmp(&b.id));
+        service_hosts(&forwards)
+    }
+
+    /// The dedicated address of a service, assigning one on first use
+    ///
+    /// Addresses come from the process-wide table, skipping those forwards in any context
+    /// already listen on.
+    pub(super) async fn allocate_service_ip(
+        &self,
+        namespace: &str,
+        service: &str,
+    ) -> Result<Ipv4Addr, CoreError> {
+        let mut forwards = list_all_forwards().await;
+        forwards.extend(self.active_forwards.read().await.values().cloned());
+        let in_use: Vec<Ipv4Addr> = forwards
+            .iter()
+            .filter_map(|state| match state.config.bind_address {
+                BindAddress::Ip(IpAddr::V4(ip)) => Some(ip),
+                _ => None,
+            })
+            .collect();
+        let ip = allocate_service_ip(&self.context, namespace, service, &in_use)?;
+        println!("[PortForward] Assigned {ip} to service {namespace}/{service}");
+        Ok(ip)
+    }
+
+    /// Start the DNS responder on its default address once a forward listens on a
+    /// dedicated address, so the service's names resolve without further setup
+    ///
+    /// A responder that is already running, e.g. on another address, is left alone; one that
+    /// can't be started only costs name resolution, so the forward keeps running.
+    pub(super) async fn serve_service_names(&self, config: &PortForwardingConfig) {
+        let BindAddress::Ip(ip) = config.bind_address else {
+            return;
+        };
+        if !is_service_ip(ip) || dns_server_address().is_some() {
+            return;
+        }
+        if let Err(e) = start_dns_server(DEFAULT_DNS_ADDRESS).await {
+            if dns_server_address().is_none() {
+                eprintln!(
+                    "[PortForward] Warning: names of services in context {} won't resolve through the DNS responder: {e}",
+                    self.context
+                );
+            }
+        }
+    }
+
+    /// Write the current service host names into this context's block of a hosts file
+    ///
+    /// Without a `path` the system hosts file is used, which usually needs administrator
+    /// rights. Returns the path written.
+    ///
+    /// # Errors
+    /// Returns an error if the hosts file cannot be read or written
+    pub async fn write_hosts_file(&self, path: Option<&Path>) -> Result<PathBuf, CoreError> {
+        let entries: Vec<(String, String)> = self
+            .service_hosts()
+            .await
+            .into_iter()
+            .map(|host| (host.ip.to_string(), host.name))
+            .collect();
+        self.apply_hosts_entries(path, &entries).await
+    }
+
+    /// Remove this context's block from a hosts file, the system one without a `path`
+    ///
+    /// # Errors
+    /// Returns an error if the hosts file cannot be read or written
+    pub async fn clear_hosts_file(&self, path: Option<&Path>) -> Result<PathBuf, CoreError> {
+        self.apply_hosts_entries(path, &[]).await
+    }
+
+    async fn apply_hosts_entries(
+        &self,
+        path: Option<&Path>,
+        entries: &[(String, String)],
+    ) -> Result<PathBuf, CoreError> {
+        let path = path.map_or_else(default_hosts_path, Path::to_path_buf);
+        apply_hosts_block(&path, &self.context, entries).await?;
+        println!(
+            "[PortForward] Wrote {} host name(s) for context {} to {}",
+            entries.len(),
+            self.context,
+            path.display()
+        );
+        Ok(path)
+    }
+}
@@ -0,0 +1,206 @@
+// App port forwards
+//
+// This module starts port forwards declared in app.json, for services and for the pods of
+// Deployments, StatefulSets, DaemonSets and named pods.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::bind::BindAddress;
+use crate::api::kubernetes::portforwarding::service::resolve_service_target;
+use crate::api::kubernetes::portforwarding::types::{ForwardTarget, PortForwardingConfig};
+use crate::api::kubernetes::portforwarding::workload::{
+    resolve_workload_target, stateful_set_pod_name,
+};
+use crate::errors::CoreError;
+use roro_domain::PortForwardingConfig as AppPortForwardingConfig;
+use roro_domain::{PortValue, ResourceKind};
+use std::net::IpAddr;
+use std::time::Duration;
+
+impl PortForwardingManager {
+    /// Start a port forward declared in app.json
+    ///
+    /// Services are resolved to a ready backing pod and the service port is mapped through
+    /// `targetPort` to the container port. Workloads are resolved to a ready pod picked by
+    /// `selection`, or to the `StatefulSet` pod named by `ordinal`, and `port` is taken as a
+    /// container port; round-robin forwards spread new connections across the ready pods.
+    /// The local port is chosen from the `localport` policy and remembered for the instance,
+    /// unless a service forward asks for a dedicated address: it then listens on the service
+    /// port of a loopback address assigned to the service. Lazy forwards are resolved here
+    /// to check the target and find its container port, then again whenever they wake.
+    ///
+    /// # Errors
+    /// Returns an error if the config is invalid, the resource cannot be resolved to a ready
+    /// pod, or the forward cannot be started
+    pub async fn start_app_forward(
+        &self,
+        namespace: &str,
+        instance_id: &str,
+        config: &AppPortForwardingConfig,
+    ) -> Result<String, CoreError> {
+        config.validate()?;
+
+        match config.resource_kind()? {
+            ResourceKind::Service => {
+                self.start_service_forward(namespace, instance_id, config)
+                    .await
+            }
+            kind => {
+                self.start_workload_forward(namespace, instance_id, kind, config)
+                    .await
+            }
+        }
+    }
+
+    async fn start_service_forward(
+        &self,
+        namespace: &str,
+        instance_id: &str,
+        config: &AppPortForwardingConfig,
+    ) -> Result<String, CoreError> {
+        let (bind_address, local_port, resolved) = if config.dedicated_address {
+            // A dedicated address lets the forward listen on the service's own port
+            let resolved =
+                resolve_service_target(&self.client, namespace, &config.name, &config.port).await?;
+            let ip = self.allocate_service_ip(namespace, &config.name).await?;
+            (
+                BindAddress::Ip(IpAddr::V4(ip)),
+                resolved.service_port,
+                resolved,
+            )
+        } else {
+            let bind_address = configured_bind_address(config)?;
+            let forward_key = forward_key(ResourceKind::Service, &config.name, &config.port);
+            let local_port = self
+                .allocate_local_port(
+                    instance_id,
+                    &forward_key,
+                    config.local_port_policy()?,
+                    bind_address,
+                )
+                .await?;
+
+            let resolved =
+                resolve_service_target(&self.client, namespace, &config.name, &config.port).await?;
+            (bind_address, local_port, resolved)
+        };
+
+        self.start_forward(PortForwardingConfig {
+            namespace: namespace.to_string(),
+            pod: resolved.pod,
+            remote_port: resolved.remote_port,
+            local_port,
+            instance_id: instance_id.to_string(),
+            target: ForwardTarget::Service {
+                name: config.name.clone(),
+                port: config.port.clone(),
+            },
+            health_check: config.health_check.clone(),
+            bind_address,
+            group_id: None,
+            tls: config.tls,
+            capture: None,
+            ready_timeout: None,
+            shaping: None,
+            lazy: config.lazy,
+            idle_timeout: config.idle_timeout.map(Duration::from_secs),
+            transport: config.transport.unwrap_or_default(),
+        })
+        .await
+    }
+
+    async fn start_workload_forward(
+        &self,
+        namespace: &str,
+        instance_id: &str,
+        kind: ResourceKind,
+        config: &AppPortForwardingConfig,
+    ) -> Result<String, CoreError> {
+        let bind_address = configured_bind_address(config)?;
+        // Ordinal forwards are remembered per pod, so db-0 and db-1 keep separate ports
+        let key_name = config.ordinal.map_or_else(
+            || config.name.clone(),
+            |ordinal| stateful_set_pod_name(&config.name, ordinal),
+        );
+        let local_port = self
+            .allocate_local_port(
+                instance_id,
+                &forward_key(kind, &key_name, &config.port),
+                config.local_port_policy()?,
+                bind_address,
+            )
+            .await?;
+
+        let selection = config.selection.unwrap_or_default();
+        let resolved = resolve_workload_target(
+            &self.client,
+            namespace,
+            kind,
+            &config.name,
+            selection,
+            config.ordinal,
+            &config.port,
+        )
+        .await?;
+
+        let forward_id = self
+            .start_forward(PortForwardingConfig {
+                namespace: namespace.to_string(),
+                pod: resolved.pod,
+                remote_port: resolved.remote_port,
+                local_port,
+                instance_id: instance_id.to_string(),
+                target: ForwardTarget::Workload {
+                    kind,
+                    name: config.name.clone(),
+                    selection,
+                    ordinal: config.ordinal,
+                },
+                health_check: config.health_check.clone(),
+                bind_address,
+                group_id: None,
+                tls: config.tls,
+                capture: None,
+                ready_timeout: None,
+                shaping: None,
+                lazy: config.lazy,
+                idle_timeout: config.idle_timeout.map(Duration::from_secs),
+                transport: config.transport.unwrap_or_default(),
+            })
+            .await?;
+
+        // Rotate from the start rather than waiting for the failover watcher's first listing
+        if let Some(state) = self.active_forwards.write().await.get_mut(&forward_id) {
+            if state.config.target.is_round_robin() && state.ready_pods.is_empty() {
+                state.ready_pods = resolved.ready_pods;
+            }
+        }
+
+        Ok(forward_id)
+    }
+}
+
+fn configured_bind_address(config: &AppPortForwardingConfig) -> Result<BindAddress, CoreError> {
+    Ok(config
+        .bind_address
+        .as_deref()
+        .map(str::parse::<BindAddress>)
+        .transpose()?
+        .unwrap_or_default())
+}
+
+/// How a forward of an app is named in reports, e.g. "service/api:http"
+pub(super) fn forward_label(config: &AppPortForwardingConfig) -> String {
+    match config.resource_kind() {
+        Ok(kind) => forward_key(kind, &config.name, &config.port),
+        Err(_) => format!("{}/{}", config.kind, config.name),
+    }
+}
+
+/// Key remembering a forward's local port within its instance, e.g. "service/api:http"
+fn forward_key(kind: ResourceKind, name: &str, port: &PortValue) -> String {
+    let port = match port {
+        PortValue::Numeric(number) => number.to_string(),
+        PortValue::Named(name) => name.clone(),
+    };
+    format!("{kind}/{name}:{port}")
+}
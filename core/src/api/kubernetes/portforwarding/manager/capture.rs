@@ -0,0 +1,85 @@
+// Traffic inspection
+//
+// This module turns HTTP capture on and off for running forwards, reads the captured
+// exchanges and exports them as HAR files.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::capture::{CaptureConfig, HttpCapture, HttpExchange};
+use crate::api::kubernetes::portforwarding::har::har_log;
+use crate::errors::CoreError;
+use roro_persistence::{default_capture_path, save_capture_to};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+impl PortForwardingManager {
+    /// Turn HTTP capture on with `config`, or off with `None`
+    ///
+    /// Connections opened from now on are inspected; exchanges already captured are kept
+    /// until cleared.
+    ///
+    /// # Errors
+    /// Returns an error if the forward is not found
+    pub async fn set_capture(
+        &self,
+        forward_id: &str,
+        config: Option<CaptureConfig>,
+    ) -> Result<(), CoreError> {
+        let enabled = config.is_some();
+        self.forward_capture(forward_id).await?.set_config(config);
+        println!(
+            "[PortForward] HTTP capture {} for {forward_id}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+        Ok(())
+    }
+
+    /// Exchanges captured on a forward, oldest first
+    ///
+    /// # Errors
+    /// Returns an error if the forward is not found
+    pub async fn captured_exchanges(
+        &self,
+        forward_id: &str,
+    ) -> Result<Vec<HttpExchange>, CoreError> {
+        Ok(self.forward_capture(forward_id).await?.exchanges())
+    }
+
+    /// Discard the exchanges captured on a forward
+    ///
+    /// # Errors
+    /// Returns an error if the forward is not found
+    pub async fn clear_capture(&self, forward_id: &str) -> Result<(), CoreError> {
+        self.forward_capture(forward_id).await?.clear();
+        Ok(())
+    }
+
+    /// Write a forward's captured exchanges to a HAR file
+    ///
+    /// Without a `path` the file goes to `~/.roro/captures/<forward id>.har`. Returns the
+    /// path written and the number of exchanges in it.
+    ///
+    /// # Errors
+    /// Returns an error if the forward is not found or the file cannot be written
+    pub async fn export_har(
+        &self,
+        forward_id: &str,
+        path: Option<&Path>,
+    ) -> Result<(PathBuf, usize), CoreError> {
+        let exchanges = self.captured_exchanges(forward_id).await?;
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => default_capture_path(forward_id)?,
+        };
+        save_capture_to(&path, &har_log(&exchanges)).await?;
+        Ok((path, exchanges.len()))
+    }
+
+    async fn forward_capture(&self, forward_id: &str) -> Result<Arc<HttpCapture>, CoreError> {
+        self.active_forwards
+            .read()
+            .await
+            .get(forward_id)
+            .map(|state| Arc::clone(&state.capture))
+            .ok_or_else(|| CoreError::PortForwardingNotFound(forward_id.to_string()))
+    }
+}
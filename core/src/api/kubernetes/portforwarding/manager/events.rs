@@ -0,0 +1,18 @@
+// Event subscriptions
+//
+// This module exposes the manager's port forward event stream to the GUI, tray and CLI.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::events::PortForwardingEvent;
+use tokio::sync::broadcast;
+
+impl PortForwardingManager {
+    /// Subscribe to port forward events
+    ///
+    /// Each subscriber receives every event sent after it subscribed. Subscribers that fall
+    /// more than the channel capacity behind receive `RecvError::Lagged` and skip ahead.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<PortForwardingEvent> {
+        self.events.subscribe()
+    }
+}
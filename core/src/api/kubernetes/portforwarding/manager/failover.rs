@@ -0,0 +1,43 @@
+// Failover watchers
+//
+// This module attaches pod failover watchers to forwards started by the manager.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::failover::{failover_selector, spawn_failover_watcher};
+use crate::api::kubernetes::portforwarding::types::PortForwardingConfig;
+use std::sync::Arc;
+
+impl PortForwardingManager {
+    /// Watch the workload behind a forward and retarget it when its pod is replaced
+    ///
+    /// `forward_id` may be a group id, so the group's members move together. Forwards to
+    /// standalone pods have no replacement selector and are left unwatched.
+    /// Returns the selector being watched.
+    pub(super) async fn start_failover_watcher(
+        &self,
+        forward_id: &str,
+        config: &PortForwardingConfig,
+    ) -> Option<String> {
+        let selector = failover_selector(&self.client, config).await?;
+
+        let handle = spawn_failover_watcher(
+            self.client.clone(),
+            Arc::clone(&self.active_forwards),
+            self.events.clone(),
+            forward_id.to_string(),
+            config.namespace.clone(),
+            selector.clone(),
+        );
+
+        if let Some(previous) = self
+            .failover_tasks
+            .write()
+            .await
+            .insert(forward_id.to_string(), handle)
+        {
+            previous.abort();
+        }
+
+        Some(selector)
+    }
+}
@@ -0,0 +1,135 @@
+// Forward groups
+//
+// This module starts and stops groups of forwards that share one pod, so an app's ports
+// resolve, fail over and report status together under a single group id.
+//
+// Members don't share one multi-port tunnel. A port-forward websocket carries exactly one
+// stream per requested port, and the pod side connects to every requested port as soon as
+// it opens, so a websocket for all of a group's ports would serve a single connection per
+// port and open idle connections to the other ports each time. Each member therefore runs
+// its own forward task with its own sessions, all pinned to the group's pod.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::group::{
+    group_id, group_status, validate_port_mappings, PortMapping,
+};
+use crate::api::kubernetes::portforwarding::types::{
+    PortForwardingConfig, PortForwardingState, PortForwardingStatus, StopMode, StopSummary,
+};
+use crate::errors::CoreError;
+use futures::future::join_all;
+
+impl PortForwardingManager {
+    /// Forward several remote ports of one pod as a single group
+    ///
+    /// `template` supplies everything but the ports. The pod is resolved once and every
+    /// member is pinned to it; failover retargets the whole group together. If any member
+    /// fails to start, the ones already started are stopped again.
+    ///
+    /// # Errors
+    /// Returns an error if the template is lazy, the mappings are invalid, a local port is in
+    /// use, the pod doesn't exist, or the group is already running
+    pub async fn start_group(
+        &self,
+        mut template: PortForwardingConfig,
+        ports: &[PortMapping],
+    ) -> Result<String, CoreError> {
+        validate_port_mappings(ports)?;
+        // Lazy members would each resolve a pod of their own when woken
+        if template.lazy {
+            return Err(CoreError::Validation(
+                "Forward groups can't be lazy".to_string(),
+            ));
+        }
+        for mapping in ports {
+            self.check_port_available(template.bind_address, mapping.local_port)
+                .await?;
+        }
+        template.pod = self.resolve_pod(&template).await?;
+
+        let group_id = group_id(&template.instance_id, &template.pod, ports);
+        if !self.group_members(&group_id).await.is_empty() {
+            return Err(CoreError::PortForwarding(format!(
+                "Port forward group already exists: {group_id}"
+            )));
+        }
+        template.group_id = Some(group_id.clone());
+
+        let mut started = Vec::new();
+        for mapping in ports {
+            let config = PortForwardingConfig {
+                remote_port: mapping.remote_port,
+                local_port: mapping.local_port,
+                ..template.clone()
+            };
+            match self.launch_forward(config.clone()).await {
+                Ok(forward_id) => started.push((forward_id, config)),
+                Err(e) => {
+                    for (forward_id, _) in &started {
+                        let _ = self.halt_forward(forward_id, StopMode::Now).await;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        let selector = self.start_failover_watcher(&group_id, &template).await;
+        for (forward_id, config) in &started {
+            self.record_forward(forward_id, config, selector.clone())
+                .await;
+        }
+
+        Ok(group_id)
+    }
+
+    /// Stop every member of a group
+    ///
+    /// # Errors
+    /// Returns an error if the group has no running members
+    pub async fn stop_group(
+        &self,
+        group_id: &str,
+        mode: StopMode,
+    ) -> Result<StopSummary, CoreError> {
+        let members = self.group_members(group_id).await;
+        if members.is_empty() {
+            return Err(CoreError::PortForwardingNotFound(group_id.to_string()));
+        }
+
+        if let Some(handle) = self.failover_tasks.write().await.remove(group_id) {
+            handle.abort();
+        }
+
+        let summary = join_all(
+            members
+                .iter()
+                .map(|state| self.stop_forward_with(&state.id, mode)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .fold(StopSummary::default(), |total, summary| total + summary);
+
+        Ok(summary)
+    }
+
+    /// Running members of a group, ordered by local port
+    pub async fn group_members(&self, group_id: &str) -> Vec<PortForwardingState> {
+        let mut members: Vec<PortForwardingState> = self
+            .active_forwards
+            .read()
+            .await
+            .values()
+            .filter(|state| state.config.group_id.as_deref() == Some(group_id))
+            .cloned()
+            .collect();
+        members.sort_by_key(|state| state.config.local_port);
+        members
+    }
+
+    /// Status of a group: the least healthy status among its members
+    pub async fn get_group_status(&self, group_id: &str) -> Option<PortForwardingStatus> {
+        let members = self.group_members(group_id).await;
+        group_status(members.iter().map(|state| &state.status))
+    }
+}
@@ -0,0 +1,70 @@
+// Health monitoring
+//
+// This module periodically probes active forwards through their tunnels and moves
+// failing forwards to a ready pod. Only the supervisor marks a forward failed, since a
+// forward whose listener still serves can recover once its pod does.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::failover::recover_forward;
+use crate::api::kubernetes::portforwarding::health::{health_check_forward, record_health};
+use crate::api::kubernetes::portforwarding::types::PortForwardingStatus;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+impl PortForwardingManager {
+    /// Start probing active and reconnecting forwards every health check interval
+    ///
+    /// Each failed probe is counted in `health_failures`, marks the forward `Reconnecting`
+    /// and triggers failover to a ready pod; the next healthy probe makes it `Active` again.
+    /// A forward the supervisor marked `Reconnecting` stays so until its listener is bound.
+    pub fn start_health_monitoring(&self) {
+        let client = self.client.clone();
+        let forwards = Arc::clone(&self.active_forwards);
+        let events = self.events.clone();
+        let interval = self.health_check_interval;
+
+        tokio::spawn(async move {
+            let mut interval_timer = tokio::time::interval(interval);
+            loop {
+                interval_timer.tick().await;
+
+                let checks: Vec<_> = {
+                    let f = forwards.read().await;
+                    f.values()
+                        .filter(|state| {
+                            matches!(
+                                state.status,
+                                PortForwardingStatus::Active | PortForwardingStatus::Reconnecting
+                            )
+                        })
+                        .map(|state| (state.id.clone(), state.config.clone()))
+                        .collect()
+                };
+
+                for (forward_id, config) in checks {
+                    let health = health_check_forward(&client, &config).await;
+
+                    let needs_recovery = {
+                        let mut f = forwards.write().await;
+                        // A lazy forward may have gone idle, or the supervisor may have given up,
+                        // while it was being probed
+                        let Some(state) = f.get_mut(&forward_id).filter(|state| {
+                            !matches!(
+                                state.status,
+                                PortForwardingStatus::Idle | PortForwardingStatus::Failed
+                            )
+                        }) else {
+                            continue;
+                        };
+                        state.last_health_check = Some(SystemTime::now());
+                        record_health(state, health, &events)
+                    };
+
+                    if needs_recovery {
+                        recover_forward(&client, &forwards, &events, &forward_id).await;
+                    }
+                }
+            }
+        });
+    }
+}
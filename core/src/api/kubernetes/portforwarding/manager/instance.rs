@@ -0,0 +1,108 @@
+// App instance forwards
+//
+// This module starts every forward an app declares for one instance as a unit, either all
+// or nothing or best effort, and stops all of an instance's forwards together.
+
+use super::app::forward_label;
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::types::{
+    AppForwardOutcome, InstanceStartReport, StartMode, StopMode, StopSummary,
+};
+use crate::errors::CoreError;
+use futures::future::join_all;
+use roro_domain::AppConfig;
+use std::collections::HashSet;
+
+impl PortForwardingManager {
+    /// Start every forward `app` declares, for the instance running in `namespace`
+    ///
+    /// The whole app config is validated before anything starts, then forwards start in
+    /// app.json order. With `StartMode::AllOrNothing`, a forward failing to start stops the
+    /// ones already started again and drops them from saved state; with
+    /// `StartMode::BestEffort` the remaining forwards still start and the report says which
+    /// failed.
+    ///
+    /// # Errors
+    /// Returns an error if the app config is invalid, or in `StartMode::AllOrNothing` the
+    /// first error a forward failed with
+    pub async fn start_app_instance(
+        &self,
+        app: &AppConfig,
+        namespace: &str,
+        instance_id: &str,
+        mode: StartMode,
+    ) -> Result<InstanceStartReport, CoreError> {
+        app.validate()?;
+
+        let mut report = InstanceStartReport::default();
+        for config in &app.port_forwarding {
+            let forward = forward_label(config);
+            let result = self.start_app_forward(namespace, instance_id, config).await;
+
+            if let Err(e) = &result {
+                eprintln!("[PortForward] Failed to start {forward} for {instance_id}: {e}");
+                if mode == StartMode::AllOrNothing {
+                    let started = report.started();
+                    if !started.is_empty() {
+                        println!(
+                            "[PortForward] Rolling back {} forward(s) of {instance_id}",
+                            started.len()
+                        );
+                    }
+                    for forward_id in started {
+                        let _ = self.halt_forward(forward_id, StopMode::Now).await;
+                        // The instance never started, so there is nothing to restore later
+                        if self.persist_state {
+                            let _ = self.forget_forward(forward_id).await;
+                        }
+                    }
+                    return result.map(|_| report);
+                }
+            }
+            report.forwards.push(AppForwardOutcome { forward, result });
+        }
+
+        Ok(report)
+    }
+
+    /// Stop every forward of an instance, including members of its groups
+    ///
+    /// # Errors
+    /// Returns `CoreError::PortForwardingNotFound` if the instance has no running forwards
+    pub async fn stop_app_instance(
+        &self,
+        instance_id: &str,
+        mode: StopMode,
+    ) -> Result<StopSummary, CoreError> {
+        let forwards = self.list_forwards_by_instance(instance_id).await;
+        if forwards.is_empty() {
+            return Err(CoreError::PortForwardingNotFound(instance_id.to_string()));
+        }
+
+        // Group failover watchers are keyed by group rather than by member
+        let groups: HashSet<&str> = forwards
+            .iter()
+            .filter_map(|state| state.config.group_id.as_deref())
+            .collect();
+        {
+            let mut failover_tasks = self.failover_tasks.write().await;
+            for group_id in groups {
+                if let Some(handle) = failover_tasks.remove(group_id) {
+                    handle.abort();
+                }
+            }
+        }
+
+        let summary = join_all(
+            forwards
+                .iter()
+                .map(|state| self.stop_forward_with(&state.id, mode)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .fold(StopSummary::default(), |total, summary| total + summary);
+
+        Ok(summary)
+    }
+}
@@ -0,0 +1,328 @@
+// Port forwarding manager
+//
+// This module provides the main PortForwardingManager implementation.
+
+mod addresses;
+mod app;
+mod capture;
+mod events;
+mod failover;
+mod group;
+mod health;
+mod instance;
+mod persist;
+mod ports;
+mod shaping;
+mod socks;
+mod stop;
+
+use crate::api::kubernetes::client::KubernetesClient;
+use crate::api::kubernetes::portforwarding::backoff::BackoffPolicy;
+use crate::api::kubernetes::portforwarding::bind::{
+    check_bind_available, find_available_port, BindAddress,
+};
+use crate::api::kubernetes::portforwarding::events::{
+    set_status, EventSender, PortForwardingEvent, EVENT_CHANNEL_CAPACITY,
+};
+use crate::api::kubernetes::portforwarding::failover::FailoverTaskMap;
+use crate::api::kubernetes::portforwarding::holder::identify_port_holder;
+use crate::api::kubernetes::portforwarding::pods::find_ready_pod;
+use crate::api::kubernetes::portforwarding::socks::RunningSocksProxy;
+use crate::api::kubernetes::portforwarding::task::{
+    spawn_forward_task, ForwardTaskMap, TaskControl,
+};
+use crate::api::kubernetes::portforwarding::types::{
+    ForwardTarget, PortForwardingConfig, PortForwardingState, PortForwardingStatus,
+};
+use crate::errors::CoreError;
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::Api;
+use kube::Client;
+use roro_domain::ForwardTransport;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Mutex, RwLock};
+
+/// How often `start_forward` checks for a ready pod while waiting for one
+const READY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct PortForwardingManager {
+    active_forwards: Arc<RwLock<HashMap<String, PortForwardingState>>>,
+    client: Client,
+    health_check_interval: Duration,
+    backoff: BackoffPolicy,
+    drain_timeout: Duration,
+    forward_tasks: ForwardTaskMap,
+    failover_tasks: FailoverTaskMap,
+    events: EventSender,
+    context: String,
+    persist_state: bool,
+    state_lock: Arc<Mutex<()>>,
+    socks_proxy: Arc<Mutex<Option<RunningSocksProxy>>>,
+}
+
+impl PortForwardingManager {
+    #[must_use]
+    pub fn new(client: &KubernetesClient) -> Self {
+        Self {
+            active_forwards: Arc::new(RwLock::new(HashMap::new())),
+            client: client.inner().clone(),
+            health_check_interval: Duration::from_secs(30),
+            backoff: BackoffPolicy::default(),
+            drain_timeout: Duration::from_secs(30),
+            forward_tasks: Arc::new(RwLock::new(HashMap::new())),
+            failover_tasks: Arc::new(RwLock::new(HashMap::new())),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            context: client.current_context().to_string(),
+            persist_state: false,
+            state_lock: Arc::new(Mutex::new(())),
+            socks_proxy: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    #[must_use]
+    pub fn with_health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = interval;
+        self
+    }
+
+    /// Set the delay before the first reconnection attempt
+    #[must_use]
+    pub fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.backoff.initial = delay;
+        self
+    }
+
+    /// Set how many reconnection attempts a forward gets before it is marked failed
+    #[must_use]
+    pub fn with_max_retries(mut self, max: u32) -> Self {
+        self.backoff.max_attempts = Some(max);
+        self
+    }
+
+    /// Set the backoff policy forward supervisors follow between reconnection attempts
+    #[must_use]
+    pub fn with_backoff_policy(mut self, backoff: BackoffPolicy) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set how long a graceful stop waits for open connections before closing them
+    #[must_use]
+    pub fn with_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    /// Save requested forwards to `~/.roro/forwards.json` so they can be restored later
+    #[must_use]
+    pub fn with_state_persistence(mut self, enabled: bool) -> Self {
+        self.persist_state = enabled;
+        self
+    }
+
+    /// Kube context this manager runs forwards in
+    #[must_use]
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    /// Start a port forward
+    ///
+    /// Lazy forwards only bind their listener here and show as `Idle`; the pod is resolved
+    /// when the first connection arrives, so a missing pod is reported then. They are not
+    /// watched for failover, as each wake resolves the target afresh. The first forward on
+    /// a dedicated service address also starts the DNS responder for service names.
+    ///
+    /// # Errors
+    /// Returns an error if the shaping settings are invalid, an idle timeout is set on an
+    /// eager forward, the service proxy transport is chosen for a target other than a
+    /// service, the port is already in use, the forward already exists, no ready pod
+    /// matches (within `ready_timeout` when set), or the pod name is ambiguous
+    pub async fn start_forward(
+        &self,
+        mut config: PortForwardingConfig,
+    ) -> Result<String, CoreError> {
+        if let Some(shaping) = &config.shaping {
+            shaping.validate()?;
+        }
+        if config.idle_timeout.is_some() && !config.lazy {
+            return Err(CoreError::Validation(
+                "An idle timeout can only be set on a lazy forward".to_string(),
+            ));
+        }
+        if config.transport == ForwardTransport::ServiceProxy
+            && !matches!(config.target, ForwardTarget::Service { .. })
+        {
+            return Err(CoreError::Validation(
+                "The service proxy transport only works for forwards to a service".to_string(),
+            ));
+        }
+        self.check_port_available(config.bind_address, config.local_port)
+            .await?;
+
+        if config.lazy {
+            let forward_id = self.launch_forward(config.clone()).await?;
+            self.record_forward(&forward_id, &config, None).await;
+            self.serve_service_names(&config).await;
+            return Ok(forward_id);
+        }
+        config.pod = self.resolve_pod(&config).await?;
+
+        let forward_id = self.launch_forward(config.clone()).await?;
+        let selector = self.start_failover_watcher(&forward_id, &config).await;
+        self.record_forward(&forward_id, &config, selector).await;
+        self.serve_service_names(&config).await;
+
+        Ok(forward_id)
+    }
+
+    /// Resolve the configured pod name to a ready pod, returning its actual name
+    ///
+    /// An exact name is used as is; otherwise the name is matched as a prefix of pod names
+    /// (for deployment-generated pod names like "app-abc123-xyz"), see `match_pods`. With a
+    /// `ready_timeout`, pods that are missing or not ready yet are polled for until the
+    /// timeout expires.
+    async fn resolve_pod(&self, config: &PortForwardingConfig) -> Result<String, CoreError> {
+        let pods: Api<Pod> = Api::namespaced(self.client.clone(), &config.namespace);
+        let deadline = config.ready_timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            let reason = match find_ready_pod(&pods, &config.namespace, &config.pod).await? {
+                Ok(pod) => return Ok(pod),
+                Err(reason) => reason,
+            };
+
+            let Some(deadline) = deadline else {
+                return Err(CoreError::PortForwarding(reason));
+            };
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(CoreError::PortForwarding(format!(
+                    "Timed out waiting for a ready pod: {reason}"
+                )));
+            }
+            tokio::time::sleep(READY_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    /// Register and spawn a forward for an already resolved pod
+    async fn launch_forward(&self, config: PortForwardingConfig) -> Result<String, CoreError> {
+        let forward_id = format!(
+            "{}-{}-{}",
+            config.instance_id, config.pod, config.local_port
+        );
+
+        let mut forwards = self.active_forwards.write().await;
+        if forwards.contains_key(&forward_id) {
+            return Err(CoreError::PortForwarding(format!(
+                "Port forward already exists: {forward_id}"
+            )));
+        }
+
+        let state = PortForwardingState::new(&forward_id, &self.context, config.clone());
+        forwards.insert(forward_id.clone(), state);
+
+        drop(forwards);
+        let _ = self.events.send(PortForwardingEvent::Started {
+            forward_id: forward_id.clone(),
+        });
+
+        // Spawn the forward task
+        let lazy = config.lazy;
+        spawn_forward_task(
+            self.client.clone(),
+            Arc::clone(&self.active_forwards),
+            Arc::clone(&self.forward_tasks),
+            self.events.clone(),
+            forward_id.clone(),
+            config,
+            self.backoff.clone(),
+        )
+        .await?;
+
+        // Set status to Active immediately after spawning - the task is running
+        // It will handle its own errors and update status if needed
+        {
+            let mut forwards = self.active_forwards.write().await;
+            if let Some(state) = forwards.get_mut(&forward_id) {
+                // The supervisor may already have moved a forward that failed to bind, and lazy
+                // forwards go idle once bound
+                if state.status == PortForwardingStatus::Connecting && !lazy {
+                    set_status(state, PortForwardingStatus::Active, &self.events);
+                }
+            }
+        }
+
+        Ok(forward_id)
+    }
+
+    pub async fn list_forwards(&self) -> Vec<PortForwardingState> {
+        let forwards = self.active_forwards.read().await;
+        forwards.values().cloned().collect()
+    }
+
+    pub async fn get_forward(&self, forward_id: &str) -> Option<PortForwardingState> {
+        let forwards = self.active_forwards.read().await;
+        forwards.get(forward_id).cloned()
+    }
+
+    pub async fn list_forwards_by_instance(&self, instance_id: &str) -> Vec<PortForwardingState> {
+        let forwards = self.active_forwards.read().await;
+        forwards
+            .values()
+            .filter(|state| state.config.instance_id == instance_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Check if a port is available on the given bind address
+    ///
+    /// # Errors
+    /// Returns `CoreError::PortHeld` naming the process that holds the port when it can be
+    /// identified, or `CoreError::PortConflict` otherwise
+    pub async fn check_port_available(
+        &self,
+        bind: BindAddress,
+        local_port: u16,
+    ) -> Result<(), CoreError> {
+        match check_bind_available(bind, local_port) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(identify_port_holder(e).await),
+        }
+    }
+
+    /// Find an available port on the given bind address starting from the given port
+    ///
+    /// # Errors
+    /// Returns an error if no available ports are found
+    pub fn find_available_port(
+        &self,
+        bind: BindAddress,
+        start_port: u16,
+    ) -> Result<u16, CoreError> {
+        find_available_port(bind, start_port)
+    }
+
+    /// Reconnect a port forward now, including one that was marked failed
+    ///
+    /// The forward's supervisor rebinds it straight away, skipping any backoff delay, and
+    /// counts its retries afresh. Open connections are kept.
+    ///
+    /// # Errors
+    /// Returns an error if the forward is not found or its supervisor has stopped
+    pub async fn reconnect_forward(&self, forward_id: &str) -> Result<(), CoreError> {
+        let control_tx = self
+            .forward_tasks
+            .read()
+            .await
+            .get(forward_id)
+            .map(|(_, control_tx)| control_tx.clone())
+            .ok_or_else(|| CoreError::PortForwardingNotFound(forward_id.to_string()))?;
+
+        control_tx.send(TaskControl::Reconnect).await.map_err(|_| {
+            CoreError::PortForwarding(format!("Forward {forward_id} is no longer supervised"))
+        })
+    }
+}
@@ -0,0 +1,244 @@
+// Forward persistence
+//
+// This module saves requested forwards to ~/.roro/forwards.json and restores them on
+// launch, re-resolving pods since their names change across restarts.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::failover::{failover_decision, FailoverDecision};
+use crate::api::kubernetes::portforwarding::group::PortMapping;
+use crate::api::kubernetes::portforwarding::service::resolve_service_target;
+use crate::api::kubernetes::portforwarding::types::{ForwardTarget, PortForwardingConfig};
+use crate::api::kubernetes::portforwarding::workload::resolve_workload_target;
+use crate::bridge::{forward_to_record, record_to_forward};
+use crate::errors::CoreError;
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, ListParams};
+use roro_domain::PortValue;
+use roro_persistence::{
+    load_forward_records, save_forward_records, DesiredState, ForwardRecord, PersistenceError,
+};
+
+impl PortForwardingManager {
+    /// List saved forwards for this manager's context
+    ///
+    /// # Errors
+    /// Returns an error if the saved state cannot be read
+    pub async fn saved_forwards(&self) -> Result<Vec<ForwardRecord>, CoreError> {
+        let records = load_forward_records().await?;
+        Ok(records
+            .into_iter()
+            .filter(|record| record.context == self.context)
+            .collect())
+    }
+
+    /// Restart every saved forward in this context whose desired state is running
+    ///
+    /// Forwards that are already active are skipped, and members of a forward group are
+    /// restarted together as a group. Returns the outcome per record, with the new forward
+    /// id (or group id for group members) on success.
+    ///
+    /// # Errors
+    /// Returns an error if the saved state cannot be read
+    pub async fn restore_forwards(
+        &self,
+    ) -> Result<Vec<(ForwardRecord, Result<String, CoreError>)>, CoreError> {
+        let active_ports: Vec<u16> = self
+            .list_forwards()
+            .await
+            .iter()
+            .map(|state| state.config.local_port)
+            .collect();
+
+        let mut outcomes = Vec::new();
+        let mut groups: Vec<(String, Vec<ForwardRecord>)> = Vec::new();
+        for record in self.saved_forwards().await? {
+            if record.desired_state != DesiredState::Running
+                || active_ports.contains(&record.local_port)
+            {
+                continue;
+            }
+
+            if let Some(group_id) = record.group_id.clone() {
+                match groups.iter_mut().find(|(id, _)| *id == group_id) {
+                    Some((_, members)) => members.push(record),
+                    None => groups.push((group_id, vec![record])),
+                }
+                continue;
+            }
+
+            let result = match self.resolve_saved_forward(&record).await {
+                Ok(config) => self.start_forward(config).await,
+                Err(e) => Err(e),
+            };
+            outcomes.push((record, result));
+        }
+
+        for (_, members) in groups {
+            outcomes.extend(self.restore_group(members).await);
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Restart the saved members of a forward group as one group on a freshly resolved pod
+    async fn restore_group(
+        &self,
+        members: Vec<ForwardRecord>,
+    ) -> Vec<(ForwardRecord, Result<String, CoreError>)> {
+        let ports: Vec<PortMapping> = members
+            .iter()
+            .map(|record| PortMapping::new(record.remote_port, record.local_port))
+            .collect();
+
+        let result = match members.first() {
+            Some(first) => match self.resolve_saved_forward(first).await {
+                Ok(template) => self.start_group(template, &ports).await,
+                Err(e) => Err(e),
+            },
+            None => return Vec::new(),
+        };
+
+        match result {
+            Ok(group_id) => members
+                .into_iter()
+                .map(|record| (record, Ok(group_id.clone())))
+                .collect(),
+            Err(e) => {
+                let message = e.to_string();
+                members
+                    .into_iter()
+                    .map(|record| (record, Err(CoreError::PortForwarding(message.clone()))))
+                    .collect()
+            }
+        }
+    }
+
+    /// Remove a saved forward so it is no longer offered for restore
+    ///
+    /// # Errors
+    /// Returns an error if the saved state cannot be read or written
+    pub async fn forget_forward(&self, forward_id: &str) -> Result<(), CoreError> {
+        self.update_records(|records| records.retain(|record| record.id != forward_id))
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Resolve a saved forward to a config targeting a currently ready pod
+    async fn resolve_saved_forward(
+        &self,
+        record: &ForwardRecord,
+    ) -> Result<PortForwardingConfig, CoreError> {
+        let mut config = record_to_forward(record);
+
+        if let ForwardTarget::Service { name, port } = &config.target {
+            let resolved =
+                resolve_service_target(&self.client, &config.namespace, name, port).await?;
+            config.pod = resolved.pod;
+            config.remote_port = resolved.remote_port;
+        } else if let ForwardTarget::Workload {
+            kind,
+            name,
+            selection,
+            ordinal,
+        } = &config.target
+        {
+            // The saved remote port is already a container port
+            let resolved = resolve_workload_target(
+                &self.client,
+                &config.namespace,
+                *kind,
+                name,
+                *selection,
+                *ordinal,
+                &PortValue::Numeric(config.remote_port),
+            )
+            .await?;
+            config.pod = resolved.pod;
+        } else if let Some(selector) = &record.selector {
+            let pods: Api<Pod> = Api::namespaced(self.client.clone(), &config.namespace);
+            let pod_list = pods
+                .list(&ListParams::default().labels(selector))
+                .await
+                .map_err(|e| {
+                    CoreError::PortForwarding(format!(
+                        "Failed to list pods for {} in namespace {}: {e}",
+                        record.id, config.namespace
+                    ))
+                })?;
+
+            match failover_decision(&config.pod, &pod_list.items) {
+                FailoverDecision::Keep => {}
+                FailoverDecision::Retarget(pod) => config.pod = pod,
+                FailoverDecision::Unavailable => {
+                    return Err(CoreError::PortForwarding(format!(
+                        "No ready pods match {selector} in namespace {}",
+                        config.namespace
+                    )));
+                }
+            }
+        }
+        // Standalone pods keep their name and rely on start_forward's prefix matching
+
+        Ok(config)
+    }
+
+    /// Save a started forward, replacing any record for the same local port
+    pub(super) async fn record_forward(
+        &self,
+        forward_id: &str,
+        config: &PortForwardingConfig,
+        selector: Option<String>,
+    ) {
+        if !self.persist_state {
+            return;
+        }
+
+        let record = forward_to_record(
+            forward_id,
+            &self.context,
+            config,
+            selector,
+            DesiredState::Running,
+        );
+        let result = self
+            .update_records(|records| {
+                records
+                    .retain(|r| r.context != record.context || r.local_port != record.local_port);
+                records.push(record);
+            })
+            .await;
+
+        if let Err(e) = result {
+            eprintln!("[PortForward] Failed to save forward {forward_id}: {e}");
+        }
+    }
+
+    /// Mark a saved forward as stopped so it is not restored
+    pub(super) async fn record_stopped(&self, forward_id: &str) {
+        if !self.persist_state {
+            return;
+        }
+
+        let result = self
+            .update_records(|records| {
+                for record in records.iter_mut().filter(|r| r.id == forward_id) {
+                    record.desired_state = DesiredState::Stopped;
+                }
+            })
+            .await;
+
+        if let Err(e) = result {
+            eprintln!("[PortForward] Failed to save stopped forward {forward_id}: {e}");
+        }
+    }
+
+    async fn update_records(
+        &self,
+        update: impl FnOnce(&mut Vec<ForwardRecord>),
+    ) -> Result<(), PersistenceError> {
+        let _guard = self.state_lock.lock().await;
+        let mut records = load_forward_records().await?;
+        update(&mut records);
+        save_forward_records(&records).await
+    }
+}
@@ -0,0 +1,73 @@
+// Local port allocation
+//
+// This module picks local ports for forwards declared with a `localport` policy and
+// remembers the choice per instance in ~/.roro/ports.json.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::bind::{check_bind_available, BindAddress};
+use crate::api::kubernetes::portforwarding::holder::identify_port_holder;
+use crate::api::kubernetes::portforwarding::ports::choose_local_port;
+use crate::errors::CoreError;
+use roro_domain::LocalPortPolicy;
+use roro_persistence::{load_port_allocations, save_port_allocations, PortAllocation};
+
+impl PortForwardingManager {
+    /// Choose the local port for a forward of an instance and remember it
+    ///
+    /// `forward` identifies the forward within the instance. The port remembered for the
+    /// instance and forward is reused when it still satisfies `policy` and is free.
+    ///
+    /// # Errors
+    /// Returns an error if no port allowed by the policy is free on `bind`, naming the
+    /// process holding a fixed port when it can be identified
+    pub async fn allocate_local_port(
+        &self,
+        instance_id: &str,
+        forward: &str,
+        policy: LocalPortPolicy,
+        bind: BindAddress,
+    ) -> Result<u16, CoreError> {
+        let _guard = self.state_lock.lock().await;
+
+        let mut allocations = if self.persist_state {
+            load_port_allocations().await.unwrap_or_else(|e| {
+                eprintln!("[PortForward] Failed to load port allocations: {e}");
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+
+        let remembered = allocations
+            .iter()
+            .find(|a| a.instance_id == instance_id && a.forward == forward)
+            .map(|a| a.local_port);
+
+        let port = match choose_local_port(policy, remembered, |port| {
+            check_bind_available(bind, port).is_ok()
+        }) {
+            Ok(port) => port,
+            Err(e) => return Err(identify_port_holder(e).await),
+        };
+
+        if remembered != Some(port) {
+            println!(
+                "[PortForward] Allocated {bind}:{port} for {instance_id} {forward} ({policy})"
+            );
+        }
+
+        if self.persist_state && remembered != Some(port) {
+            allocations.retain(|a| a.instance_id != instance_id || a.forward != forward);
+            allocations.push(PortAllocation {
+                instance_id: instance_id.to_string(),
+                forward: forward.to_string(),
+                local_port: port,
+            });
+            if let Err(e) = save_port_allocations(&allocations).await {
+                eprintln!("[PortForward] Failed to save port allocations: {e}");
+            }
+        }
+
+        Ok(port)
+    }
+}
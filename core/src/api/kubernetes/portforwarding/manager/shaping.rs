@@ -0,0 +1,63 @@
+// Traffic shaping
+//
+// This module changes the latency, bandwidth and fault injection applied to running
+// forwards, for testing how clients cope with a slow or unreliable connection.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::events::PortForwardingEvent;
+use crate::api::kubernetes::portforwarding::shaping::{TrafficShaper, TrafficShaping};
+use crate::errors::CoreError;
+use std::sync::Arc;
+
+impl PortForwardingManager {
+    /// Degrade traffic through a forward with `shaping`, or restore it with `None`
+    ///
+    /// Open connections are affected from their next chunk on. Settings that change nothing
+    /// are treated as `None`. Subscribers get a `ShapingChanged` event.
+    ///
+    /// # Errors
+    /// Returns an error if the settings are invalid or the forward is not found
+    pub async fn set_shaping(
+        &self,
+        forward_id: &str,
+        shaping: Option<TrafficShaping>,
+    ) -> Result<(), CoreError> {
+        if let Some(shaping) = &shaping {
+            shaping.validate()?;
+        }
+
+        let shaper = self.forward_shaper(forward_id).await?;
+        shaper.set(shaping);
+        let active = shaper.is_active();
+        println!(
+            "[PortForward] Traffic shaping for {forward_id}: {}",
+            shaper
+                .shaping()
+                .map_or_else(|| "off".to_string(), |shaping| shaping.to_string())
+        );
+
+        // Sending only fails when nobody is subscribed
+        let _ = self.events.send(PortForwardingEvent::ShapingChanged {
+            forward_id: forward_id.to_string(),
+            active,
+        });
+        Ok(())
+    }
+
+    /// Shaping currently applied to a forward; `None` while its traffic is untouched
+    ///
+    /// # Errors
+    /// Returns an error if the forward is not found
+    pub async fn shaping(&self, forward_id: &str) -> Result<Option<TrafficShaping>, CoreError> {
+        Ok(self.forward_shaper(forward_id).await?.shaping())
+    }
+
+    async fn forward_shaper(&self, forward_id: &str) -> Result<Arc<TrafficShaper>, CoreError> {
+        self.active_forwards
+            .read()
+            .await
+            .get(forward_id)
+            .map(|state| Arc::clone(&state.shaper))
+            .ok_or_else(|| CoreError::PortForwardingNotFound(forward_id.to_string()))
+    }
+}
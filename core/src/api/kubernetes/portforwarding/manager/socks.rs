@@ -0,0 +1,61 @@
+// SOCKS proxy
+//
+// This module starts and stops the manager's SOCKS5 endpoint, which reaches any service in
+// the manager's context without declaring a forward for it.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::socks::{spawn_socks_proxy, SocksConfig};
+use crate::errors::CoreError;
+use std::net::SocketAddr;
+
+impl PortForwardingManager {
+    /// Start the SOCKS5 proxy, returning the addresses it listens on
+    ///
+    /// # Errors
+    /// Returns an error if the proxy is already running or its port cannot be bound
+    pub async fn start_socks_proxy(
+        &self,
+        config: SocksConfig,
+    ) -> Result<Vec<SocketAddr>, CoreError> {
+        let mut socks = self.socks_proxy.lock().await;
+        if socks.is_some() {
+            return Err(CoreError::PortForwarding(format!(
+                "SOCKS proxy is already running for context {}",
+                self.context
+            )));
+        }
+        let bind = format!("{}:{}", config.bind_address, config.port);
+        let running = spawn_socks_proxy(self.client.clone(), config).await?;
+        let addresses = running.addresses.clone();
+        *socks = Some(running);
+        println!(
+            "[PortForward] SOCKS proxy for context {} listening on {bind}",
+            self.context
+        );
+        Ok(addresses)
+    }
+
+    /// Stop the SOCKS5 proxy; returns whether it was running
+    ///
+    /// Connections already being tunnelled are left to finish.
+    pub async fn stop_socks_proxy(&self) -> bool {
+        let Some(running) = self.socks_proxy.lock().await.take() else {
+            return false;
+        };
+        running.task.abort();
+        println!(
+            "[PortForward] SOCKS proxy for context {} stopped",
+            self.context
+        );
+        true
+    }
+
+    /// Addresses the SOCKS5 proxy listens on, or `None` if it is not running
+    pub async fn socks_proxy_addresses(&self) -> Option<Vec<SocketAddr>> {
+        self.socks_proxy
+            .lock()
+            .await
+            .as_ref()
+            .map(|running| running.addresses.clone())
+    }
+}
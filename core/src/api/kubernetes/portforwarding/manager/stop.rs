@@ -0,0 +1,105 @@
+// Stopping forwards
+//
+// This module stops forwards either gracefully, closing the listener and draining open
+// connections up to the drain timeout, or immediately.
+
+use super::PortForwardingManager;
+use crate::api::kubernetes::portforwarding::events::PortForwardingEvent;
+use crate::api::kubernetes::portforwarding::task::TaskControl;
+use crate::api::kubernetes::portforwarding::types::{StopMode, StopSummary};
+use crate::errors::CoreError;
+use futures::future::join_all;
+use std::time::Duration;
+
+impl PortForwardingManager {
+    /// Stop a port forward, letting open connections finish up to the drain timeout
+    ///
+    /// # Errors
+    /// Returns an error if the forward is not found
+    pub async fn stop_forward(&self, forward_id: &str) -> Result<StopSummary, CoreError> {
+        self.stop_forward_with(forward_id, StopMode::Drain).await
+    }
+
+    /// Stop a port forward, closing open connections immediately
+    ///
+    /// # Errors
+    /// Returns an error if the forward is not found
+    pub async fn stop_forward_now(&self, forward_id: &str) -> Result<StopSummary, CoreError> {
+        self.stop_forward_with(forward_id, StopMode::Now).await
+    }
+
+    /// Stop a port forward and mark it stopped in saved state
+    ///
+    /// # Errors
+    /// Returns an error if the forward is not found
+    pub async fn stop_forward_with(
+        &self,
+        forward_id: &str,
+        mode: StopMode,
+    ) -> Result<StopSummary, CoreError> {
+        let summary = self.halt_forward(forward_id, mode).await?;
+        self.record_stopped(forward_id).await;
+        Ok(summary)
+    }
+
+    /// Stop every forward without changing saved state, e.g. when the process exits
+    ///
+    /// Saved forwards stay marked as running so they are offered for restore next launch.
+    /// The SOCKS proxy, if running, stops too; the DNS responder serves every context and is
+    /// left running.
+    pub async fn shutdown(&self, mode: StopMode) -> StopSummary {
+        self.stop_socks_proxy().await;
+        let forward_ids: Vec<String> = self.active_forwards.read().await.keys().cloned().collect();
+        join_all(forward_ids.iter().map(|id| self.halt_forward(id, mode)))
+            .await
+            .into_iter()
+            .flatten()
+            .fold(StopSummary::default(), |total, summary| total + summary)
+    }
+
+    /// Remove a forward, close its listener and drain or close its connections
+    pub(super) async fn halt_forward(
+        &self,
+        forward_id: &str,
+        mode: StopMode,
+    ) -> Result<StopSummary, CoreError> {
+        if self
+            .active_forwards
+            .write()
+            .await
+            .remove(forward_id)
+            .is_none()
+        {
+            return Err(CoreError::PortForwardingNotFound(forward_id.to_string()));
+        }
+
+        if let Some(handle) = self.failover_tasks.write().await.remove(forward_id) {
+            handle.abort();
+        }
+
+        let task = self.forward_tasks.write().await.remove(forward_id);
+        let summary = match task {
+            Some((handle, control_tx)) => {
+                let drain = match mode {
+                    StopMode::Drain => self.drain_timeout,
+                    StopMode::Now => Duration::ZERO,
+                };
+                let _ = control_tx.send(TaskControl::Stop(drain)).await;
+                handle.await.unwrap_or_default()
+            }
+            None => StopSummary::default(),
+        };
+
+        if summary.force_closed > 0 {
+            println!(
+                "[PortForward] Stopped {forward_id}, force-closed {} open connection(s)",
+                summary.force_closed
+            );
+        }
+        let _ = self.events.send(PortForwardingEvent::Stopped {
+            forward_id: forward_id.to_string(),
+        });
+
+        Ok(summary)
+    }
+}
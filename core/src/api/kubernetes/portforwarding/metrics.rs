@@ -0,0 +1,351 @@
+// Port forwarding metrics
+//
+// This module tracks per-forward traffic and connection counters, updated from the
+// forwarding task's copy loops and read through the manager's forward state. The copy loops
+// can also show every chunk they copy to an observer, which traffic capture uses.
+
+use crate::api::kubernetes::portforwarding::shaping::{
+    injected_reset, is_injected_reset, ChunkPlan, DelayQueue, TrafficShaper,
+};
+use futures::future::{select, Either};
+use std::pin::pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::time::Instant;
+
+const COPY_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Live traffic and connection counters for a single forward
+///
+/// Counters are shared between the forward state and its task, so they survive reconnects.
+#[derive(Debug, Default)]
+pub struct ForwardMetrics {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    total_connections: AtomicU64,
+    open_connections: AtomicU64,
+    failed_connections: AtomicU64,
+    /// Milliseconds since the Unix epoch; zero means no activity yet
+    last_activity_ms: AtomicU64,
+}
+
+/// Point-in-time copy of a forward's metrics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwardMetricsSnapshot {
+    /// Bytes copied from local clients to the pod
+    pub bytes_sent: u64,
+    /// Bytes copied from the pod to local clients
+    pub bytes_received: u64,
+    /// Local connections accepted since the forward started
+    pub total_connections: u64,
+    /// Connections currently tunnelled to the pod
+    pub open_connections: u64,
+    /// Connections that could not be tunnelled to the pod
+    pub failed_connections: u64,
+    pub last_activity: Option<SystemTime>,
+}
+
+/// Direction of traffic through a tunnel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Local client to pod
+    Sent,
+    /// Pod to local client
+    Received,
+}
+
+impl ForwardMetrics {
+    pub fn record_accepted(&self) {
+        self.total_connections.fetch_add(1, Ordering::Relaxed);
+        self.touch();
+    }
+
+    pub fn record_opened(&self) {
+        self.open_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_closed(&self) {
+        // Saturate so a mismatched close can never wrap the gauge
+        let _ = self
+            .open_connections
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |open| {
+                Some(open.saturating_sub(1))
+            });
+        self.touch();
+    }
+
+    pub fn record_failed(&self) {
+        self.failed_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_bytes(&self, direction: Direction, bytes: u64) {
+        let counter = match direction {
+            Direction::Sent => &self.bytes_sent,
+            Direction::Received => &self.bytes_received,
+        };
+        counter.fetch_add(bytes, Ordering::Relaxed);
+        self.touch();
+    }
+
+    #[must_use]
+    pub fn snapshot(&self) -> ForwardMetricsSnapshot {
+        let last_activity_ms = self.last_activity_ms.load(Ordering::Relaxed);
+        ForwardMetricsSnapshot {
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            total_connections: self.total_connections.load(Ordering::Relaxed),
+            open_connections: self.open_connections.load(Ordering::Relaxed),
+            failed_connections: self.failed_connections.load(Ordering::Relaxed),
+            last_activity: (last_activity_ms > 0)
+                .then(|| UNIX_EPOCH + Duration::from_millis(last_activity_ms)),
+        }
+    }
+
+    fn touch(&self) {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+        self.last_activity_ms.store(now_ms, Ordering::Relaxed);
+    }
+}
+
+/// Copy from `reader` to `writer` until EOF, recording bytes against `metrics`
+///
+/// # Errors
+/// Returns an error if reading or writing fails
+pub async fn copy_with_metrics<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    metrics: &ForwardMetrics,
+    direction: Direction,
+) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    copy_observed(reader, writer, metrics, direction, &|_, _| {}, None).await
+}
+
+/// Copy from `reader` to `writer` until EOF, showing each chunk to `observe` once written
+///
+/// While `shaper` shapes traffic, each chunk is delayed, discarded or resets the copy as
+/// it decides.
+async fn copy_observed<R, W, O>(
+    reader: &mut R,
+    writer: &mut W,
+    metrics: &ForwardMetrics,
+    direction: Direction,
+    observe: &O,
+    shaper: Option<&TrafficShaper>,
+) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+    O: Fn(Direction, &[u8]) + Sync,
+{
+    if let Some(shaper) = shaper {
+        return copy_shaped(reader, writer, metrics, direction, observe, shaper).await;
+    }
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    let mut total = 0u64;
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            writer.flush().await?;
+            return Ok(total);
+        }
+        writer.write_all(&buffer[..read]).await?;
+        observe(direction, &buffer[..read]);
+        let read = read as u64;
+        total += read;
+        metrics.record_bytes(direction, read);
+    }
+}
+
+/// Copy like `copy_observed`, holding chunks in a delay queue while they are due
+///
+/// Reading goes on while earlier chunks wait, so each chunk is delayed from when it was
+/// read rather than after the chunks before it were written.
+async fn copy_shaped<R, W, O>(
+    reader: &mut R,
+    writer: &mut W,
+    metrics: &ForwardMetrics,
+    direction: Direction,
+    observe: &O,
+    shaper: &TrafficShaper,
+) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+    O: Fn(Direction, &[u8]) + Sync,
+{
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    let mut queue = DelayQueue::new();
+    let mut reading = true;
+    let mut total = 0u64;
+    loop {
+        let due = queue.next_due();
+        if !reading && due.is_none() {
+            writer.flush().await?;
+            return Ok(total);
+        }
+        tokio::select! {
+            read = reader.read(&mut buffer), if reading && !queue.is_full() => {
+                let read = read?;
+                reading = read > 0;
+                if reading {
+                    queue_chunk(&mut queue, shaper, &buffer[..read])?;
+                }
+            }
+            () = tokio::time::sleep_until(due.unwrap_or_else(Instant::now)), if due.is_some() => {
+                let chunk = queue.pop().unwrap_or_default();
+                writer.write_all(&chunk).await?;
+                observe(direction, &chunk);
+                let written = chunk.len() as u64;
+                total += written;
+                metrics.record_bytes(direction, written);
+            }
+        }
+    }
+}
+
+/// Queue a chunk as the shaper plans it
+///
+/// # Errors
+/// Returns the injected reset error if the shaper resets the connection
+fn queue_chunk(queue: &mut DelayQueue, shaper: &TrafficShaper, chunk: &[u8]) -> io::Result<()> {
+    match shaper.plan_chunk(chunk.len()) {
+        None => queue.push(chunk.to_vec(), Duration::ZERO, Duration::ZERO),
+        Some(ChunkPlan::Copy { latency, transfer }) => {
+            queue.push(chunk.to_vec(), latency, transfer);
+        }
+        Some(ChunkPlan::Discard) => {}
+        Some(ChunkPlan::Reset) => return Err(injected_reset()),
+    }
+    Ok(())
+}
+
+/// Copy both directions between `local` and `remote` until each side reaches EOF
+///
+/// When one direction finishes, the write side of its destination is shut down so the peer
+/// sees a half-close, while the other direction keeps flowing until it finishes too.
+///
+/// # Errors
+/// Returns the first error from either direction, after both have finished
+pub async fn copy_bidirectional_with_metrics<L, R>(
+    local: L,
+    remote: R,
+    metrics: &ForwardMetrics,
+) -> io::Result<(u64, u64)>
+where
+    L: AsyncRead + AsyncWrite + Unpin,
+    R: AsyncRead + AsyncWrite + Unpin,
+{
+    copy_both(local, remote, metrics, &|_, _| {}, None).await
+}
+
+/// Copy both directions like `copy_bidirectional_with_metrics`, showing every chunk copied
+/// to `observe` along with its direction
+///
+/// # Errors
+/// Returns the first error from either direction, after both have finished
+pub async fn copy_bidirectional_observed<L, R, O>(
+    local: L,
+    remote: R,
+    metrics: &ForwardMetrics,
+    observe: &O,
+) -> io::Result<(u64, u64)>
+where
+    L: AsyncRead + AsyncWrite + Unpin,
+    R: AsyncRead + AsyncWrite + Unpin,
+    O: Fn(Direction, &[u8]) + Sync,
+{
+    copy_both(local, remote, metrics, observe, None).await
+}
+
+/// Copy both directions like `copy_bidirectional_observed`, degrading traffic as `shaper`
+/// currently says
+///
+/// A reset injected in either direction closes the whole connection straight away.
+///
+/// # Errors
+/// Returns an error if the connection was reset by fault injection, or else the first error
+/// from either direction after both have finished
+pub async fn copy_bidirectional_shaped<L, R, O>(
+    local: L,
+    remote: R,
+    metrics: &ForwardMetrics,
+    observe: &O,
+    shaper: &TrafficShaper,
+) -> io::Result<(u64, u64)>
+where
+    L: AsyncRead + AsyncWrite + Unpin,
+    R: AsyncRead + AsyncWrite + Unpin,
+    O: Fn(Direction, &[u8]) + Sync,
+{
+    copy_both(local, remote, metrics, observe, Some(shaper)).await
+}
+
+async fn copy_both<L, R, O>(
+    local: L,
+    remote: R,
+    metrics: &ForwardMetrics,
+    observe: &O,
+    shaper: Option<&TrafficShaper>,
+) -> io::Result<(u64, u64)>
+where
+    L: AsyncRead + AsyncWrite + Unpin,
+    R: AsyncRead + AsyncWrite + Unpin,
+    O: Fn(Direction, &[u8]) + Sync,
+{
+    let (mut local_read, mut local_write) = io::split(local);
+    let (mut remote_read, mut remote_write) = io::split(remote);
+
+    let sent = async {
+        let copied = copy_observed(
+            &mut local_read,
+            &mut remote_write,
+            metrics,
+            Direction::Sent,
+            observe,
+            shaper,
+        )
+        .await;
+        let _ = remote_write.shutdown().await;
+        copied
+    };
+    let received = async {
+        let copied = copy_observed(
+            &mut remote_read,
+            &mut local_write,
+            metrics,
+            Direction::Received,
+            observe,
+            shaper,
+        )
+        .await;
+        let _ = local_write.shutdown().await;
+        copied
+    };
+
+    // An injected reset drops both halves at once instead of waiting for the other direction
+    let (sent, received) = (pin!(sent), pin!(received));
+    let copied = match select(sent, received).await {
+        Either::Left((sent, received)) => {
+            if sent.as_ref().is_err_and(is_injected_reset) {
+                return sent.map(|_| (0, 0));
+            }
+            let received = received.await;
+            Ok((sent?, received?))
+        }
+        Either::Right((received, sent)) => {
+            if received.as_ref().is_err_and(is_injected_reset) {
+                return received.map(|_| (0, 0));
+            }
+            let sent = sent.await;
+            Ok((sent?, received?))
+        }
+    };
+    copied
+}
@@ -1,11 +1,93 @@
 // Port forwarding module
 //
-// This module provides port forwarding functionality for Kubernetes pods.
+// This module provides port forwarding functionality for Kubernetes pods and services.
 
+mod backoff;
+mod bind;
+mod capture;
+mod connections;
+mod dns;
+mod events;
+mod failover;
+mod group;
+mod har;
 mod health;
+mod holder;
+mod http;
+mod lazy;
+mod loopback;
 mod manager;
+mod metrics;
+mod pods;
+mod ports;
+mod proxy;
+mod service;
+mod service_proxy;
+mod session;
+mod shaping;
+mod socks;
 mod task;
+mod tls;
 mod types;
+mod workload;
 
+pub use backoff::BackoffPolicy;
+pub use bind::{check_bind_available, find_available_port, BindAddress};
+pub use capture::{CaptureConfig, ConnectionTap, HttpCapture, HttpExchange};
+pub use connections::ConnectionSet;
+pub use dns::{
+    current_service_hosts, dns_response, dns_server_address, start_dns_server, stop_dns_server,
+    DEFAULT_DNS_ADDRESS,
+};
+pub use events::{set_status, PortForwardingEvent};
+pub use failover::{failover_decision, FailoverDecision};
+pub use group::{group_id, group_status, validate_port_mappings, PortMapping};
+pub use har::{har_log, rfc3339};
+pub use health::{http_probe, http_probe_request, parse_status_code, record_health};
+pub use holder::{
+    find_port_holder, identify_port_holder, listening_inodes, parse_cmdline, socket_inode,
+    terminate_port_holder, HolderKind, PortHolder,
+};
+pub use http::{HttpHead, HttpParser, MessageKind, ParseEvent};
+pub use lazy::{idle_remaining, resolve_wake_target, WakeTarget};
+pub use loopback::{
+    allocate_service_ip, is_service_ip, service_host_names, service_hosts, ServiceHost,
+    FIRST_SERVICE_IP,
+};
 pub use manager::PortForwardingManager;
-pub use types::{PortForwardingConfig, PortForwardingState, PortForwardingStatus};
+pub use metrics::{
+    copy_bidirectional_observed, copy_bidirectional_shaped, copy_bidirectional_with_metrics,
+    copy_with_metrics, Direction, ForwardMetrics, ForwardMetricsSnapshot,
+};
+pub use pods::{is_pod_ready, label_selector, match_pods, matching_pods, PodMatch};
+pub use ports::{choose_local_port, AUTO_PORT_RANGE};
+pub use proxy::{
+    current_proxy_routes, find_route, proxy_addresses, proxy_routes, serve_connection, start_proxy,
+    stop_proxy, ProxyConfig, ProxyRoute, DEFAULT_PROXY_PORT, PROXY_DOMAIN,
+};
+pub use service::{
+    find_service_port, ready_endpoint_pods, resolve_service_target, target_container_port,
+    ResolvedTarget,
+};
+pub use service_proxy::{
+    is_relayed_header, service_proxy_path, ServiceProxy, MAX_PROXY_REQUEST_BODY,
+};
+pub use session::{
+    spawn_session, ForwardSessions, TunnelStream, KEEPALIVE_INTERVAL, MAX_CONCURRENT_OPENS,
+};
+pub use shaping::{ChunkPlan, TrafficShaper, TrafficShaping};
+pub use socks::{
+    parse_cluster_host, serve_socks_connection, ClusterService, SocksConfig, DEFAULT_SOCKS_PORT,
+};
+pub use tls::{
+    export_ca, is_permitted_host, local_ca, LocalCa, CA_COMMON_NAME, DEFAULT_TLS_HOSTS,
+    PERMITTED_TLS_DOMAINS,
+};
+pub use types::{
+    AppForwardOutcome, ForwardTarget, HealthStatus, InstanceStartReport, PortForwardingConfig,
+    PortForwardingState, PortForwardingStatus, StartMode, StopMode, StopSummary,
+};
+pub use workload::{
+    container_port, ready_pod_names, resolve_workload_target, select_pod, selector_query,
+    stateful_set_pod_name, workload_selector, ResolvedWorkload, STATEFUL_SET_POD_NAME_LABEL,
+};
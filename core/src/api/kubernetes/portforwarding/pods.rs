@@ -0,0 +1,190 @@
+// Pod inspection helpers for port forwarding
+//
+// This module inspects pod metadata and status to decide which pods can serve a port forward
+// and which pod a configured name refers to.
+
+use crate::errors::CoreError;
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, ListParams};
+use std::collections::BTreeMap;
+
+/// Check whether a pod is running, reports the `Ready` condition and is not being deleted
+#[must_use]
+pub fn is_pod_ready(pod: &Pod) -> bool {
+    if pod.metadata.deletion_timestamp.is_some() {
+        return false;
+    }
+
+    let Some(status) = &pod.status else {
+        return false;
+    };
+
+    if status.phase.as_deref() != Some("Running") {
+        return false;
+    }
+
+    status.conditions.as_ref().is_some_and(|conditions| {
+        conditions
+            .iter()
+            .any(|c| c.type_ == "Ready" && c.status == "True")
+    })
+}
+
+/// Build a Kubernetes label selector string (`a=b,c=d`) from a selector map
+#[must_use]
+pub fn label_selector(labels: &BTreeMap<String, String>) -> String {
+    labels
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Outcome of matching a configured pod name against the pods of a namespace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PodMatch {
+    /// A ready pod to forward to
+    Ready(String),
+    /// Matching pods exist but none is ready yet; lists them
+    NotReady(Vec<String>),
+    /// The name is a prefix of pods from several workloads; lists them
+    Ambiguous(Vec<String>),
+    /// No pod matches
+    NotFound,
+}
+
+/// Match a configured pod name against `pods`, preferring ready pods
+///
+/// Pods being deleted are ignored. A pod with exactly the configured name is used if it is
+/// ready. Otherwise pods whose names start with it are grouped by the workload that owns
+/// them (e.g. `api` and `api-gateway` for `ap`); a workload named exactly like the
+/// configured name wins, and prefixes of several workloads are ambiguous. The first ready
+/// pod of the matched workload by name is chosen.
+#[must_use]
+pub fn match_pods<'a>(name: &str, pods: impl IntoIterator<Item = &'a Pod>) -> PodMatch {
+    let candidates = match matching_pods(name, pods) {
+        Ok(candidates) => candidates,
+        Err(outcome) => return outcome,
+    };
+
+    let ready = pod_names(candidates.iter().copied().filter(|pod| is_pod_ready(pod)));
+    match ready.into_iter().next() {
+        Some(pod) => PodMatch::Ready(pod),
+        None => PodMatch::NotReady(pod_names(candidates)),
+    }
+}
+
+/// The pods a configured pod name refers to, ready or not, as `match_pods` finds them
+///
+/// Returns the pod named exactly `name`, or else the pods of the one workload matched by
+/// prefix. Pods being deleted are left out.
+///
+/// # Errors
+/// Returns `PodMatch::Ambiguous` if the name is a prefix of pods from several workloads,
+/// or `PodMatch::NotFound` if no pod matches
+pub fn matching_pods<'a>(
+    name: &str,
+    pods: impl IntoIterator<Item = &'a Pod>,
+) -> Result<Vec<&'a Pod>, PodMatch> {
+    let live: Vec<&Pod> = pods
+        .into_iter()
+        .filter(|pod| pod.metadata.deletion_timestamp.is_none())
+        .collect();
+
+    if let Some(pod) = live
+        .iter()
+        .find(|pod| pod.metadata.name.as_deref() == Some(name))
+    {
+        return Ok(vec![*pod]);
+    }
+
+    let mut workloads: BTreeMap<String, Vec<&Pod>> = BTreeMap::new();
+    for pod in live {
+        if pod
+            .metadata
+            .name
+            .as_deref()
+            .is_some_and(|n| n.starts_with(name))
+        {
+            workloads.entry(workload_name(pod)).or_default().push(pod);
+        }
+    }
+
+    match workloads.remove(name) {
+        Some(pods) => Ok(pods),
+        None if workloads.len() > 1 => Err(PodMatch::Ambiguous(pod_names(
+            workloads.into_values().flatten(),
+        ))),
+        None => workloads.into_values().next().ok_or(PodMatch::NotFound),
+    }
+}
+
+/// Name of the workload owning a pod, or the pod's own name for standalone pods
+///
+/// Pods of a Deployment are owned by a `ReplicaSet` named after the Deployment plus the
+/// pod-template hash, which is stripped so every rollout maps to the same workload.
+fn workload_name(pod: &Pod) -> String {
+    let owner = pod
+        .metadata
+        .owner_references
+        .iter()
+        .flatten()
+        .find(|owner| owner.controller == Some(true));
+    let Some(owner) = owner else {
+        return pod.metadata.name.clone().unwrap_or_default();
+    };
+
+    let hash = pod
+        .metadata
+        .labels
+        .as_ref()
+        .and_then(|labels| labels.get("pod-template-hash"));
+    match hash {
+        Some(hash) if owner.kind == "ReplicaSet" => owner
+            .name
+            .strip_suffix(hash.as_str())
+            .and_then(|name| name.strip_suffix('-'))
+            .unwrap_or(&owner.name)
+            .to_string(),
+        _ => owner.name.clone(),
+    }
+}
+
+fn pod_names<'a>(pods: impl IntoIterator<Item = &'a Pod>) -> Vec<String> {
+    let mut names: Vec<String> = pods
+        .into_iter()
+        .filter_map(|pod| pod.metadata.name.clone())
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+/// Look a configured pod name up among the pods of `namespace`, see `match_pods`
+///
+/// Returns `Ok(Err(reason))` while no matching pod is ready, so callers can wait for one.
+///
+/// # Errors
+/// Returns an error if the pods cannot be listed or the name is ambiguous
+pub(crate) async fn find_ready_pod(
+    pods: &Api<Pod>,
+    namespace: &str,
+    name: &str,
+) -> Result<Result<String, String>, CoreError> {
+    let pod_list = pods.list(&ListParams::default()).await.map_err(|e| {
+        CoreError::PortForwarding(format!("Failed to list pods in namespace {namespace}: {e}"))
+    })?;
+
+    match match_pods(name, &pod_list.items) {
+        PodMatch::Ready(pod) => Ok(Ok(pod)),
+        PodMatch::Ambiguous(candidates) => {
+            Err(CoreError::AmbiguousPod(name.to_string(), candidates))
+        }
+        PodMatch::NotReady(candidates) => Ok(Err(format!(
+            "No ready pod for {name} in namespace {namespace} (matching pods: {})",
+            candidates.join(", ")
+        ))),
+        PodMatch::NotFound => Ok(Err(format!(
+            "Pod {name} not found in namespace {namespace} (and no pods starting with this name)"
+        ))),
+    }
+}
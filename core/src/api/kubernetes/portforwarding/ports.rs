@@ -0,0 +1,51 @@
+// Local port allocation
+//
+// This module chooses a forward's local port from its `localport` policy, preferring the
+// port remembered for the instance so allocations stay stable across restarts.
+
+use crate::errors::CoreError;
+use roro_domain::LocalPortPolicy;
+use std::ops::RangeInclusive;
+
+/// Ports searched for `localport: "auto"` (the IANA dynamic range)
+pub const AUTO_PORT_RANGE: RangeInclusive<u16> = 49152..=65535;
+
+/// Choose a local port satisfying `policy`
+///
+/// A `remembered` port is reused when it still satisfies the policy and is free, otherwise
+/// the lowest free port allowed by the policy is chosen.
+///
+/// # Errors
+/// Returns `CoreError::PortConflict` if a fixed port is in use, or `CoreError::PortForwarding`
+/// if no port allowed by the policy is free
+pub fn choose_local_port(
+    policy: LocalPortPolicy,
+    remembered: Option<u16>,
+    is_free: impl Fn(u16) -> bool,
+) -> Result<u16, CoreError> {
+    let candidates = match policy {
+        LocalPortPolicy::Fixed(port) => {
+            return if is_free(port) {
+                Ok(port)
+            } else {
+                Err(CoreError::PortConflict(port))
+            };
+        }
+        LocalPortPolicy::Preferred(port) => port..=u16::MAX,
+        LocalPortPolicy::Range(start, end) => start..=end,
+        LocalPortPolicy::Auto => AUTO_PORT_RANGE,
+    };
+
+    if let Some(port) = remembered.filter(|port| {
+        (candidates.contains(port) || policy == LocalPortPolicy::Auto) && is_free(*port)
+    }) {
+        return Ok(port);
+    }
+
+    candidates
+        .into_iter()
+        .find(|port| is_free(*port))
+        .ok_or_else(|| {
+            CoreError::PortForwarding(format!("No available local port for localport '{policy}'"))
+        })
+}
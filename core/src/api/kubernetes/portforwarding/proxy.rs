@@ -0,0 +1,367 @@
+// Host-based local reverse proxy
+//
+// This module runs a single local HTTP entry point that routes each connection by its Host
+// header to a forward's local listener, so `api.myapp.localhost` reaches the `api` forward of
+// instance `myapp` without remembering its port. Routes are rebuilt from the forwards of every
+// context on each connection, so starting and stopping forwards needs no proxy restart.
+
+use crate::api::kubernetes::portforwarding::bind::{accept_any, bind_listeners, BindAddress};
+use crate::api::kubernetes::portforwarding::http::{HttpParser, MessageKind, ParseEvent};
+use crate::api::kubernetes::portforwarding::types::{ForwardTarget, PortForwardingState};
+use crate::api::kubernetes::portforwarding_registry::list_all_forwards;
+use crate::errors::CoreError;
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Mutex, PoisonError};
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+
+/// Port the proxy listens on unless configured otherwise
+pub const DEFAULT_PROXY_PORT: u16 = 8080;
+
+/// Domain route host names end in; browsers resolve `*.localhost` to loopback
+pub const PROXY_DOMAIN: &str = "localhost";
+
+/// Largest request head read while looking for the Host header
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+
+/// Longest DNS label a route name part may use
+const MAX_LABEL_LEN: usize = 63;
+
+/// The running proxy, if any
+static PROXY: Mutex<Option<RunningProxy>> = Mutex::new(None);
+
+struct RunningProxy {
+    addresses: Vec<SocketAddr>,
+    task: JoinHandle<()>,
+}
+
+/// Where the proxy listens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProxyConfig {
+    pub bind_address: BindAddress,
+    pub port: u16,
+    /// Opt-in required to listen on all interfaces, like a forward's `exposeToNetwork`
+    pub expose_to_network: bool,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: BindAddress::Localhost,
+            port: DEFAULT_PROXY_PORT,
+            expose_to_network: false,
+        }
+    }
+}
+
+/// A host name the proxy routes and the forward behind it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyRoute {
+    /// Host name matched against the Host header, e.g. `api.myapp.localhost`
+    pub host: String,
+    pub forward_id: String,
+    /// Kube context of the manager running the forward
+    pub context: String,
+    /// Local listener of the forward that requests are relayed to
+    pub target: SocketAddr,
+}
+
+/// Build the routes for `forwards`
+///
+/// A forward is routed as `<name>.<instance id>.localhost`, where the name is the service
+/// or workload name from app.json or the pod name for pod forwards. When several forwards
+/// would share a host, for example ports of one service, the later ones get
+/// `<name>-<remote port>` instead; forwards are considered in the order given. Forwards that
+/// terminate TLS are not routed, since the proxy relays plaintext HTTP.
+#[must_use]
+pub fn proxy_routes(forwards: &[PortForwardingState]) -> Vec<ProxyRoute> {
+    let mut taken = HashSet::new();
+    let mut routes = Vec::new();
+    for state in forwards.iter().filter(|state| !state.config.tls) {
+        let config = &state.config;
+        let name = match &config.target {
+            ForwardTarget::Service { name, .. } | ForwardTarget::Workload { name, .. } => name,
+            ForwardTarget::Pod => &config.pod,
+        };
+        let candidates = [
+            route_host(name, &config.instance_id),
+            route_host(
+                &format!("{name}-{}", config.remote_port),
+                &config.instance_id,
+            ),
+        ];
+        let Some(host) = candidates.into_iter().find(|host| !taken.contains(host)) else {
+            eprintln!(
+                "[PortForward] No free proxy host name for forward {}",
+                state.id
+            );
+            continue;
+        };
+        taken.insert(host.clone());
+        routes.push(ProxyRoute {
+            host,
+            forward_id: state.id.clone(),
+            context: state.context.clone(),
+            target: SocketAddr::new(target_ip(config.bind_address), config.local_port),
+        });
+    }
+    routes
+}
+
+/// Find the route for a Host header value, ignoring its port and letter case
+#[must_use]
+pub fn find_route<'a>(routes: &'a [ProxyRoute], host_header: &str) -> Option<&'a ProxyRoute> {
+    let host = host_header.trim();
+    // Keep IPv6 literals like `[::1]:8080` intact; only strip a trailing port
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if !name.contains(':') && port.bytes().all(|b| b.is_ascii_digit()) => {
+            name
+        }
+        _ => host,
+    };
+    let host = host.trim_end_matches('.');
+    routes
+        .iter()
+        .find(|route| route.host.eq_ignore_ascii_case(host))
+}
+
+/// Routes for the forwards currently running in every context
+pub async fn current_proxy_routes() -> Vec<ProxyRoute> {
+    proxy_routes(&list_all_forwards().await)
+}
+
+/// Start the proxy, returning the addresses it listens on
+///
+/// Unknown hosts are answered with the routed hosts only while the proxy listens on loopback.
+///
+/// # Errors
+/// Returns `CoreError::Validation` if the proxy would listen on all interfaces without
+/// `expose_to_network`, or an error if it is already running or its port cannot be bound
+pub async fn start_proxy(config: ProxyConfig) -> Result<Vec<SocketAddr>, CoreError> {
+    if let BindAddress::Ip(ip) = config.bind_address {
+        if ip.is_unspecified() && !config.expose_to_network {
+            return Err(CoreError::Validation(format!(
+                "Reverse proxy bind address '{ip}' listens on all interfaces and requires exposeToNetwork"
+            )));
+        }
+    }
+    if proxy_addresses().is_some() {
+        return Err(CoreError::PortForwarding(
+            "Reverse proxy is already running".to_string(),
+        ));
+    }
+
+    let listeners = bind_listeners(config.bind_address, config.port)
+        .await
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::AddrInUse => CoreError::PortConflict(config.port),
+            _ => CoreError::PortForwarding(format!(
+                "Failed to bind reverse proxy on {}:{}: {e}",
+                config.bind_address, config.port
+            )),
+        })?;
+    let addresses: Vec<SocketAddr> = listeners
+        .iter()
+        .filter_map(|listener| listener.local_addr().ok())
+        .collect();
+
+    let list_routes = !config.bind_address.is_exposed();
+    let task = tokio::spawn(async move {
+        loop {
+            let stream = match accept_any(&listeners).await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    eprintln!("[PortForward] Reverse proxy stopped accepting: {e}");
+                    return;
+                }
+            };
+            tokio::spawn(async move {
+                let routes = current_proxy_routes().await;
+                if let Err(e) = serve_connection(stream, &routes, list_routes).await {
+                    eprintln!("[PortForward] Reverse proxy connection failed: {e}");
+                }
+            });
+        }
+    });
+
+    let mut proxy = PROXY.lock().unwrap_or_else(PoisonError::into_inner);
+    if proxy.is_some() {
+        // Another caller started the proxy while this one was binding
+        task.abort();
+        return Err(CoreError::PortForwarding(
+            "Reverse proxy is already running".to_string(),
+        ));
+    }
+    println!(
+        "[PortForward] Reverse proxy listening on {}:{}",
+        config.bind_address, config.port
+    );
+    *proxy = Some(RunningProxy {
+        addresses: addresses.clone(),
+        task,
+    });
+    Ok(addresses)
+}
+
+/// Stop the proxy; returns whether it was running
+///
+/// Connections already being relayed are left to finish.
+pub fn stop_proxy() -> bool {
+    let running = PROXY.lock().unwrap_or_else(PoisonError::into_inner).take();
+    match running {
+        Some(running) => {
+            running.task.abort();
+            println!("[PortForward] Reverse proxy stopped");
+            true
+        }
+        None => false,
+    }
+}
+
+/// Addresses the proxy listens on, or `None` if it is not running
+#[must_use]
+pub fn proxy_addresses() -> Option<Vec<SocketAddr>> {
+    PROXY
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map(|running| running.addresses.clone())
+}
+
+/// Route one client connection by the Host header of its first request
+///
+/// The connection is relayed to a single forward for its lifetime, so keep-alive requests
+/// for another host on the same connection reach the first forward. Clients open a
+/// connection per host, so this only matters for hand-written requests. Unknown hosts get a
+/// 404, listing the routes if `list_routes` is set; forwards that refuse the connection get
+/// a 502.
+///
+/// # Errors
+/// Returns an error if reading from or writing to either side fails
+pub async fn serve_connection<S>(
+    mut client: S,
+    routes: &[ProxyRoute],
+    list_routes: bool,
+) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut buffer = Vec::new();
+    let Some(head_len) = read_head(&mut client, &mut buffer).await? else {
+        return respond(&mut client, 400, "Bad Request", "Malformed HTTP request\n").await;
+    };
+
+    let mut parser = HttpParser::new(MessageKind::Request, 0);
+    let host = parser
+        .feed(&buffer[..head_len])
+        .into_iter()
+        .find_map(|event| match event {
+            ParseEvent::Head(head) => head.header("host").map(str::to_string),
+            ParseEvent::Complete { .. } => None,
+        });
+    let Some(host) = host else {
+        return respond(&mut client, 400, "Bad Request", "Missing Host header\n").await;
+    };
+
+    let Some(route) = find_route(routes, &host) else {
+        let mut body = format!("No forward is routed for host '{host}'.\n");
+        // Other machines reaching an exposed proxy don't get to see what else it routes
+        if list_routes {
+            if routes.is_empty() {
+                body.push_str("No forwards are running.\n");
+            } else {
+                body.push_str("Routed hosts:\n");
+                body.extend(routes.iter().map(|route| format!("  {}\n", route.host)));
+            }
+        }
+        return respond(&mut client, 404, "Not Found", &body).await;
+    };
+
+    let mut upstream = match TcpStream::connect(route.target).await {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            let body = format!(
+                "Forward {} at {} is not accepting connections: {e}\n",
+                route.forward_id, route.target
+            );
+            return respond(&mut client, 502, "Bad Gateway", &body).await;
+        }
+    };
+    upstream.write_all(&buffer).await?;
+    io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+/// Read until the end of the request head, returning its length; `None` if the client
+/// closed first or the head is too large
+async fn read_head<S>(client: &mut S, buffer: &mut Vec<u8>) -> io::Result<Option<usize>>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            return Ok(Some(end + 4));
+        }
+        if buffer.len() > MAX_HEAD_SIZE {
+            return Ok(None);
+        }
+        let read = client.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+}
+
+async fn respond<S>(client: &mut S, status: u16, reason: &str, body: &str) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let response = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: text/plain; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    client.write_all(response.as_bytes()).await?;
+    client.shutdown().await
+}
+
+/// Address the proxy connects to for a forward listening on `bind`
+fn target_ip(bind: BindAddress) -> IpAddr {
+    match bind {
+        BindAddress::Localhost => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        BindAddress::Ip(IpAddr::V4(ip)) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        BindAddress::Ip(IpAddr::V6(ip)) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        BindAddress::Ip(ip) => ip,
+    }
+}
+
+/// `<name>.<instance>.localhost`, leaving out parts that have no usable characters
+fn route_host(name: &str, instance_id: &str) -> String {
+    let mut labels: Vec<String> = [dns_label(name), dns_label(instance_id)]
+        .into_iter()
+        .filter(|label| !label.is_empty())
+        .collect();
+    labels.push(PROXY_DOMAIN.to_string());
+    labels.join(".")
+}
+
+/// Lowercase `name` into a DNS label: other characters become `-`, which are then trimmed
+fn dns_label(name: &str) -> String {
+    let label: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let label = label.trim_matches('-');
+    let label = &label[..label.len().min(MAX_LABEL_LEN)];
+    label.trim_end_matches('-').to_string()
+}
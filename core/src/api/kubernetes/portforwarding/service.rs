@@ -0,0 +1,197 @@
+// Service resolution for port forwarding
+//
+// This module resolves `kind: service` port forwards to a ready backing pod and the
+// container port behind the service's `targetPort`.
+
+use crate::api::kubernetes::portforwarding::pods::{is_pod_ready, label_selector};
+use crate::errors::CoreError;
+use k8s_openapi::api::core::v1::{Pod, Service, ServicePort};
+use k8s_openapi::api::discovery::v1::EndpointSlice;
+use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+use kube::api::{Api, ListParams};
+use kube::Client;
+use roro_domain::PortValue;
+
+/// A service port forward resolved to a concrete pod and container port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedTarget {
+    pub pod: String,
+    pub remote_port: u16,
+    /// Port the service itself exposes, which `remote_port` is mapped from
+    pub service_port: u16,
+}
+
+/// Resolve a service and service port to a ready backing pod and container port
+///
+/// Backing pods are taken from the service's `EndpointSlices` when available, falling back
+/// to listing pods through the service selector.
+///
+/// # Errors
+/// Returns an error if the service or port does not exist, no ready pod backs the service,
+/// or the `targetPort` cannot be mapped to a container port
+pub async fn resolve_service_target(
+    client: &Client,
+    namespace: &str,
+    service_name: &str,
+    port: &PortValue,
+) -> Result<ResolvedTarget, CoreError> {
+    let services: Api<Service> = Api::namespaced(client.clone(), namespace);
+    let service = services.get(service_name).await.map_err(|e| {
+        CoreError::PortForwarding(format!(
+            "Service {service_name} not found in namespace {namespace}: {e}"
+        ))
+    })?;
+
+    let service_port = find_service_port(&service, port)?;
+    let pod = find_ready_backing_pod(client, namespace, &service).await?;
+    let remote_port = target_container_port(service_port, &pod)?;
+
+    let pod_name = pod.metadata.name.ok_or_else(|| {
+        CoreError::PortForwarding(format!("Pod backing service {service_name} has no name"))
+    })?;
+
+    Ok(ResolvedTarget {
+        pod: pod_name,
+        remote_port,
+        service_port: u16::try_from(service_port.port).map_err(|_| {
+            CoreError::PortForwarding(format!(
+                "Service {service_name} port {} is out of range",
+                service_port.port
+            ))
+        })?,
+    })
+}
+
+async fn find_ready_backing_pod(
+    client: &Client,
+    namespace: &str,
+    service: &Service,
+) -> Result<Pod, CoreError> {
+    let service_name = service.metadata.name.clone().unwrap_or_default();
+    let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+
+    // EndpointSlices reflect the endpoints controller's view of readiness, so prefer them
+    let slices: Api<EndpointSlice> = Api::namespaced(client.clone(), namespace);
+    let slice_params =
+        ListParams::default().labels(&format!("kubernetes.io/service-name={service_name}"));
+    if let Ok(slice_list) = slices.list(&slice_params).await {
+        for pod_name in ready_endpoint_pods(&slice_list.items) {
+            if let Ok(pod) = pods.get(&pod_name).await {
+                if is_pod_ready(&pod) {
+                    return Ok(pod);
+                }
+            }
+        }
+    }
+
+    // Fall back to the selector, e.g. when EndpointSlices cannot be listed
+    let selector = service
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.selector.as_ref())
+        .filter(|selector| !selector.is_empty())
+        .ok_or_else(|| {
+            CoreError::PortForwarding(format!("Service {service_name} has no pod selector"))
+        })?;
+
+    let pod_list = pods
+        .list(&ListParams::default().labels(&label_selector(selector)))
+        .await
+        .map_err(|e| {
+            CoreError::PortForwarding(format!(
+                "Failed to list pods for service {service_name} in namespace {namespace}: {e}"
+            ))
+        })?;
+
+    pod_list
+        .items
+        .into_iter()
+        .find(is_pod_ready)
+        .ok_or_else(|| {
+            CoreError::PortForwarding(format!(
+                "No ready pods back service {service_name} in namespace {namespace}"
+            ))
+        })
+}
+
+/// Names of pods referenced by ready endpoints, in slice order
+#[must_use]
+pub fn ready_endpoint_pods(slices: &[EndpointSlice]) -> Vec<String> {
+    slices
+        .iter()
+        .flat_map(|slice| slice.endpoints.iter())
+        .filter(|endpoint| {
+            endpoint
+                .conditions
+                .as_ref()
+                .is_none_or(|c| c.ready.unwrap_or(true))
+        })
+        .filter_map(|endpoint| endpoint.target_ref.as_ref())
+        .filter(|target| target.kind.as_deref() == Some("Pod"))
+        .filter_map(|target| target.name.clone())
+        .collect()
+}
+
+/// Find the service port matching a numeric port or a port name
+///
+/// # Errors
+/// Returns an error if the service exposes no matching port
+pub fn find_service_port<'a>(
+    service: &'a Service,
+    port: &PortValue,
+) -> Result<&'a ServicePort, CoreError> {
+    let service_name = service.metadata.name.as_deref().unwrap_or_default();
+    let ports = service
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.ports.as_ref())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    ports
+        .iter()
+        .find(|p| match port {
+            PortValue::Numeric(number) => p.port == i32::from(*number),
+            PortValue::Named(name) => p.name.as_deref() == Some(name.as_str()),
+        })
+        .ok_or_else(|| {
+            let port_display = match port {
+                PortValue::Numeric(number) => number.to_string(),
+                PortValue::Named(name) => name.clone(),
+            };
+            CoreError::PortForwarding(format!("Service {service_name} has no port {port_display}"))
+        })
+}
+
+/// Map a service port through its `targetPort` to a container port on the given pod
+///
+/// A missing `targetPort` defaults to the service port; a named `targetPort` is looked up
+/// among the pod's container ports.
+///
+/// # Errors
+/// Returns an error if a named target port is not declared by any container, or the
+/// resulting port number is out of range
+pub fn target_container_port(service_port: &ServicePort, pod: &Pod) -> Result<u16, CoreError> {
+    let port = match &service_port.target_port {
+        None => service_port.port,
+        Some(IntOrString::Int(number)) => *number,
+        Some(IntOrString::String(name)) => pod
+            .spec
+            .iter()
+            .flat_map(|spec| spec.containers.iter())
+            .flat_map(|container| container.ports.iter().flatten())
+            .find(|p| p.name.as_deref() == Some(name.as_str()))
+            .map(|p| p.container_port)
+            .ok_or_else(|| {
+                CoreError::PortForwarding(format!(
+                    "Target port {name} is not declared by pod {}",
+                    pod.metadata.name.as_deref().unwrap_or_default()
+                ))
+            })?,
+    };
+
+    u16::try_from(port)
+        .ok()
+        .filter(|p| *p > 0)
+        .ok_or_else(|| CoreError::PortForwarding(format!("Invalid target port {port}")))
+}
@@ -0,0 +1,364 @@
+// Service proxy transport
+//
+// This module carries a service forward's HTTP traffic through the API server's
+// `services/proxy` subresource instead of a port-forward tunnel, for clusters that deny
+// `pods/portforward` but allow `services/proxy`. Each request read from a local connection
+// is sent to `/api/v1/namespaces/<namespace>/services/<service>:<port>/proxy/<path>` and
+// its response streamed back, so only plain HTTP/1.1 without protocol upgrades gets
+// through; TLS termination on the local listener still works in front of it.
+
+use crate::api::kubernetes::portforwarding::http::{HttpHead, HttpParser, MessageKind, ParseEvent};
+use crate::api::kubernetes::portforwarding::metrics::{Direction, ForwardMetrics};
+use crate::errors::CoreError;
+use http::{Request, Response};
+use http_body_util::BodyExt;
+use kube::client::Body;
+use kube::Client;
+use roro_domain::PortValue;
+use std::fmt::Write as _;
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Largest request body relayed; larger requests get a 413
+pub const MAX_PROXY_REQUEST_BODY: usize = 16 * 1024 * 1024;
+
+/// Request and response headers that describe one connection rather than the message, plus
+/// the credentials the API server would take as its own
+const UNRELAYED_HEADERS: [&str; 11] = [
+    "authorization",
+    "connection",
+    "content-length",
+    "expect",
+    "host",
+    "keep-alive",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// API server path proxying `target` to port `port` of a service
+///
+/// `target` is the request target read from the client, either a path or an absolute URL
+/// whose path is kept.
+///
+/// # Errors
+/// Returns `CoreError::Validation` if the path has a `.` or `..` segment, written out or
+/// percent-encoded, since it could climb out of the service's proxy path to other API
+/// server resources
+pub fn service_proxy_path(
+    namespace: &str,
+    service: &str,
+    port: &PortValue,
+    target: &str,
+) -> Result<String, CoreError> {
+    let port = match port {
+        PortValue::Numeric(number) => number.to_string(),
+        PortValue::Named(name) => name.clone(),
+    };
+    let path = match target.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |start| &rest[start..]),
+        None if target.starts_with('/') => target,
+        None => "/",
+    };
+    if has_dot_segment(path) {
+        return Err(CoreError::Validation(format!(
+            "Request path '{path}' has a '.' or '..' segment"
+        )));
+    }
+    Ok(format!(
+        "/api/v1/namespaces/{namespace}/services/{service}:{port}/proxy{path}"
+    ))
+}
+
+/// Whether the path part of `target` has a `.` or `..` segment once percent-encoded dots
+/// and slashes are decoded
+fn has_dot_segment(target: &str) -> bool {
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    let decoded = path
+        .to_ascii_lowercase()
+        .replace("%2e", ".")
+        .replace("%2f", "/")
+        .replace("%5c", "\\");
+    decoded
+        .split(['/', '\\'])
+        .any(|segment| segment == "." || segment == "..")
+}
+
+/// Whether a header is passed between the client and the API server
+#[must_use]
+pub fn is_relayed_header(name: &str) -> bool {
+    !UNRELAYED_HEADERS
+        .iter()
+        .any(|unrelayed| name.eq_ignore_ascii_case(unrelayed))
+}
+
+/// Relays HTTP requests for one service port through the API server
+#[derive(Clone)]
+pub struct ServiceProxy {
+    client: Client,
+    namespace: String,
+    service: String,
+    port: PortValue,
+}
+
+impl ServiceProxy {
+    #[must_use]
+    pub fn new(client: Client, namespace: &str, service: &str, port: &PortValue) -> Self {
+        Self {
+            client,
+            namespace: namespace.to_string(),
+            service: service.to_string(),
+            port: port.clone(),
+        }
+    }
+
+    /// Status the service returns for a `GET` of `path`
+    ///
+    /// # Errors
+    /// Returns an error if `path` has a dot segment or the API server can't be reached
+    pub async fn probe(&self, path: &str) -> Result<u16, CoreError> {
+        let path = service_proxy_path(&self.namespace, &self.service, &self.port, path)?;
+        let response = self.send("GET", &path, &[], Vec::new()).await?;
+        Ok(response.status().as_u16())
+    }
+
+    /// Serve HTTP requests from `local` until the client closes the connection
+    ///
+    /// Bytes read and written are counted in `metrics` and passed to `observe`, so capture
+    /// sees the same HTTP/1.1 exchanges as over a tunnel.
+    ///
+    /// # Errors
+    /// Returns an error if reading from or writing to the client fails
+    pub async fn serve<S, O>(
+        &self,
+        mut local: S,
+        metrics: &ForwardMetrics,
+        observe: &O,
+    ) -> io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+        O: Fn(Direction, &[u8]),
+    {
+        let mut parser = HttpParser::new(MessageKind::Request, MAX_PROXY_REQUEST_BODY);
+        let mut head = None;
+        let mut chunk = vec![0u8; 16 * 1024];
+        loop {
+            let read = local.read(&mut chunk).await?;
+            if read == 0 {
+                return Ok(());
+            }
+            metrics.record_bytes(Direction::Sent, u64::try_from(read).unwrap_or(u64::MAX));
+            observe(Direction::Sent, &chunk[..read]);
+
+            for event in parser.feed(&chunk[..read]) {
+                match event {
+                    ParseEvent::Head(request) => {
+                        if request
+                            .header("expect")
+                            .is_some_and(|expect| expect.eq_ignore_ascii_case("100-continue"))
+                        {
+                            local.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").await?;
+                        }
+                        head = Some(request);
+                    }
+                    ParseEvent::Complete { body, size } => {
+                        let Some(request) = head.take() else {
+                            continue;
+                        };
+                        if u64::try_from(body.len()) != Ok(size) {
+                            let message = format!(
+                                "Request bodies through the service proxy are limited to {MAX_PROXY_REQUEST_BODY} bytes\n"
+                            );
+                            return respond(&mut local, 413, "Payload Too Large", &message).await;
+                        }
+                        if !self
+                            .relay(&mut local, &request, body, metrics, observe)
+                            .await?
+                        {
+                            return local.shutdown().await;
+                        }
+                    }
+                }
+            }
+            if parser.is_failed() {
+                return respond(
+                    &mut local,
+                    400,
+                    "Bad Request",
+                    "Malformed HTTP/1.1 request\n",
+                )
+                .await;
+            }
+        }
+    }
+
+    /// Send one request and write its response to `local`; returns whether the connection
+    /// stays open for another request
+    async fn relay<S, O>(
+        &self,
+        local: &mut S,
+        request: &HttpHead,
+        body: Vec<u8>,
+        metrics: &ForwardMetrics,
+        observe: &O,
+    ) -> io::Result<bool>
+    where
+        S: AsyncWrite + Unpin,
+        O: Fn(Direction, &[u8]),
+    {
+        let Some((method, target, version)) = request.request_line() else {
+            respond(local, 400, "Bad Request", "Malformed HTTP/1.1 request\n").await?;
+            return Ok(false);
+        };
+        if request.header("upgrade").is_some() {
+            let message = "Protocol upgrades can't go through the service proxy\n";
+            respond(local, 501, "Not Implemented", message).await?;
+            return Ok(false);
+        }
+
+        let path = match service_proxy_path(&self.namespace, &self.service, &self.port, target) {
+            Ok(path) => path,
+            Err(e) => {
+                respond(local, 400, "Bad Request", &format!("{e}\n")).await?;
+                return Ok(false);
+            }
+        };
+        let response = match self.send(method, &path, &request.headers, body).await {
+            Ok(response) => response,
+            Err(e) => {
+                let message = format!("Service proxy request failed: {e}\n");
+                respond(local, 502, "Bad Gateway", &message).await?;
+                return Ok(false);
+            }
+        };
+
+        let keep_alive = version == "HTTP/1.1"
+            && !request
+                .header("connection")
+                .is_some_and(|connection| connection.eq_ignore_ascii_case("close"));
+        let status = response.status();
+        let bodyless = method.eq_ignore_ascii_case("HEAD")
+            || status.is_informational()
+            || status == http::StatusCode::NO_CONTENT
+            || status == http::StatusCode::NOT_MODIFIED;
+        // HTTP/1.0 clients read the body until the connection closes
+        let chunked = !bodyless && version == "HTTP/1.1";
+        let reusable = keep_alive && (chunked || bodyless);
+
+        let head = response_head(&response, bodyless, chunked, reusable);
+        let write = |data: Vec<u8>| {
+            metrics.record_bytes(
+                Direction::Received,
+                u64::try_from(data.len()).unwrap_or(u64::MAX),
+            );
+            observe(Direction::Received, &data);
+            data
+        };
+        local.write_all(&write(head.into_bytes())).await?;
+        if !bodyless {
+            stream_body(local, response.into_body(), chunked, write).await?;
+        }
+        local.flush().await?;
+
+        Ok(reusable)
+    }
+
+    /// Send a request for an API server `path` from `service_proxy_path`
+    async fn send(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(String, String)],
+        body: Vec<u8>,
+    ) -> Result<Response<Body>, CoreError> {
+        let request = headers
+            .iter()
+            .filter(|(name, _)| is_relayed_header(name))
+            .fold(
+                Request::builder().method(method).uri(path),
+                |builder, (name, value)| builder.header(name, value),
+            )
+            .body(Body::from(body))
+            .map_err(|e| CoreError::PortForwarding(format!("Invalid request for {path}: {e}")))?;
+
+        self.client.send(request).await.map_err(|e| {
+            CoreError::Kubernetes(format!(
+                "Service proxy to {}/{} failed: {e}",
+                self.namespace, self.service
+            ))
+        })
+    }
+}
+
+/// Status line and headers of a response relayed to the client
+fn response_head(
+    response: &Response<Body>,
+    bodyless: bool,
+    chunked: bool,
+    reusable: bool,
+) -> String {
+    let status = response.status();
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or_default()
+    );
+    for (name, value) in response.headers() {
+        let relayed =
+            is_relayed_header(name.as_str()) || (bodyless && name == http::header::CONTENT_LENGTH);
+        if let (true, Ok(value)) = (relayed, value.to_str()) {
+            let _ = write!(head, "{name}: {value}\r\n");
+        }
+    }
+    if chunked {
+        head.push_str("Transfer-Encoding: chunked\r\n");
+    }
+    if !reusable {
+        head.push_str("Connection: close\r\n");
+    }
+    head.push_str("\r\n");
+    head
+}
+
+/// Copy a response body to the client, chunk-encoded or as is
+async fn stream_body<S, W>(local: &mut S, mut body: Body, chunked: bool, write: W) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+    W: Fn(Vec<u8>) -> Vec<u8>,
+{
+    while let Some(frame) = body.frame().await {
+        let Ok(data) = frame.map_err(io::Error::other)?.into_data() else {
+            continue;
+        };
+        if data.is_empty() {
+            continue;
+        }
+        let framed = if chunked {
+            let mut framed = format!("{:x}\r\n", data.len()).into_bytes();
+            framed.extend_from_slice(&data);
+            framed.extend_from_slice(b"\r\n");
+            framed
+        } else {
+            data.to_vec()
+        };
+        local.write_all(&write(framed)).await?;
+    }
+    if chunked {
+        local.write_all(&write(b"0\r\n\r\n".to_vec())).await?;
+    }
+    Ok(())
+}
+
+async fn respond<S>(local: &mut S, status: u16, reason: &str, body: &str) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let response = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: text/plain; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    local.write_all(response.as_bytes()).await?;
+    local.shutdown().await
+}
@@ -0,0 +1,245 @@
+// Port forwarding sessions
+//
+// This module opens the tunnels a forward's connections run over. The port-forward websocket
+// protocol carries a single TCP stream per requested port and cannot close one stream without
+// closing the socket, so connections cannot share a websocket and each connection gets a
+// session of its own; sessions are not reused or multiplexed. Sessions are opened on demand
+// only, since the pod side connects to the remote port as soon as one opens, and a forward
+// limits how many it opens at once. What a session adds over a plain tunnel is a clean
+// half-close, delivering the pod's remaining data after the client finishes sending, and
+// websocket pings so idle connections aren't cut by the API server or load balancers in
+// front of it.
+
+use crate::errors::CoreError;
+use futures::stream::SplitSink;
+use futures::{SinkExt, StreamExt};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::Api;
+use kube::client::UpgradeConnectionError;
+use kube::core::Request;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, WriteHalf};
+use tokio::sync::Semaphore;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::WebSocketStream;
+
+/// How often an open session pings the API server
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Sessions a single forward may be opening at the same time
+pub const MAX_CONCURRENT_OPENS: usize = 8;
+
+/// Buffer between a connection and its session's websocket
+const TUNNEL_BUFFER: usize = 1024 * 1024;
+
+/// Channel carrying the forwarded port's data; the next one carries its errors
+const DATA_CHANNEL: u8 = 0;
+const ERROR_CHANNEL: u8 = 1;
+
+/// A bidirectional stream to the remote port
+pub trait TunnelStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> TunnelStream for T {}
+
+/// Opens port-forward sessions for one forward
+pub struct ForwardSessions {
+    pods: Api<Pod>,
+    remote_port: u16,
+    opens: Semaphore,
+}
+
+impl ForwardSessions {
+    #[must_use]
+    pub fn new(pods: Api<Pod>, remote_port: u16) -> Arc<Self> {
+        Arc::new(Self {
+            pods,
+            remote_port,
+            opens: Semaphore::new(MAX_CONCURRENT_OPENS),
+        })
+    }
+
+    /// Open a session to the remote port on `pod` and get its stream
+    ///
+    /// The session closes once the stream is dropped and the pod's side has finished.
+    ///
+    /// # Errors
+    /// Returns `CoreError::PortForwardForbidden` if the API server denies port-forwarding,
+    /// or `CoreError::PortForwarding` if the session cannot be opened otherwise
+    pub async fn connect(&self, pod: &str) -> Result<Box<dyn TunnelStream>, CoreError> {
+        let _permit = self.opens.acquire().await.map_err(|e| {
+            CoreError::PortForwarding(format!("Port forward session limiter closed: {e}"))
+        })?;
+        let request = Request::new(self.pods.resource_url())
+            .portforward(pod, &[self.remote_port])
+            .map_err(|e| CoreError::PortForwarding(format!("Invalid port forward request: {e}")))?;
+        let connection = self
+            .pods
+            .clone()
+            .into_client()
+            .connect(request)
+            .await
+            .map_err(|e| {
+                let message = format!(
+                    "Failed to create portforwarder for remote port {}: {e}",
+                    self.remote_port
+                );
+                if is_forbidden(&e) {
+                    CoreError::PortForwardForbidden(message)
+                } else {
+                    CoreError::PortForwarding(message)
+                }
+            })?;
+
+        Ok(Box::new(spawn_session(
+            connection.into_stream(),
+            pod,
+            KEEPALIVE_INTERVAL,
+        )))
+    }
+}
+
+/// Run the port-forward protocol for one port over `socket` in the background
+///
+/// Data written to the returned stream goes to the pod and the pod's data can be read from
+/// it. The websocket is pinged every `keepalive`; when the stream is closed the socket is
+/// closed too, and the pod's remaining data is still delivered until the API server closes
+/// its end. An error the pod reports for the port ends the session.
+pub fn spawn_session<S>(
+    socket: WebSocketStream<S>,
+    pod: &str,
+    keepalive: Duration,
+) -> tokio::io::DuplexStream
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (stream, tunnel) = tokio::io::duplex(TUNNEL_BUFFER);
+    tokio::spawn(run_session(socket, tunnel, pod.to_string(), keepalive));
+    stream
+}
+
+async fn run_session<S>(
+    socket: WebSocketStream<S>,
+    tunnel: tokio::io::DuplexStream,
+    pod: String,
+    keepalive: Duration,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (sink, mut source) = socket.split();
+    let (mut local_read, local_write) = tokio::io::split(tunnel);
+    let mut session = Session {
+        sink,
+        local_write,
+        pod,
+        announced: [false; 2],
+    };
+    let mut ping = tokio::time::interval_at(tokio::time::Instant::now() + keepalive, keepalive);
+    let mut local_open = true;
+    let mut buffer = vec![0u8; 32 * 1024];
+
+    loop {
+        let running = tokio::select! {
+            read = local_read.read(&mut buffer[1..]), if local_open => {
+                // A failed read ends the connection like a close does
+                let read = read.unwrap_or(0);
+                local_open = read > 0;
+                session.send(to_pod_message(read, &mut buffer)).await
+            }
+            message = source.next() => session.receive(message).await,
+            _ = ping.tick() => session.send(Message::Ping(Vec::new().into())).await,
+        };
+        if !running {
+            break;
+        }
+    }
+
+    let _ = session.local_write.shutdown().await;
+    if local_open {
+        let _ = session.sink.send(Message::Close(None)).await;
+    }
+}
+
+/// The message sent for `read` bytes read from the connection into `buffer[1..]`
+///
+/// The protocol can't close just the stream, so the end of the connection closes the socket;
+/// what the pod still sends is read until the API server closes its end.
+fn to_pod_message(read: usize, buffer: &mut [u8]) -> Message {
+    if read == 0 {
+        return Message::Close(None);
+    }
+    buffer[0] = DATA_CHANNEL;
+    Message::binary(buffer[..=read].to_vec())
+}
+
+/// The halves of an open session that carry data between the websocket and the connection
+struct Session<S> {
+    sink: SplitSink<WebSocketStream<S>, Message>,
+    local_write: WriteHalf<tokio::io::DuplexStream>,
+    pod: String,
+    /// The first frame on each channel only names the port
+    announced: [bool; 2],
+}
+
+impl<S> Session<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Send a message to the API server; returns whether the session is still open
+    async fn send(&mut self, message: Message) -> bool {
+        self.sink.send(message).await.is_ok()
+    }
+
+    /// Handle a message from the API server; returns whether the session is still open
+    async fn receive(&mut self, message: Option<Result<Message, WsError>>) -> bool {
+        let frame = match message {
+            Some(Ok(Message::Binary(frame))) => frame,
+            Some(Ok(Message::Close(_)) | Err(_)) | None => return false,
+            Some(Ok(_)) => return true,
+        };
+        match pod_frame(&frame, &mut self.announced) {
+            PodFrame::Data(data) => self.local_write.write_all(data).await.is_ok(),
+            PodFrame::Error(error) => {
+                eprintln!("[PortForward] Pod {} reported: {error}", self.pod);
+                false
+            }
+            PodFrame::Ignored => true,
+        }
+    }
+}
+
+/// A binary frame received from the pod's side
+enum PodFrame<'a> {
+    Data(&'a [u8]),
+    Error(String),
+    /// The port announcement each channel starts with, or a frame for another channel
+    Ignored,
+}
+
+fn pod_frame<'a>(frame: &'a [u8], announced: &mut [bool; 2]) -> PodFrame<'a> {
+    let Some((&channel, data)) = frame.split_first() else {
+        return PodFrame::Ignored;
+    };
+    let Some(announced) = announced.get_mut(usize::from(channel)) else {
+        return PodFrame::Ignored;
+    };
+    if !*announced {
+        *announced = true;
+        return PodFrame::Ignored;
+    }
+    if channel == ERROR_CHANNEL {
+        PodFrame::Error(String::from_utf8_lossy(data).into_owned())
+    } else {
+        PodFrame::Data(data)
+    }
+}
+
+/// Whether the API server refused the port-forward upgrade, typically for lack of
+/// `pods/portforward` permission
+fn is_forbidden(error: &kube::Error) -> bool {
+    matches!(
+        error,
+        kube::Error::UpgradeConnection(UpgradeConnectionError::ProtocolSwitch(status))
+            if *status == http::StatusCode::FORBIDDEN
+    )
+}
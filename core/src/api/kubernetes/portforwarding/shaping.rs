@@ -0,0 +1,301 @@
+// Traffic shaping and fault injection
+//
+// This module degrades traffic through a forward for resilience testing: added latency with
+// jitter, a bandwidth cap, random connection resets and a blackhole that swallows all data.
+// The copy loop consults the forward's shaper for every chunk, so changes made while the
+// forward runs apply to open connections straight away. Delayed chunks wait in a queue
+// timed from when each was read, so a stream of chunks pays the latency once rather than
+// once per chunk, the way a slow link behaves.
+
+use crate::api::kubernetes::portforwarding::backoff::random_unit;
+use crate::errors::CoreError;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How traffic through a forward is degraded; the default leaves it untouched
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrafficShaping {
+    /// Delay added to every chunk copied, in each direction
+    pub latency: Duration,
+    /// Up to this much extra delay, drawn at random for each chunk
+    pub jitter: Duration,
+    /// Bytes per second allowed in each direction of a connection; `None` is unlimited
+    pub bandwidth: Option<u64>,
+    /// Chance from 0 to 1 that a chunk resets its connection instead of being copied
+    pub reset_probability: f64,
+    /// Keep connections open but discard everything sent through them
+    pub blackhole: bool,
+}
+
+/// What the copy loop does with the next chunk of a shaped connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkPlan {
+    /// Copy the chunk once it has taken `transfer` at the bandwidth cap, after the chunks
+    /// before it, and then `latency`
+    Copy {
+        latency: Duration,
+        transfer: Duration,
+    },
+    /// Close the connection abruptly
+    Reset,
+    /// Drop the chunk and keep reading
+    Discard,
+}
+
+impl TrafficShaping {
+    #[must_use]
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    #[must_use]
+    pub fn with_bandwidth(mut self, bytes_per_second: Option<u64>) -> Self {
+        self.bandwidth = bytes_per_second;
+        self
+    }
+
+    #[must_use]
+    pub fn with_reset_probability(mut self, probability: f64) -> Self {
+        self.reset_probability = probability;
+        self
+    }
+
+    #[must_use]
+    pub fn with_blackhole(mut self, blackhole: bool) -> Self {
+        self.blackhole = blackhole;
+        self
+    }
+
+    /// Whether any setting changes traffic
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        !self.latency.is_zero()
+            || !self.jitter.is_zero()
+            || self.bandwidth.is_some()
+            || self.reset_probability > 0.0
+            || self.blackhole
+    }
+
+    /// Check the settings are usable
+    ///
+    /// # Errors
+    /// Returns `CoreError::Validation` if the reset probability is outside 0 to 1 or the
+    /// bandwidth is zero
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if !(0.0..=1.0).contains(&self.reset_probability) {
+            return Err(CoreError::Validation(format!(
+                "Reset probability {} must be between 0 and 1",
+                self.reset_probability
+            )));
+        }
+        if self.bandwidth == Some(0) {
+            return Err(CoreError::Validation(
+                "Bandwidth must be at least 1 byte per second".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Delay before copying a chunk of `len` bytes on an idle connection, given a jitter
+    /// sample from 0 to 1
+    ///
+    /// The delay is the latency, the sampled share of the jitter, and the time the chunk
+    /// takes at the bandwidth cap.
+    #[must_use]
+    pub fn chunk_delay(&self, len: usize, jitter_sample: f64) -> Duration {
+        self.chunk_latency(jitter_sample) + self.transfer_time(len)
+    }
+
+    /// The latency plus the sampled share of the jitter, given a sample from 0 to 1
+    #[must_use]
+    pub fn chunk_latency(&self, jitter_sample: f64) -> Duration {
+        self.latency + self.jitter.mul_f64(jitter_sample.clamp(0.0, 1.0))
+    }
+
+    /// Time a chunk of `len` bytes takes at the bandwidth cap; zero without a cap
+    #[must_use]
+    pub fn transfer_time(&self, len: usize) -> Duration {
+        self.bandwidth.map_or(Duration::ZERO, |rate| {
+            let bytes = u64::try_from(len).unwrap_or(u64::MAX);
+            Duration::from_nanos(bytes.saturating_mul(1_000_000_000) / rate.max(1))
+        })
+    }
+
+    /// Decide what happens to a chunk of `len` bytes, given random samples from 0 to 1
+    #[must_use]
+    pub fn plan_chunk(&self, len: usize, reset_sample: f64, jitter_sample: f64) -> ChunkPlan {
+        if self.blackhole {
+            ChunkPlan::Discard
+        } else if reset_sample < self.reset_probability {
+            ChunkPlan::Reset
+        } else {
+            ChunkPlan::Copy {
+                latency: self.chunk_latency(jitter_sample),
+                transfer: self.transfer_time(len),
+            }
+        }
+    }
+}
+
+impl fmt::Display for TrafficShaping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if !self.latency.is_zero() || !self.jitter.is_zero() {
+            parts.push(if self.jitter.is_zero() {
+                format!("latency {}ms", self.latency.as_millis())
+            } else {
+                format!(
+                    "latency {}ms ±{}ms",
+                    self.latency.as_millis(),
+                    self.jitter.as_millis()
+                )
+            });
+        }
+        if let Some(bandwidth) = self.bandwidth {
+            parts.push(format!("{bandwidth} B/s"));
+        }
+        if self.reset_probability > 0.0 {
+            parts.push(format!("{}% resets", self.reset_probability * 100.0));
+        }
+        if self.blackhole {
+            parts.push("blackhole".to_string());
+        }
+
+        if parts.is_empty() {
+            f.write_str("off")
+        } else {
+            f.write_str(&parts.join(", "))
+        }
+    }
+}
+
+/// A forward's shaping settings, shared with its connections and changed while it runs
+#[derive(Debug, Default)]
+pub struct TrafficShaper {
+    shaping: Mutex<Option<TrafficShaping>>,
+}
+
+impl TrafficShaper {
+    #[must_use]
+    pub fn new(shaping: Option<TrafficShaping>) -> Self {
+        Self {
+            shaping: Mutex::new(shaping.filter(TrafficShaping::is_active)),
+        }
+    }
+
+    /// Shape traffic with `shaping`, or stop shaping with `None`
+    pub fn set(&self, shaping: Option<TrafficShaping>) {
+        *self.lock() = shaping.filter(TrafficShaping::is_active);
+    }
+
+    /// Current settings; `None` while traffic flows untouched
+    #[must_use]
+    pub fn shaping(&self) -> Option<TrafficShaping> {
+        self.lock().clone()
+    }
+
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    /// Decide what happens to the next chunk of `len` bytes; `None` copies it untouched
+    #[must_use]
+    pub fn plan_chunk(&self, len: usize) -> Option<ChunkPlan> {
+        self.lock()
+            .as_ref()
+            .map(|shaping| shaping.plan_chunk(len, random_unit(), random_unit()))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<TrafficShaping>> {
+        // A panic while holding the lock can't leave the settings inconsistent
+        self.shaping
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Bytes a delay queue holds before the copy loop stops reading, like a link's buffer
+const MAX_QUEUED_BYTES: usize = 1024 * 1024;
+
+/// Chunks read from one direction of a shaped connection, held until they are due
+///
+/// Each chunk is due its latency after it has gone out at the bandwidth cap, counted from
+/// when it was read or the previous chunk went out, whichever is later. Chunks never
+/// overtake each other, whatever jitter they drew.
+#[derive(Debug)]
+pub(crate) struct DelayQueue {
+    chunks: VecDeque<(Instant, Vec<u8>)>,
+    queued_bytes: usize,
+    /// When the chunks queued so far have all gone out at the bandwidth cap
+    link_free: Instant,
+}
+
+impl DelayQueue {
+    pub(crate) fn new() -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            queued_bytes: 0,
+            link_free: Instant::now(),
+        }
+    }
+
+    /// Queue a chunk read just now
+    pub(crate) fn push(&mut self, data: Vec<u8>, latency: Duration, transfer: Duration) {
+        self.link_free = self.link_free.max(Instant::now()) + transfer;
+        let mut due = self.link_free + latency;
+        if let Some((last_due, _)) = self.chunks.back() {
+            due = due.max(*last_due);
+        }
+        self.queued_bytes += data.len();
+        self.chunks.push_back((due, data));
+    }
+
+    /// When the next chunk is due, or `None` if none is queued
+    pub(crate) fn next_due(&self) -> Option<Instant> {
+        self.chunks.front().map(|(due, _)| *due)
+    }
+
+    /// Take the next chunk, whether or not it is due yet
+    pub(crate) fn pop(&mut self) -> Option<Vec<u8>> {
+        let (_, data) = self.chunks.pop_front()?;
+        self.queued_bytes -= data.len();
+        Some(data)
+    }
+
+    /// Whether the queue holds as much as it may, so reading should wait
+    pub(crate) fn is_full(&self) -> bool {
+        self.queued_bytes >= MAX_QUEUED_BYTES
+    }
+}
+
+/// Error a connection fails with when fault injection resets it
+#[derive(Debug)]
+struct InjectedReset;
+
+impl fmt::Display for InjectedReset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("connection reset by fault injection")
+    }
+}
+
+impl std::error::Error for InjectedReset {}
+
+pub(crate) fn injected_reset() -> io::Error {
+    io::Error::new(io::ErrorKind::ConnectionReset, InjectedReset)
+}
+
+pub(crate) fn is_injected_reset(error: &io::Error) -> bool {
+    matches!(error.get_ref(), Some(inner) if inner.is::<InjectedReset>())
+}
@@ -0,0 +1,329 @@
+// SOCKS5 proxy to cluster services
+//
+// This module implements a local SOCKS5 endpoint that accepts CONNECT requests for in-cluster
+// service names such as `api.dev.svc.cluster.local:8080` or `api.dev:8080`, resolves them
+// through the Kubernetes API to a ready backing pod and container port, and tunnels the
+// connection over a port-forward session opened on demand. Only domain-name destinations
+// are supported, so clients must resolve names through the proxy (`curl --socks5-hostname`).
+
+use crate::api::kubernetes::portforwarding::bind::{accept_any, bind_listeners, BindAddress};
+use crate::api::kubernetes::portforwarding::service::resolve_service_target;
+use crate::api::kubernetes::portforwarding::session::{ForwardSessions, TunnelStream};
+use crate::errors::CoreError;
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::Api;
+use kube::Client;
+use roro_domain::PortValue;
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+/// Port the SOCKS proxy listens on unless configured otherwise
+pub const DEFAULT_SOCKS_PORT: u16 = 1080;
+
+/// Suffixes of fully qualified and partially qualified service names
+const SERVICE_SUFFIXES: [&str; 2] = [".svc.cluster.local", ".svc"];
+
+const SOCKS_VERSION: u8 = 0x05;
+const METHOD_NO_AUTH: u8 = 0x00;
+const METHOD_NONE_ACCEPTABLE: u8 = 0xff;
+const COMMAND_CONNECT: u8 = 0x01;
+const ADDRESS_IPV4: u8 = 0x01;
+const ADDRESS_DOMAIN: u8 = 0x03;
+const ADDRESS_IPV6: u8 = 0x04;
+
+/// Reply codes from RFC 1928
+const REPLY_SUCCEEDED: u8 = 0x00;
+const REPLY_HOST_UNREACHABLE: u8 = 0x04;
+const REPLY_COMMAND_NOT_SUPPORTED: u8 = 0x07;
+const REPLY_ADDRESS_NOT_SUPPORTED: u8 = 0x08;
+
+/// Where the SOCKS proxy listens and how it completes short names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocksConfig {
+    pub bind_address: BindAddress,
+    pub port: u16,
+    /// Namespace of single-label names such as `api`
+    pub default_namespace: String,
+    /// Opt-in required to listen anywhere other than loopback, like a forward's
+    /// `exposeToNetwork`; the proxy has no authentication
+    pub expose_to_network: bool,
+}
+
+impl Default for SocksConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: BindAddress::Localhost,
+            port: DEFAULT_SOCKS_PORT,
+            default_namespace: "default".to_string(),
+            expose_to_network: false,
+        }
+    }
+}
+
+impl SocksConfig {
+    /// Check the proxy may listen where it is configured to
+    ///
+    /// # Errors
+    /// Returns `CoreError::Validation` if the bind address is reachable from other machines
+    /// and `expose_to_network` is not set
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if self.bind_address.is_exposed() && !self.expose_to_network {
+            return Err(CoreError::Validation(format!(
+                "SOCKS proxy bind address '{}' is reachable from other machines and requires exposeToNetwork",
+                self.bind_address
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A service named by a SOCKS destination
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClusterService {
+    pub name: String,
+    pub namespace: String,
+}
+
+/// Parse an in-cluster service host name
+///
+/// Accepts `<service>.<namespace>.svc.cluster.local`, `<service>.<namespace>.svc`,
+/// `<service>.<namespace>` and `<service>`, the last in `default_namespace`. Returns `None`
+/// for names outside the cluster.
+#[must_use]
+pub fn parse_cluster_host(host: &str, default_namespace: &str) -> Option<ClusterService> {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let name = SERVICE_SUFFIXES
+        .iter()
+        .find_map(|suffix| host.strip_suffix(suffix))
+        .unwrap_or(&host);
+
+    let labels: Vec<&str> = name.split('.').collect();
+    let (service, namespace) = match labels.as_slice() {
+        [service] => (*service, default_namespace),
+        [service, namespace] => (*service, *namespace),
+        _ => return None,
+    };
+    let is_label = |label: &str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    };
+    (is_label(service) && is_label(namespace)).then(|| ClusterService {
+        name: service.to_string(),
+        namespace: namespace.to_string(),
+    })
+}
+
+/// Serve one SOCKS5 client: negotiate, resolve its destination, and relay it through a tunnel
+/// opened by `connect`
+///
+/// Only the no-authentication method and the CONNECT command are supported. Destinations
+/// that are not cluster service names, and services `connect` cannot reach, are refused with
+/// the matching SOCKS reply.
+///
+/// # Errors
+/// Returns an error if the client speaks something other than SOCKS5 or the relay fails
+pub async fn serve_socks_connection<S, C, F>(
+    mut client: S,
+    default_namespace: &str,
+    connect: C,
+) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    C: FnOnce(ClusterService, u16) -> F,
+    F: Future<Output = Result<Box<dyn TunnelStream>, CoreError>>,
+{
+    negotiate(&mut client).await?;
+
+    let mut request = [0u8; 4];
+    client.read_exact(&mut request).await?;
+    let [version, command, _, address_type] = request;
+    if version != SOCKS_VERSION {
+        return Err(invalid_data("unexpected SOCKS version in request"));
+    }
+
+    let host = match address_type {
+        ADDRESS_DOMAIN => {
+            let len = client.read_u8().await?;
+            let mut name = vec![0u8; usize::from(len)];
+            client.read_exact(&mut name).await?;
+            String::from_utf8(name).ok()
+        }
+        ADDRESS_IPV4 | ADDRESS_IPV6 => {
+            let len = if address_type == ADDRESS_IPV4 { 4 } else { 16 };
+            let mut address = vec![0u8; len];
+            client.read_exact(&mut address).await?;
+            None
+        }
+        _ => return reply(&mut client, REPLY_ADDRESS_NOT_SUPPORTED).await,
+    };
+    let port = client.read_u16().await?;
+
+    if command != COMMAND_CONNECT {
+        return reply(&mut client, REPLY_COMMAND_NOT_SUPPORTED).await;
+    }
+    let Some(host) = host else {
+        // IP destinations mean the client resolved the name itself, which can't work for
+        // cluster names
+        return reply(&mut client, REPLY_ADDRESS_NOT_SUPPORTED).await;
+    };
+    let Some(service) = parse_cluster_host(&host, default_namespace) else {
+        eprintln!("[PortForward] SOCKS destination {host} is not a cluster service name");
+        return reply(&mut client, REPLY_HOST_UNREACHABLE).await;
+    };
+
+    let mut tunnel = match connect(service, port).await {
+        Ok(tunnel) => tunnel,
+        Err(e) => {
+            eprintln!("[PortForward] SOCKS connection to {host}:{port} failed: {e}");
+            return reply(&mut client, REPLY_HOST_UNREACHABLE).await;
+        }
+    };
+    reply(&mut client, REPLY_SUCCEEDED).await?;
+    io::copy_bidirectional(&mut client, &mut tunnel).await?;
+    Ok(())
+}
+
+/// Read the client's greeting and select the no-authentication method
+async fn negotiate<S>(client: &mut S) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut greeting = [0u8; 2];
+    client.read_exact(&mut greeting).await?;
+    let [version, method_count] = greeting;
+    if version != SOCKS_VERSION {
+        return Err(invalid_data("client is not speaking SOCKS5"));
+    }
+    let mut methods = vec![0u8; usize::from(method_count)];
+    client.read_exact(&mut methods).await?;
+
+    if !methods.contains(&METHOD_NO_AUTH) {
+        client
+            .write_all(&[SOCKS_VERSION, METHOD_NONE_ACCEPTABLE])
+            .await?;
+        return Err(invalid_data("client requires authentication"));
+    }
+    client.write_all(&[SOCKS_VERSION, METHOD_NO_AUTH]).await
+}
+
+/// Send a reply with an unspecified bound address
+async fn reply<S>(client: &mut S, code: u8) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    client
+        .write_all(&[SOCKS_VERSION, code, 0x00, ADDRESS_IPV4, 0, 0, 0, 0, 0, 0])
+        .await?;
+    if code != REPLY_SUCCEEDED {
+        client.shutdown().await?;
+    }
+    Ok(())
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Opens tunnels to cluster services, reusing sessions per namespace and container port
+struct ServiceTunnels {
+    client: Client,
+    sessions: Mutex<HashMap<(String, u16), Arc<ForwardSessions>>>,
+}
+
+impl ServiceTunnels {
+    async fn connect(
+        &self,
+        service: &ClusterService,
+        port: u16,
+    ) -> Result<Box<dyn TunnelStream>, CoreError> {
+        // Resolve per connection so a restarted pod is picked up without a restart
+        let resolved = resolve_service_target(
+            &self.client,
+            &service.namespace,
+            &service.name,
+            &PortValue::Numeric(port),
+        )
+        .await?;
+        let sessions = Arc::clone(
+            self.sessions
+                .lock()
+                .await
+                .entry((service.namespace.clone(), resolved.remote_port))
+                .or_insert_with(|| {
+                    let pods: Api<Pod> = Api::namespaced(self.client.clone(), &service.namespace);
+                    ForwardSessions::new(pods, resolved.remote_port)
+                }),
+        );
+        sessions.connect(&resolved.pod).await
+    }
+}
+
+/// A SOCKS proxy serving in the background
+pub(crate) struct RunningSocksProxy {
+    pub(crate) addresses: Vec<SocketAddr>,
+    pub(crate) task: JoinHandle<()>,
+}
+
+/// Bind the SOCKS proxy and serve clients in the background
+///
+/// # Errors
+/// Returns `CoreError::Validation` if the config is invalid, or an error if the port cannot
+/// be bound
+pub(crate) async fn spawn_socks_proxy(
+    client: Client,
+    config: SocksConfig,
+) -> Result<RunningSocksProxy, CoreError> {
+    config.validate()?;
+    let listeners = bind_listeners(config.bind_address, config.port)
+        .await
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::AddrInUse => CoreError::PortConflict(config.port),
+            _ => CoreError::PortForwarding(format!(
+                "Failed to bind SOCKS proxy on {}:{}: {e}",
+                config.bind_address, config.port
+            )),
+        })?;
+    let addresses = listeners
+        .iter()
+        .filter_map(|listener| listener.local_addr().ok())
+        .collect();
+
+    let tunnels = Arc::new(ServiceTunnels {
+        client,
+        sessions: Mutex::default(),
+    });
+    let default_namespace: Arc<str> = config.default_namespace.into();
+    let task = tokio::spawn(async move {
+        loop {
+            let stream = match accept_any(&listeners).await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    eprintln!("[PortForward] SOCKS proxy stopped accepting: {e}");
+                    return;
+                }
+            };
+            let tunnels = Arc::clone(&tunnels);
+            let default_namespace = Arc::clone(&default_namespace);
+            tokio::spawn(async move {
+                let connect = |service: ClusterService, port| async move {
+                    tunnels.connect(&service, port).await
+                };
+                if let Err(e) = serve_socks_connection(stream, &default_namespace, connect).await {
+                    eprintln!("[PortForward] SOCKS client failed: {e}");
+                }
+            });
+        }
+    });
+
+    Ok(RunningSocksProxy { addresses, task })
+}
@@ -1,227 +1,609 @@
 // Port forwarding task management
 //
-// This module handles spawning and managing port forwarding tasks.
+// This module handles spawning and supervising port forwarding tasks. Each forward runs
+// under a supervisor that rebinds and resumes it after failures, following its backoff policy,
+// and drains its open connections when stopped. Forwards with TLS termination complete the
+// handshake on the local connection and tunnel plaintext to the pod. Lazy forwards open
+// their tunnel on the first connection and close it again once idle. Service forwards the
+// cluster denies port-forwarding for relay HTTP through the API server's service proxy.
 
-use crate::api::kubernetes::portforwarding::types::{PortForwardingConfig, PortForwardingStatus};
+use crate::api::kubernetes::portforwarding::backoff::BackoffPolicy;
+use crate::api::kubernetes::portforwarding::bind::{accept_any, bind_listeners};
+use crate::api::kubernetes::portforwarding::capture::HttpCapture;
+use crate::api::kubernetes::portforwarding::connections::ConnectionSet;
+use crate::api::kubernetes::portforwarding::events::{
+    set_status, EventSender, PortForwardingEvent,
+};
+use crate::api::kubernetes::portforwarding::lazy::{idle_remaining, resolve_wake_target};
+use crate::api::kubernetes::portforwarding::metrics::{copy_bidirectional_shaped, ForwardMetrics};
+use crate::api::kubernetes::portforwarding::service_proxy::ServiceProxy;
+use crate::api::kubernetes::portforwarding::session::{ForwardSessions, TunnelStream};
+use crate::api::kubernetes::portforwarding::shaping::{is_injected_reset, TrafficShaper};
+use crate::api::kubernetes::portforwarding::tls::local_ca;
+use crate::api::kubernetes::portforwarding::types::{
+    ForwardTarget, PortForwardingConfig, PortForwardingStatus, StopSummary,
+};
 use crate::errors::CoreError;
 use k8s_openapi::api::core::v1::Pod;
 use kube::api::Api;
 use kube::Client;
+use roro_domain::ForwardTransport;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::io;
-use tokio::net::TcpListener as TokioTcpListener;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::{self, AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
 use tokio::sync::{mpsc, RwLock};
 use tokio::task::JoinHandle;
+use tokio_rustls::TlsAcceptor;
 
 use super::types::PortForwardingState;
 
-pub type ForwardTaskHandle = (JoinHandle<()>, mpsc::Sender<()>);
+/// A supervisor's handle and its control channel
+pub type ForwardTaskHandle = (JoinHandle<StopSummary>, mpsc::Sender<TaskControl>);
 pub type ForwardTaskMap = Arc<RwLock<HashMap<String, ForwardTaskHandle>>>;
+type ForwardMap = Arc<RwLock<HashMap<String, PortForwardingState>>>;
 
-/// Spawn a port forwarding task
-#[allow(clippy::too_many_lines)]
+/// A request sent to a forward's supervisor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskControl {
+    /// Stop, draining open connections for up to the given timeout
+    Stop(Duration),
+    /// Rebind now, skipping any backoff delay, and count attempts afresh; also revives a
+    /// forward that was marked failed
+    Reconnect,
+}
+
+/// Spawn a supervised port forwarding task
 pub async fn spawn_forward_task(
     client: Client,
-    forwards: Arc<RwLock<HashMap<String, PortForwardingState>>>,
+    forwards: ForwardMap,
     forward_tasks: ForwardTaskMap,
+    events: EventSender,
     forward_id: String,
     config: PortForwardingConfig,
-    reconnect_delay: Duration,
-    max_retries: u32,
+    backoff: BackoffPolicy,
 ) -> Result<(), CoreError> {
-    let manager_forwards = Arc::clone(&forwards);
-    let manager_tasks = Arc::clone(&forward_tasks);
-
-    let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
-    let forward_id_clone = forward_id.clone();
-
-    let handle = tokio::spawn(async move {
-        // Create Pod API
-        let pods: Api<Pod> = Api::namespaced(client.clone(), &config.namespace);
-
-        // Note: Pod validation is done in start_forward() before spawning this task
-        // So we can assume the pod exists here
-
-        // Set up local TCP listener
-        let listener = match TokioTcpListener::bind(("127.0.0.1", config.local_port)).await {
-            Ok(listener) => listener,
-            Err(_e) => {
-                let mut f = forwards.write().await;
-                if let Some(state) = f.get_mut(&forward_id_clone) {
-                    state.status = PortForwardingStatus::Failed;
-                }
-                drop(f);
-
-                let retry_count = {
-                    let mut f = forwards.write().await;
-                    if let Some(state) = f.get_mut(&forward_id_clone) {
-                        state.retry_count
-                    } else {
-                        return;
-                    }
-                };
-
-                if retry_count < max_retries {
-                    tokio::time::sleep(reconnect_delay * retry_count).await;
-                    let _ = reconnect_forward_internal(
-                        &manager_forwards,
-                        &manager_tasks,
-                        &forward_id_clone,
-                        &config,
-                        reconnect_delay,
-                        max_retries,
-                    )
-                    .await;
+    let (control_tx, control_rx) = mpsc::channel::<TaskControl>(1);
+
+    // Share the state's counters, capture and shaper so they carry across reconnects
+    let (metrics, capture, shaper) = forwards
+        .read()
+        .await
+        .get(&forward_id)
+        .map(|state| {
+            (
+                Arc::clone(&state.metrics),
+                Arc::clone(&state.capture),
+                Arc::clone(&state.shaper),
+            )
+        })
+        .unwrap_or_default();
+
+    let handle = tokio::spawn(supervise_forward(
+        ForwardRun {
+            client,
+            forwards,
+            events,
+            forward_id: forward_id.clone(),
+            config,
+            metrics,
+            capture,
+            shaper,
+            connections: ConnectionSet::default(),
+        },
+        backoff,
+        control_rx,
+    ));
+
+    let mut task_map = forward_tasks.write().await;
+    task_map.insert(forward_id, (handle, control_tx));
+
+    Ok(())
+}
+
+/// Everything a single run of a forward needs
+struct ForwardRun {
+    client: Client,
+    forwards: ForwardMap,
+    events: EventSender,
+    forward_id: String,
+    config: PortForwardingConfig,
+    metrics: Arc<ForwardMetrics>,
+    capture: Arc<HttpCapture>,
+    shaper: Arc<TrafficShaper>,
+    /// Connections outlive the run that accepted them, so a restart doesn't cut them
+    connections: ConnectionSet,
+}
+
+/// Run the forward until shutdown, restarting it with backoff whenever it fails
+///
+/// A run that lasted `reset_after` resets the attempt count, and the forward is marked
+/// `Failed` once the policy allows no further attempts. A reconnect request restarts the
+/// forward straight away, even a failed one. On shutdown the listener closes first and open
+/// connections get the requested drain timeout; a dropped channel stops immediately.
+async fn supervise_forward(
+    run: ForwardRun,
+    backoff: BackoffPolicy,
+    mut control_rx: mpsc::Receiver<TaskControl>,
+) -> StopSummary {
+    let forward_id = &run.forward_id;
+    loop {
+        let started = Instant::now();
+        let error = tokio::select! {
+            control = control_rx.recv() => {
+                if let Some(summary) = handle_control(&run, control).await {
+                    return summary;
                 }
-                return;
+                continue;
             }
+            error = run_forward(&run) => error,
         };
 
-        // Listener is bound and ready - task is running successfully
-        // Status is already set to Active in start_forward() after spawning
-        // If we reach here, the task is healthy and running
-
-        // Main forwarding loop
-        loop {
-            tokio::select! {
-                _msg = shutdown_rx.recv() => {
-                    // Graceful shutdown - user requested stop
-                    // Don't change status to Failed on graceful shutdown
-                    // The forward will be removed from active_forwards in stop_forward()
-                    return;
-                }
-                result = listener.accept() => {
-                    match result {
-                        Ok((local_stream, _)) => {
-                            // Spawn a task to handle this connection
-                            // Create a new portforwarder for each connection
-                            let pods_clone = pods.clone();
-                            let pod_name = config.pod.clone();
-                            let remote_port = config.remote_port;
-
-                            tokio::spawn(async move {
-                                // Create a new portforwarder for this connection
-                                match pods_clone.portforward(&pod_name, &[remote_port]).await {
-                                    Ok(mut pf) => {
-                                        // Take the stream for the remote port
-                                        if let Some(remote_stream) = pf.take_stream(remote_port) {
-                                            // Status is already Active when listener is bound
-                                            // No need to update status here
-
-                                            // Split streams for bidirectional copying
-                                            let (mut local_read, mut local_write) = io::split(local_stream);
-                                            let (mut remote_read, mut remote_write) = io::split(remote_stream);
-
-                                            // Spawn tasks for bidirectional data copying
-                                            let local_to_remote = tokio::spawn(async move {
-                                                let _ = io::copy(&mut local_read, &mut remote_write).await;
-                                            });
-
-                                            let remote_to_local = tokio::spawn(async move {
-                                                let _ = io::copy(&mut remote_read, &mut local_write).await;
-                                            });
-
-                                            // Wait for either direction to finish
-                                            tokio::select! {
-                                                _ = local_to_remote => {}
-                                                _ = remote_to_local => {}
-                                            }
-                                        } else {
-                                            eprintln!(
-                                                "[PortForward] Failed to take stream for remote port {remote_port}"
-                                            );
-                                            // Log error but don't change status - individual connection failures
-                                            // shouldn't affect the overall port forward status
-                                        }
-                                    }
-                                    Err(e) => {
-                                        eprintln!(
-                                            "[PortForward] Failed to create portforwarder for remote port {remote_port}: {e}"
-                                        );
-                                        // Log error but don't change status - individual connection failures
-                                        // shouldn't affect the overall port forward status
-                                        // The port forward is still active and can accept other connections
-                                    }
-                                }
-                            });
-                        }
-                        Err(e) => {
-                            eprintln!("[PortForward] Failed to accept connection: {e}");
-                            // Update status on accept error
-                            let mut f = forwards.write().await;
-                            if let Some(state) = f.get_mut(&forward_id_clone) {
-                                if state.status == PortForwardingStatus::Active {
-                                    state.status = PortForwardingStatus::Failed;
-                                }
-                            }
-                            // Break the loop on accept error
-                            break;
-                        }
-                    }
-                }
+        // A failed forward keeps its open connections until it is stopped or reconnected
+        let Some(attempt) = record_failure(&run, &backoff, started, &error).await else {
+            if let Some(summary) = handle_control(&run, control_rx.recv().await).await {
+                return summary;
             }
+            continue;
+        };
+
+        let delay = backoff.next_delay(attempt);
+        eprintln!(
+            "[PortForward] {forward_id} failed: {error}; retrying in {:.1}s (attempt {attempt})",
+            delay.as_secs_f64()
+        );
+        if let Some(summary) = wait_to_retry(&run, &mut control_rx, delay).await {
+            return summary;
         }
+    }
+}
+
+/// Wait out a backoff delay, cut short by a reconnect request; returns the summary if the
+/// forward was stopped meanwhile
+async fn wait_to_retry(
+    run: &ForwardRun,
+    control_rx: &mut mpsc::Receiver<TaskControl>,
+    delay: Duration,
+) -> Option<StopSummary> {
+    tokio::select! {
+        control = control_rx.recv() => handle_control(run, control).await,
+        () = tokio::time::sleep(delay) => None,
+    }
+}
+
+/// Count a failed run against the backoff policy, returning the attempt to wait for, or
+/// `None` once the forward is marked failed
+async fn record_failure(
+    run: &ForwardRun,
+    backoff: &BackoffPolicy,
+    started: Instant,
+    error: &CoreError,
+) -> Option<u32> {
+    let mut f = run.forwards.write().await;
+    // A forward being stopped has already been removed; wait for its drain request
+    let state = f.get_mut(&run.forward_id)?;
+    if started.elapsed() >= backoff.reset_after {
+        state.retry_count = 0;
+    }
+    state.retry_count += 1;
+    state.last_error = Some(error.to_string());
+    // The supervisor owns the status until the listener is bound again
+    state.health_failures = 0;
+    if backoff.allows(state.retry_count) {
+        set_status(state, PortForwardingStatus::Reconnecting, &run.events);
+        Some(state.retry_count)
+    } else {
+        eprintln!(
+            "[PortForward] {} failed, giving up: {error}",
+            run.forward_id
+        );
+        set_status(state, PortForwardingStatus::Failed, &run.events);
+        None
+    }
+}
 
-        // If we reach here, the loop exited unexpectedly (not via shutdown)
-        // This means there was an error - update status to Failed
-        let mut f = forwards.write().await;
-        if let Some(state) = f.get_mut(&forward_id_clone) {
-            if state.status == PortForwardingStatus::Active {
-                state.status = PortForwardingStatus::Failed;
+/// Act on a control request; returns the summary once the forward has stopped
+///
+/// A reconnect resets the attempt count and marks the forward `Reconnecting` until it is
+/// bound again. A dropped channel stops the forward without draining.
+async fn handle_control(run: &ForwardRun, control: Option<TaskControl>) -> Option<StopSummary> {
+    let drain = match control {
+        Some(TaskControl::Reconnect) => {
+            println!("[PortForward] Reconnecting {}", run.forward_id);
+            let mut f = run.forwards.write().await;
+            if let Some(state) = f.get_mut(&run.forward_id) {
+                state.retry_count = 0;
+                state.health_failures = 0;
+                set_status(state, PortForwardingStatus::Reconnecting, &run.events);
             }
+            return None;
         }
-    });
+        Some(TaskControl::Stop(drain)) => drain,
+        None => Duration::ZERO,
+    };
+    Some(run.connections.drain(drain).await)
+}
 
-    let mut task_map = forward_tasks.write().await;
-    task_map.insert(forward_id, (handle, shutdown_tx));
+/// Bind the forward's listeners and serve connections until accepting fails
+///
+/// Only returns on failure, with the error that ended the run.
+async fn run_forward(run: &ForwardRun) -> CoreError {
+    let config = &run.config;
 
-    Ok(())
+    let tls = match tls_acceptor(config).await {
+        Ok(tls) => tls,
+        Err(e) => return e,
+    };
+
+    let listeners = match bind_listeners(config.bind_address, config.local_port).await {
+        Ok(listeners) => listeners,
+        Err(e) => {
+            return CoreError::PortForwarding(format!(
+                "Failed to bind {}:{}: {e}",
+                config.bind_address, config.local_port
+            ))
+        }
+    };
+
+    mark_listening(run).await;
+
+    // Connections are tunnelled over sessions opened through the Pod API; lazy forwards open
+    // theirs when a connection wakes them
+    let pods: Api<Pod> = Api::namespaced(run.client.clone(), &config.namespace);
+    let mut tunnel = (!config.lazy).then(|| ForwardSessions::new(pods.clone(), config.remote_port));
+    let service_proxy = service_proxy(run).map(Arc::new);
+
+    let mut turn = 0usize;
+    loop {
+        let accepted = tokio::select! {
+            accepted = accept_any(&listeners) => accepted,
+            () = wait_until_idle(run, tunnel.is_some()) => {
+                // The next connection resolves the target again; the listener stays bound
+                tunnel = None;
+                fall_idle(run).await;
+                continue;
+            }
+        };
+        let local_stream = match accepted {
+            Ok((local_stream, _)) => local_stream,
+            Err(e) => {
+                return CoreError::PortForwarding(format!("Failed to accept connection: {e}"))
+            }
+        };
+        run.metrics.record_accepted();
+
+        let sessions = match &tunnel {
+            Some(sessions) => Arc::clone(sessions),
+            None => match wake(run, &pods).await {
+                Some(sessions) => Arc::clone(tunnel.insert(sessions)),
+                // The connection is dropped and the forward stays idle
+                None => continue,
+            },
+        };
+
+        let (pod_name, transport) = connection_route(run, turn).await;
+        turn = turn.wrapping_add(1);
+        let connection = Connection {
+            sessions,
+            pod_name,
+            transport,
+            service_proxy: service_proxy.clone(),
+            forwards: Arc::clone(&run.forwards),
+            events: run.events.clone(),
+            forward_id: run.forward_id.clone(),
+            metrics: Arc::clone(&run.metrics),
+            capture: Arc::clone(&run.capture),
+            shaper: Arc::clone(&run.shaper),
+        };
+        let tls = tls.clone();
+
+        run.connections
+            .spawn(connection.serve_accepted(local_stream, tls));
+    }
 }
 
-async fn reconnect_forward_internal(
-    forwards: &Arc<RwLock<HashMap<String, PortForwardingState>>>,
-    tasks: &ForwardTaskMap,
-    forward_id: &str,
-    _config: &PortForwardingConfig,
-    reconnect_delay: Duration,
-    max_retries: u32,
-) -> Result<(), CoreError> {
-    let state = {
-        let f = forwards.read().await;
-        f.get(forward_id).cloned()
+/// The service proxy a service forward can relay through, unless it is limited to
+/// port-forwarding
+fn service_proxy(run: &ForwardRun) -> Option<ServiceProxy> {
+    let config = &run.config;
+    match &config.target {
+        ForwardTarget::Service { name, port }
+            if config.transport != ForwardTransport::PortForward =>
+        {
+            Some(ServiceProxy::new(
+                run.client.clone(),
+                &config.namespace,
+                name,
+                port,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Pod and transport for the next connection
+///
+/// Both are read per connection so failover retargets, round-robin rotation and a fallback
+/// to the service proxy apply without rebinding the listener.
+async fn connection_route(run: &ForwardRun, turn: usize) -> (String, ForwardTransport) {
+    run.forwards.read().await.get(&run.forward_id).map_or_else(
+        || (run.config.pod.clone(), run.config.transport),
+        |s| (s.connection_pod(turn).to_string(), s.config.transport),
+    )
+}
+
+/// The acceptor for a forward with TLS termination, which decrypts locally and tunnels
+/// plaintext to the pod
+async fn tls_acceptor(config: &PortForwardingConfig) -> Result<Option<TlsAcceptor>, CoreError> {
+    if config.tls {
+        local_ca().await?.acceptor().map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Update the status once the listener is bound
+///
+/// A rebind after a failure makes the forward active again, and lazy forwards wait idle for
+/// their first connection.
+async fn mark_listening(run: &ForwardRun) {
+    let mut f = run.forwards.write().await;
+    if let Some(state) = f.get_mut(&run.forward_id) {
+        if run.config.lazy {
+            set_status(state, PortForwardingStatus::Idle, &run.events);
+        } else if state.status == PortForwardingStatus::Reconnecting {
+            set_status(state, PortForwardingStatus::Active, &run.events);
+        }
+    }
+}
+
+/// Wait until a woken lazy forward has gone without connections for its idle timeout
+///
+/// Never finishes for forwards without an idle timeout or without a tunnel to tear down.
+async fn wait_until_idle(run: &ForwardRun, awake: bool) {
+    let Some(idle_timeout) = run.config.idle_timeout.filter(|_| awake) else {
+        return std::future::pending().await;
     };
+    while let Some(remaining) =
+        idle_remaining(&run.metrics.snapshot(), idle_timeout, SystemTime::now())
+    {
+        tokio::time::sleep(remaining).await;
+    }
+}
 
-    let state = state.ok_or_else(|| {
-        CoreError::PortForwardingNotFound(format!("Forward not found: {forward_id}"))
-    })?;
+/// Resolve a lazy forward's target and open sessions to it, marking the forward active
+///
+/// Returns `None` if no ready pod matches; the forward then stays idle with the error
+/// recorded, and the next connection tries again.
+async fn wake(run: &ForwardRun, pods: &Api<Pod>) -> Option<Arc<ForwardSessions>> {
+    match resolve_wake_target(&run.client, &run.config).await {
+        Ok(target) => {
+            println!(
+                "[PortForward] Waking {} on pod {}",
+                run.forward_id, target.pod
+            );
+            let sessions = ForwardSessions::new(pods.clone(), target.remote_port);
+            let mut f = run.forwards.write().await;
+            if let Some(state) = f.get_mut(&run.forward_id) {
+                state.config.pod = target.pod;
+                state.config.remote_port = target.remote_port;
+                state.ready_pods = target.ready_pods;
+                state.last_error = None;
+                set_status(state, PortForwardingStatus::Active, &run.events);
+            }
+            Some(sessions)
+        }
+        Err(e) => {
+            eprintln!("[PortForward] Failed to wake {}: {e}", run.forward_id);
+            run.metrics.record_failed();
+            if let Some(state) = run.forwards.write().await.get_mut(&run.forward_id) {
+                state.last_error = Some(e.to_string());
+            }
+            None
+        }
+    }
+}
+
+/// Mark a lazy forward idle after its tunnel was torn down
+async fn fall_idle(run: &ForwardRun) {
+    println!(
+        "[PortForward] {} is idle, closing its tunnel",
+        run.forward_id
+    );
+    if let Some(state) = run.forwards.write().await.get_mut(&run.forward_id) {
+        set_status(state, PortForwardingStatus::Idle, &run.events);
+    }
+}
+
+/// One accepted local connection and what it needs to reach the pod
+struct Connection {
+    sessions: Arc<ForwardSessions>,
+    pod_name: String,
+    transport: ForwardTransport,
+    service_proxy: Option<Arc<ServiceProxy>>,
+    forwards: ForwardMap,
+    events: EventSender,
+    forward_id: String,
+    metrics: Arc<ForwardMetrics>,
+    capture: Arc<HttpCapture>,
+    shaper: Arc<TrafficShaper>,
+}
 
-    if state.retry_count >= max_retries {
-        let mut f = forwards.write().await;
-        if let Some(s) = f.get_mut(forward_id) {
-            s.status = PortForwardingStatus::Failed;
+impl Connection {
+    /// Serve an accepted connection, terminating TLS first when the forward does
+    async fn serve_accepted(self, mut local: TcpStream, tls: Option<TlsAcceptor>) {
+        match tls {
+            // The handshake completes before a tunnel is opened for the connection
+            Some(acceptor) => match acceptor.accept(local).await {
+                Ok(mut tls_stream) => {
+                    if self.serve(&mut tls_stream).await {
+                        reset_on_close(tls_stream.get_ref().0);
+                    }
+                }
+                Err(e) => {
+                    self.metrics.record_failed();
+                    eprintln!(
+                        "[PortForward] TLS handshake failed on {}: {e}",
+                        self.forward_id
+                    );
+                }
+            },
+            None => {
+                if self.serve(&mut local).await {
+                    reset_on_close(&local);
+                }
+            }
         }
-        return Err(CoreError::PortForwarding(format!(
-            "Max retries exceeded for {forward_id}"
-        )));
     }
 
+    /// Tunnel `local` to the pod until both directions finish, or relay its requests
+    /// through the service proxy
+    ///
+    /// Returns whether fault injection reset the connection.
+    async fn serve<L>(self, local: &mut L) -> bool
+    where
+        L: AsyncRead + AsyncWrite + Unpin,
     {
-        let mut f = forwards.write().await;
-        if let Some(s) = f.get_mut(forward_id) {
-            s.status = PortForwardingStatus::Reconnecting;
-            s.retry_count += 1;
+        let remote = match self.open_remote().await {
+            Ok(remote) => remote,
+            Err(e) => {
+                self.metrics.record_failed();
+                eprintln!("[PortForward] {e}");
+                // Log error but don't change status - individual connection failures
+                // shouldn't affect the overall port forward status
+                // The port forward is still active and can accept other connections
+                return false;
+            }
+        };
+
+        self.metrics.record_opened();
+        let _ = self.events.send(PortForwardingEvent::ConnectionOpened {
+            forward_id: self.forward_id.clone(),
+        });
+
+        let reset = match remote {
+            // Each direction runs to EOF so half-closed connections
+            // still deliver the peer's remaining data
+            Remote::Tunnel(remote_stream) => copy_connection(
+                local,
+                remote_stream,
+                &self.metrics,
+                &self.capture,
+                &self.shaper,
+            )
+            .await
+            .is_err_and(|e| is_injected_reset(&e)),
+            Remote::ServiceProxy(proxy) => {
+                let _ = proxy_connection(local, &proxy, &self.metrics, &self.capture).await;
+                false
+            }
+        };
+
+        self.metrics.record_closed();
+        let _ = self.events.send(PortForwardingEvent::ConnectionClosed {
+            forward_id: self.forward_id,
+        });
+        reset
+    }
+
+    /// Open the connection's way to the service, switching an automatic service forward to
+    /// the service proxy for good once port-forwarding turns out to be forbidden
+    async fn open_remote(&self) -> Result<Remote, CoreError> {
+        if let (ForwardTransport::ServiceProxy, Some(proxy)) = (self.transport, &self.service_proxy)
+        {
+            return Ok(Remote::ServiceProxy(Arc::clone(proxy)));
         }
+
+        let result = self.sessions.connect(&self.pod_name).await;
+        // Only automatic service forwards have a proxy to fall back to
+        match (&result, &self.service_proxy) {
+            (Err(CoreError::PortForwardForbidden(reason)), Some(proxy)) => {
+                eprintln!(
+                    "[PortForward] {reason}; {} falls back to the service proxy",
+                    self.forward_id
+                );
+                if let Some(state) = self.forwards.write().await.get_mut(&self.forward_id) {
+                    state.config.transport = ForwardTransport::ServiceProxy;
+                }
+                Ok(Remote::ServiceProxy(Arc::clone(proxy)))
+            }
+            _ => result.map(Remote::Tunnel),
+        }
+    }
+}
+
+/// Make dropping `local` send the client a reset rather than a clean close, the way an
+/// injected reset should look from the client's side
+fn reset_on_close(local: &TcpStream) {
+    if let Err(e) = local.set_zero_linger() {
+        eprintln!("[PortForward] Failed to reset connection: {e}");
     }
+}
 
-    tokio::time::sleep(reconnect_delay * state.retry_count).await;
+/// Where an accepted connection's traffic goes
+enum Remote {
+    Tunnel(Box<dyn TunnelStream>),
+    ServiceProxy(Arc<ServiceProxy>),
+}
 
-    let mut t = tasks.write().await;
-    if let Some((handle, shutdown_tx)) = t.remove(forward_id) {
-        drop(shutdown_tx);
-        handle.abort();
+/// Copy a connection in both directions, parsing it into the capture while inspection is on
+///
+/// The shaper is consulted for every chunk, so shaping changed while the connection is open
+/// applies to it straight away.
+async fn copy_connection<L>(
+    local: L,
+    remote: Box<dyn TunnelStream>,
+    metrics: &ForwardMetrics,
+    capture: &Arc<HttpCapture>,
+    shaper: &TrafficShaper,
+) -> io::Result<(u64, u64)>
+where
+    L: AsyncRead + AsyncWrite + Unpin,
+{
+    let Some(tap) = capture.tap() else {
+        return copy_bidirectional_shaped(local, remote, metrics, &|_, _| {}, shaper).await;
+    };
+
+    let tap = std::sync::Mutex::new(tap);
+    let observe = |direction, data: &[u8]| {
+        if let Ok(mut tap) = tap.lock() {
+            tap.observe(direction, data);
+        }
+    };
+    let copied = copy_bidirectional_shaped(local, remote, metrics, &observe, shaper).await;
+    if let Ok(mut tap) = tap.into_inner() {
+        tap.finish();
     }
+    copied
+}
 
-    Ok(())
+/// Relay a connection's HTTP requests through the service proxy, parsing them into the
+/// capture while inspection is on
+///
+/// Traffic shaping is not applied to relayed requests.
+async fn proxy_connection<L>(
+    local: L,
+    proxy: &ServiceProxy,
+    metrics: &ForwardMetrics,
+    capture: &Arc<HttpCapture>,
+) -> io::Result<()>
+where
+    L: AsyncRead + AsyncWrite + Unpin,
+{
+    let Some(tap) = capture.tap() else {
+        return proxy.serve(local, metrics, &|_, _| {}).await;
+    };
+
+    let tap = std::sync::Mutex::new(tap);
+    let observe = |direction, data: &[u8]| {
+        if let Ok(mut tap) = tap.lock() {
+            tap.observe(direction, data);
+        }
+    };
+    let served = proxy.serve(local, metrics, &observe).await;
+    if let Ok(mut tap) = tap.into_inner() {
+        tap.finish();
+    }
+    served
 }
@@ -0,0 +1,356 @@
+// Local TLS termination
+//
+// This module keeps the certificate authority forwards use to terminate TLS on their local
+// listener. The CA is created once and stored under ~/.roro/tls so developers only trust it
+// once; server certificates are issued from it per requested host name and kept in memory.
+
+use crate::errors::CoreError;
+use rcgen::{
+    BasicConstraints, CertificateParams, CidrSubnet, DistinguishedName, DnType,
+    ExtendedKeyUsagePurpose, GeneralSubtree, IsCa, Issuer, KeyIdMethod, KeyPair, KeyUsagePurpose,
+    NameConstraints, SerialNumber, PKCS_ECDSA_P256_SHA256,
+};
+use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY};
+use ring::rand::{SecureRandom, SystemRandom};
+use roro_persistence::{get_tls_ca_path, load_tls_ca, save_ca_certificate_to, save_tls_ca};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls::ServerConfig;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::OnceCell;
+use tokio_rustls::TlsAcceptor;
+
+/// Common name of the local CA; issued certificates name it as their issuer
+pub const CA_COMMON_NAME: &str = "roro-kube local development CA";
+
+/// Hosts every server certificate covers, so loopback URLs work without SNI
+pub const DEFAULT_TLS_HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+/// DNS names the CA may issue for, each with its subdomains: the route domain and the
+/// in-cluster service names that dedicated addresses resolve on this machine
+pub const PERMITTED_TLS_DOMAINS: [&str; 3] = ["localhost", "svc", "cluster.local"];
+
+/// Organisation named in every certificate issued
+const ORGANIZATION: &str = "roro-kube";
+
+const CA_VALIDITY: Duration = Duration::from_hours(24 * 3650);
+const SERVER_CERT_VALIDITY: Duration = Duration::from_hours(24 * 365);
+
+/// How far certificates are backdated, so clocks that are slightly behind still accept them
+const BACKDATE: Duration = Duration::from_hours(1);
+
+const CERT_PEM_TAG: &str = "CERTIFICATE";
+const KEY_PEM_TAG: &str = "PRIVATE KEY";
+
+/// Process-wide CA, loaded or created on first use
+static LOCAL_CA: OnceCell<Arc<LocalCa>> = OnceCell::const_new();
+
+/// A certificate authority that issues server certificates for local hosts
+#[derive(Debug)]
+pub struct LocalCa {
+    issuer: Issuer<'static, KeyPair>,
+    certificate: Vec<u8>,
+    /// Key shared by the server certificates this process issues
+    server_key: KeyPair,
+}
+
+impl LocalCa {
+    /// Create a new CA with a fresh key
+    ///
+    /// The CA is name constrained to loopback addresses and the local host names in
+    /// `PERMITTED_TLS_DOMAINS`, so trusting it doesn't let it vouch for other sites.
+    ///
+    /// # Errors
+    /// Returns an error if key generation or signing fails
+    pub fn generate() -> Result<Self, CoreError> {
+        let key = generate_key()?;
+        let mut params = ca_params(&key)?;
+        params.serial_number = Some(random_serial()?);
+        params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+        params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+        params.name_constraints = Some(local_name_constraints());
+        let certificate = params
+            .self_signed(&key)
+            .map_err(|e| tls_error(&e))?
+            .der()
+            .to_vec();
+        Ok(Self {
+            issuer: Issuer::new(params, key),
+            certificate,
+            server_key: generate_key()?,
+        })
+    }
+
+    /// Load a CA from its certificate and PKCS#8 key PEMs
+    ///
+    /// # Errors
+    /// Returns an error if either PEM is malformed or the key is not ECDSA P-256
+    pub fn from_pem(cert_pem: &str, key_pem: &str) -> Result<Self, CoreError> {
+        let certificate = parse_pem(cert_pem, CERT_PEM_TAG)?;
+        let key_der = PrivatePkcs8KeyDer::from(parse_pem(key_pem, KEY_PEM_TAG)?);
+        let key = KeyPair::from_pkcs8_der_and_sign_algo(&key_der, &PKCS_ECDSA_P256_SHA256)
+            .map_err(|_| CoreError::PortForwarding("TLS: key is not an ECDSA P-256 key".into()))?;
+        Ok(Self {
+            issuer: Issuer::new(ca_params(&key)?, key),
+            certificate,
+            server_key: generate_key()?,
+        })
+    }
+
+    #[must_use]
+    pub fn certificate_der(&self) -> &[u8] {
+        &self.certificate
+    }
+
+    #[must_use]
+    pub fn certificate_pem(&self) -> String {
+        pem::encode(&pem::Pem::new(CERT_PEM_TAG, self.certificate.clone()))
+    }
+
+    #[must_use]
+    pub fn key_pem(&self) -> String {
+        self.issuer.key().serialize_pem()
+    }
+
+    /// Issue a server certificate for `host` and the default loopback hosts
+    ///
+    /// # Errors
+    /// Returns an error if `host` is not a local host name the CA may issue for, or
+    /// signing fails
+    pub fn issue(&self, host: &str) -> Result<CertifiedKey, CoreError> {
+        if !is_permitted_host(host) {
+            return Err(CoreError::PortForwarding(format!(
+                "TLS: {host} is not a loopback address or a host name under {}",
+                PERMITTED_TLS_DOMAINS.join(", ")
+            )));
+        }
+        let mut hosts = vec![host.to_string()];
+        hosts.extend(
+            DEFAULT_TLS_HOSTS
+                .iter()
+                .filter(|default| !default.eq_ignore_ascii_case(host))
+                .map(ToString::to_string),
+        );
+
+        let mut params = CertificateParams::new(hosts).map_err(|e| tls_error(&e))?;
+        params.distinguished_name = distinguished_name(host);
+        params.serial_number = Some(random_serial()?);
+        params.is_ca = IsCa::ExplicitNoCa;
+        params.key_usages = vec![KeyUsagePurpose::DigitalSignature];
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+        params.use_authority_key_identifier_extension = true;
+        params.key_identifier_method = key_id_method(&self.server_key);
+        set_validity(&mut params, SERVER_CERT_VALIDITY);
+        let certificate = params
+            .signed_by(&self.server_key, &self.issuer)
+            .map_err(|e| tls_error(&e))?;
+
+        let key_der =
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(self.server_key.serialize_der()));
+        let signing_key = rustls::crypto::ring::sign::any_ecdsa_type(&key_der)
+            .map_err(|e| CoreError::PortForwarding(format!("TLS: unusable server key: {e}")))?;
+        Ok(CertifiedKey::new(
+            vec![
+                certificate.der().clone(),
+                CertificateDer::from(self.certificate.clone()),
+            ],
+            signing_key,
+        ))
+    }
+
+    /// Build an acceptor that serves certificates from this CA, issued per SNI host name
+    ///
+    /// # Errors
+    /// Returns an error if the TLS configuration cannot be built
+    pub fn acceptor(self: &Arc<Self>) -> Result<TlsAcceptor, CoreError> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let mut config = ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| CoreError::PortForwarding(format!("TLS: {e}")))?
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(HostCertResolver {
+                ca: Arc::clone(self),
+                issued: Mutex::default(),
+            }));
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+}
+
+/// Load the CA from ~/.roro/tls, creating and saving it on first use
+///
+/// # Errors
+/// Returns an error if the stored CA is unreadable or a new one cannot be saved
+pub async fn local_ca() -> Result<Arc<LocalCa>, CoreError> {
+    LOCAL_CA
+        .get_or_try_init(|| async {
+            if let Some((cert_pem, key_pem)) = load_tls_ca().await? {
+                return Ok(Arc::new(LocalCa::from_pem(&cert_pem, &key_pem)?));
+            }
+            let ca = LocalCa::generate()?;
+            save_tls_ca(&ca.certificate_pem(), &ca.key_pem()).await?;
+            println!(
+                "[PortForward] Created local TLS CA at {}",
+                get_tls_ca_path()?.display()
+            );
+            Ok(Arc::new(ca))
+        })
+        .await
+        .cloned()
+}
+
+/// Export the CA certificate so it can be trusted, creating the CA if needed
+///
+/// Without a `path` this returns the certificate's location under ~/.roro/tls.
+///
+/// # Errors
+/// Returns an error if the CA cannot be loaded or created, or the file cannot be written
+pub async fn export_ca(path: Option<&Path>) -> Result<PathBuf, CoreError> {
+    let ca = local_ca().await?;
+    match path {
+        Some(path) => {
+            save_ca_certificate_to(path, &ca.certificate_pem()).await?;
+            Ok(path.to_path_buf())
+        }
+        None => Ok(get_tls_ca_path()?),
+    }
+}
+
+/// Issues and caches a server certificate per SNI host name
+#[derive(Debug)]
+struct HostCertResolver {
+    ca: Arc<LocalCa>,
+    issued: Mutex<HashMap<String, Arc<CertifiedKey>>>,
+}
+
+impl ResolvesServerCert for HostCertResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        // Clients connecting by IP address send no server name
+        let host = client_hello
+            .server_name()
+            .unwrap_or(DEFAULT_TLS_HOSTS[0])
+            .to_ascii_lowercase();
+        let mut issued = self
+            .issued
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(certified) = issued.get(&host) {
+            return Some(Arc::clone(certified));
+        }
+        match self.ca.issue(&host) {
+            Ok(certified) => {
+                let certified = Arc::new(certified);
+                issued.insert(host, Arc::clone(&certified));
+                Some(certified)
+            }
+            Err(e) => {
+                eprintln!("[PortForward] Failed to issue a certificate for {host}: {e}");
+                None
+            }
+        }
+    }
+}
+
+/// Whether the CA may issue a certificate for `host`
+///
+/// Loopback addresses and names under `PERMITTED_TLS_DOMAINS` are permitted; the CA's name
+/// constraints make clients reject anything else it signed.
+#[must_use]
+pub fn is_permitted_host(host: &str) -> bool {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return ip.is_loopback();
+    }
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    PERMITTED_TLS_DOMAINS.iter().any(|domain| {
+        host == *domain
+            || host
+                .strip_suffix(domain)
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+/// Parameters naming the CA; certificates it issues take their issuer from these
+fn ca_params(key: &KeyPair) -> Result<CertificateParams, CoreError> {
+    let mut params = CertificateParams::new(Vec::<String>::new()).map_err(|e| tls_error(&e))?;
+    params.distinguished_name = distinguished_name(CA_COMMON_NAME);
+    params.key_identifier_method = key_id_method(key);
+    set_validity(&mut params, CA_VALIDITY);
+    Ok(params)
+}
+
+fn local_name_constraints() -> NameConstraints {
+    let mut permitted_subtrees: Vec<GeneralSubtree> = PERMITTED_TLS_DOMAINS
+        .iter()
+        .map(|domain| GeneralSubtree::DnsName((*domain).to_string()))
+        .collect();
+    permitted_subtrees.push(GeneralSubtree::IpAddress(CidrSubnet::from_v4_prefix(
+        [127, 0, 0, 0],
+        8,
+    )));
+    permitted_subtrees.push(GeneralSubtree::IpAddress(CidrSubnet::from_v6_prefix(
+        std::net::Ipv6Addr::LOCALHOST.octets(),
+        128,
+    )));
+    NameConstraints {
+        permitted_subtrees,
+        excluded_subtrees: Vec::new(),
+    }
+}
+
+fn distinguished_name(common_name: &str) -> DistinguishedName {
+    let mut name = DistinguishedName::new();
+    name.push(DnType::OrganizationName, ORGANIZATION);
+    name.push(DnType::CommonName, common_name);
+    name
+}
+
+/// Key identifiers are the SHA-1 of the public key, as in CAs stored by earlier versions
+fn key_id_method(key: &KeyPair) -> KeyIdMethod {
+    KeyIdMethod::PreSpecified(
+        digest(&SHA1_FOR_LEGACY_USE_ONLY, key.public_key_raw())
+            .as_ref()
+            .to_vec(),
+    )
+}
+
+fn set_validity(params: &mut CertificateParams, validity: Duration) {
+    let now = OffsetDateTime::now_utc();
+    params.not_before = now - BACKDATE;
+    params.not_after = now + validity;
+}
+
+fn generate_key() -> Result<KeyPair, CoreError> {
+    KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).map_err(|e| tls_error(&e))
+}
+
+fn random_serial() -> Result<SerialNumber, CoreError> {
+    let mut serial = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut serial)
+        .map_err(|_| CoreError::PortForwarding("TLS: failed to generate a serial number".into()))?;
+    // Keep the serial positive and its encoding minimal
+    serial[0] = (serial[0] & 0x7f) | 0x40;
+    Ok(SerialNumber::from_slice(&serial))
+}
+
+fn tls_error(error: &rcgen::Error) -> CoreError {
+    CoreError::PortForwarding(format!("TLS: {error}"))
+}
+
+fn parse_pem(contents: &str, tag: &str) -> Result<Vec<u8>, CoreError> {
+    let parsed = pem::parse(contents)
+        .map_err(|e| CoreError::PortForwarding(format!("TLS: invalid {tag} PEM: {e}")))?;
+    if parsed.tag() != tag {
+        return Err(CoreError::PortForwarding(format!(
+            "TLS: expected a {tag} PEM, found {}",
+            parsed.tag()
+        )));
+    }
+    Ok(parsed.into_contents())
+}
@@ -2,15 +2,82 @@
 //
 // This module defines the types used for port forwarding configuration and state.
 
-use std::time::SystemTime;
+use crate::api::kubernetes::portforwarding::bind::BindAddress;
+use crate::api::kubernetes::portforwarding::capture::{CaptureConfig, HttpCapture};
+use crate::api::kubernetes::portforwarding::metrics::ForwardMetrics;
+use crate::api::kubernetes::portforwarding::shaping::{TrafficShaper, TrafficShaping};
+use crate::errors::CoreError;
+use roro_domain::{ForwardTransport, HealthCheckConfig, PodSelection, PortValue, ResourceKind};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct PortForwardingConfig {
     pub namespace: String,
     pub pod: String,
     pub remote_port: u16,
     pub local_port: u16,
     pub instance_id: String,
+    /// Resource the forward was requested against; `pod` holds the resolved pod name
+    pub target: ForwardTarget,
+    /// Probe run through the tunnel by health monitoring; `None` only checks the stream opens
+    pub health_check: Option<HealthCheckConfig>,
+    /// Local address the forward listens on
+    pub bind_address: BindAddress,
+    /// Group the forward belongs to; members share a pod and are retargeted together
+    pub group_id: Option<String>,
+    /// Terminate TLS on the local listener with a certificate from the local CA and forward
+    /// plaintext to the pod
+    pub tls: bool,
+    /// Record HTTP exchanges through the forward from the start; `None` leaves capture off
+    pub capture: Option<CaptureConfig>,
+    /// How long `start_forward` waits for a matching pod to become ready; `None` fails
+    /// right away when no ready pod matches
+    pub ready_timeout: Option<Duration>,
+    /// Latency, bandwidth and fault injection applied from the start; `None` leaves traffic
+    /// untouched
+    pub shaping: Option<TrafficShaping>,
+    /// Bind the listener right away but resolve the pod and open the tunnel only when the
+    /// first connection arrives
+    pub lazy: bool,
+    /// Tear a lazy forward's tunnel down after this long without connections, keeping the
+    /// listener; `None` keeps it open once woken
+    pub idle_timeout: Option<Duration>,
+    /// How connections reach the cluster; an automatic service forward switches to the
+    /// service proxy when port-forwarding is forbidden and keeps it from then on
+    pub transport: ForwardTransport,
+}
+
+/// Kubernetes resource a port forward targets
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ForwardTarget {
+    /// A pod, matched by exact name or name prefix
+    #[default]
+    Pod,
+    /// A service, resolved through its endpoints or selector to a ready backing pod
+    Service { name: String, port: PortValue },
+    /// Pods of a workload, or pods matching a name prefix for `ResourceKind::Pod`, picked by
+    /// `selection`, or one `StatefulSet` pod by `ordinal`; `kind` is never a service
+    Workload {
+        kind: ResourceKind,
+        name: String,
+        selection: PodSelection,
+        ordinal: Option<u32>,
+    },
+}
+
+impl ForwardTarget {
+    /// Whether connections are spread across the target's ready pods
+    #[must_use]
+    pub fn is_round_robin(&self) -> bool {
+        matches!(
+            self,
+            Self::Workload {
+                selection: PodSelection::RoundRobin,
+                ..
+            }
+        )
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -19,13 +86,163 @@ pub enum PortForwardingStatus {
     Active,
     Failed,
     Reconnecting,
+    /// A lazy forward is listening without a tunnel; the next connection opens one
+    Idle,
+}
+
+/// Outcome of the most recent health check through a forward's tunnel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    Healthy,
+    Unhealthy { reason: String },
+}
+
+/// How starting an app instance handles a forward that fails to start
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartMode {
+    /// Stop the forwards already started and return the error
+    #[default]
+    AllOrNothing,
+    /// Start every forward that can be started and report each outcome
+    BestEffort,
+}
+
+/// Outcome of starting one forward of an app instance
+#[derive(Debug)]
+pub struct AppForwardOutcome {
+    /// The forward as declared in app.json, e.g. "service/api:http"
+    pub forward: String,
+    /// The id of the started forward, or why it didn't start
+    pub result: Result<String, CoreError>,
+}
+
+/// What starting an app instance did for each of its forwards, in app.json order
+#[derive(Debug, Default)]
+pub struct InstanceStartReport {
+    pub forwards: Vec<AppForwardOutcome>,
+}
+
+impl InstanceStartReport {
+    /// Ids of the forwards that started
+    #[must_use]
+    pub fn started(&self) -> Vec<&str> {
+        self.forwards
+            .iter()
+            .filter_map(|outcome| outcome.result.as_deref().ok())
+            .collect()
+    }
+
+    /// Forwards that failed to start, with their errors
+    #[must_use]
+    pub fn failed(&self) -> Vec<(&str, &CoreError)> {
+        self.forwards
+            .iter()
+            .filter_map(|outcome| {
+                outcome
+                    .result
+                    .as_ref()
+                    .err()
+                    .map(|e| (outcome.forward.as_str(), e))
+            })
+            .collect()
+    }
+
+    /// Whether every forward started
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.forwards.iter().all(|outcome| outcome.result.is_ok())
+    }
+}
+
+/// How stopping a forward treats connections that are still open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopMode {
+    /// Close the listener, then let open connections finish up to the drain timeout
+    Drain,
+    /// Close the listener and every open connection immediately
+    Now,
+}
+
+/// Connections a stop let finish and those it had to close
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StopSummary {
+    /// Connections that finished on their own while draining
+    pub drained: usize,
+    /// Connections still open when the drain timeout expired, or when stopping immediately
+    pub force_closed: usize,
+}
+
+impl std::ops::Add for StopSummary {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            drained: self.drained + other.drained,
+            force_closed: self.force_closed + other.force_closed,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct PortForwardingState {
     pub id: String,
+    /// Kube context of the manager running the forward
+    pub context: String,
     pub config: PortForwardingConfig,
     pub status: PortForwardingStatus,
     pub last_health_check: Option<SystemTime>,
+    /// Result of the most recent health check; `None` until the first check runs
+    pub health: Option<HealthStatus>,
+    /// Consecutive failed runs of the forward's task, counted against its backoff policy
     pub retry_count: u32,
+    /// Consecutive failed health checks; these trigger failover but never fail the forward
+    /// while its task is still serving
+    pub health_failures: u32,
+    /// Most recent failure reported by the forward's task or health checks
+    pub last_error: Option<String>,
+    /// Live traffic counters; call `snapshot()` to read them
+    pub metrics: Arc<ForwardMetrics>,
+    /// HTTP exchanges captured while inspection is on
+    pub capture: Arc<HttpCapture>,
+    /// Ready pods a round-robin forward spreads new connections across, kept current by
+    /// failover; empty for other forwards, which use `config.pod`
+    pub ready_pods: Vec<String>,
+    /// Traffic shaping and fault injection applied to the forward's connections
+    pub shaper: Arc<TrafficShaper>,
+}
+
+impl PortForwardingState {
+    /// A forward that is still connecting, with capture and traffic shaping set up from
+    /// `config`
+    #[must_use]
+    pub fn new(id: &str, context: &str, config: PortForwardingConfig) -> Self {
+        Self {
+            id: id.to_string(),
+            context: context.to_string(),
+            capture: Arc::new(HttpCapture::new(config.capture.clone())),
+            shaper: Arc::new(TrafficShaper::new(config.shaping.clone())),
+            config,
+            status: PortForwardingStatus::Connecting,
+            last_health_check: None,
+            health: None,
+            retry_count: 0,
+            health_failures: 0,
+            last_error: None,
+            metrics: Arc::default(),
+            ready_pods: Vec::new(),
+        }
+    }
+
+    /// Pod the `turn`-th new connection should be tunnelled to
+    ///
+    /// Round-robin forwards rotate through their ready pods; other forwards, and round-robin
+    /// forwards before their ready pods are known, use `config.pod`.
+    #[must_use]
+    pub fn connection_pod(&self, turn: usize) -> &str {
+        if self.config.target.is_round_robin() && !self.ready_pods.is_empty() {
+            &self.ready_pods[turn % self.ready_pods.len()]
+        } else {
+            &self.config.pod
+        }
+    }
 }
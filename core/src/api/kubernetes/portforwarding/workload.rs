@@ -0,0 +1,278 @@
+// Workload resolution for port forwarding
+//
+// This module resolves port forwards to Deployments, StatefulSets, DaemonSets and pod name
+// prefixes to a ready pod picked by the forward's selection strategy, and maps the
+// configured port to a container port of that pod.
+
+use crate::api::kubernetes::portforwarding::pods::{
+    is_pod_ready, label_selector, matching_pods, PodMatch,
+};
+use crate::errors::CoreError;
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, StatefulSet};
+use k8s_openapi::api::core::v1::Pod;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
+use kube::api::{Api, ListParams};
+use kube::Client;
+use roro_domain::{PodSelection, PortValue, ResourceKind};
+
+/// Label the `StatefulSet` controller puts on each pod with the pod's own name
+pub const STATEFUL_SET_POD_NAME_LABEL: &str = "statefulset.kubernetes.io/pod-name";
+
+/// A workload port forward resolved to a concrete pod and container port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedWorkload {
+    pub pod: String,
+    pub remote_port: u16,
+    /// Ready pods of the workload, sorted by name
+    pub ready_pods: Vec<String>,
+}
+
+/// Names of the ready pods among `pods`, sorted by name, leaving out pods being deleted
+#[must_use]
+pub fn ready_pod_names<'a>(pods: impl IntoIterator<Item = &'a Pod>) -> Vec<String> {
+    let mut ready: Vec<String> = pods
+        .into_iter()
+        .filter(|pod| is_serving(pod))
+        .filter_map(|pod| pod.metadata.name.clone())
+        .collect();
+    ready.sort_unstable();
+    ready
+}
+
+/// Pick the pod a forward starts on among `pods`, or `None` if none is ready
+///
+/// Pods being deleted are skipped even while they still report ready. Round-robin
+/// forwards start on the first ready pod by name and rotate from there per connection.
+/// Pods created at the same time are ordered by name.
+#[must_use]
+pub fn select_pod<'a>(
+    pods: impl IntoIterator<Item = &'a Pod>,
+    selection: PodSelection,
+) -> Option<String> {
+    let mut ready: Vec<&Pod> = pods.into_iter().filter(|pod| is_serving(pod)).collect();
+    ready.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+
+    let pod = match selection {
+        PodSelection::FirstReady | PodSelection::RoundRobin => ready.first().copied(),
+        // max_by_key keeps the last of equal pods, so reverse to break ties by first name
+        PodSelection::Newest => ready
+            .into_iter()
+            .rev()
+            .max_by_key(|pod| pod.metadata.creation_timestamp.clone()),
+    };
+    pod.and_then(|pod| pod.metadata.name.clone())
+}
+
+/// Whether a pod can take new connections: ready and not being deleted
+fn is_serving(pod: &Pod) -> bool {
+    pod.metadata.deletion_timestamp.is_none() && is_pod_ready(pod)
+}
+
+/// Name of the `StatefulSet` pod with the given ordinal
+#[must_use]
+pub fn stateful_set_pod_name(name: &str, ordinal: u32) -> String {
+    format!("{name}-{ordinal}")
+}
+
+/// Label selector matching the pods a workload forward can use
+///
+/// A `StatefulSet` ordinal selects only that pod, so failover waits for it to come back
+/// rather than moving to another ordinal. Pod targets are matched by name and have no
+/// selector.
+///
+/// # Errors
+/// Returns an error if the workload does not exist or has no pod selector
+pub async fn workload_selector(
+    client: &Client,
+    namespace: &str,
+    kind: ResourceKind,
+    name: &str,
+    ordinal: Option<u32>,
+) -> Result<Option<String>, CoreError> {
+    let not_found = |e: kube::Error| {
+        CoreError::PortForwarding(format!(
+            "{kind} {name} not found in namespace {namespace}: {e}"
+        ))
+    };
+
+    let selector = match kind {
+        ResourceKind::Deployment => {
+            let deployments: Api<Deployment> = Api::namespaced(client.clone(), namespace);
+            deployments
+                .get(name)
+                .await
+                .map_err(not_found)?
+                .spec
+                .map(|spec| spec.selector)
+        }
+        ResourceKind::StatefulSet => {
+            let stateful_sets: Api<StatefulSet> = Api::namespaced(client.clone(), namespace);
+            let stateful_set = stateful_sets.get(name).await.map_err(not_found)?;
+            if let Some(ordinal) = ordinal {
+                return Ok(Some(format!(
+                    "{STATEFUL_SET_POD_NAME_LABEL}={}",
+                    stateful_set_pod_name(name, ordinal)
+                )));
+            }
+            stateful_set.spec.map(|spec| spec.selector)
+        }
+        ResourceKind::DaemonSet => {
+            let daemon_sets: Api<DaemonSet> = Api::namespaced(client.clone(), namespace);
+            daemon_sets
+                .get(name)
+                .await
+                .map_err(not_found)?
+                .spec
+                .map(|spec| spec.selector)
+        }
+        ResourceKind::Pod | ResourceKind::Service => return Ok(None),
+    };
+
+    selector
+        .as_ref()
+        .map(selector_query)
+        .transpose()?
+        .flatten()
+        .map(Some)
+        .ok_or_else(|| CoreError::PortForwarding(format!("{kind} {name} has no pod selector")))
+}
+
+/// Label selector query equivalent to a workload's `LabelSelector`
+///
+/// `matchLabels` become `key=value` terms and `matchExpressions` become `key in (a,b)`,
+/// `key notin (a,b)`, `key` or `!key` terms, all of which must match. Returns `None` for a
+/// selector with no terms, which would match every pod in the namespace.
+///
+/// # Errors
+/// Returns an error if an expression uses an unknown operator
+pub fn selector_query(selector: &LabelSelector) -> Result<Option<String>, CoreError> {
+    let mut terms: Vec<String> = selector
+        .match_labels
+        .as_ref()
+        .filter(|labels| !labels.is_empty())
+        .map(label_selector)
+        .into_iter()
+        .collect();
+
+    for expression in selector.match_expressions.iter().flatten() {
+        let key = &expression.key;
+        let values = || expression.values.as_deref().unwrap_or_default().join(",");
+        terms.push(match expression.operator.as_str() {
+            "In" => format!("{key} in ({})", values()),
+            "NotIn" => format!("{key} notin ({})", values()),
+            "Exists" => key.clone(),
+            "DoesNotExist" => format!("!{key}"),
+            operator => {
+                return Err(CoreError::PortForwarding(format!(
+                    "Unsupported label selector operator {operator} for key {key}"
+                )))
+            }
+        });
+    }
+
+    Ok((!terms.is_empty()).then(|| terms.join(",")))
+}
+
+/// Resolve a workload forward to a ready pod and container port
+///
+/// Pod targets match pod names like `match_pods`: the pod named `name` exactly, or else the
+/// pods of the one workload whose pod names start with it. A `StatefulSet` ordinal resolves
+/// to that pod, which must be ready. Pods being deleted are never chosen.
+///
+/// # Errors
+/// Returns an error if the workload does not exist, a pod name prefix matches several
+/// workloads, no matching pod is ready, or the port is not declared by the chosen pod
+pub async fn resolve_workload_target(
+    client: &Client,
+    namespace: &str,
+    kind: ResourceKind,
+    name: &str,
+    selection: PodSelection,
+    ordinal: Option<u32>,
+    port: &PortValue,
+) -> Result<ResolvedWorkload, CoreError> {
+    let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+    let list_error = |e: kube::Error| {
+        CoreError::PortForwarding(format!(
+            "Failed to list pods for {kind} {name} in namespace {namespace}: {e}"
+        ))
+    };
+
+    let selector = workload_selector(client, namespace, kind, name, ordinal).await?;
+    let listed = pods
+        .list(
+            &selector
+                .as_deref()
+                .map_or_else(ListParams::default, |selector| {
+                    ListParams::default().labels(selector)
+                }),
+        )
+        .await
+        .map_err(list_error)?
+        .items;
+    let candidates: Vec<Pod> = if selector.is_some() {
+        listed
+    } else {
+        match matching_pods(name, &listed) {
+            Ok(matched) => matched.into_iter().cloned().collect(),
+            Err(PodMatch::Ambiguous(candidates)) => {
+                return Err(CoreError::AmbiguousPod(name.to_string(), candidates))
+            }
+            Err(_) => Vec::new(),
+        }
+    };
+
+    let pod_name = select_pod(&candidates, selection).ok_or_else(|| match ordinal {
+        Some(ordinal) => CoreError::PortForwarding(format!(
+            "Pod {} of statefulset {name} in namespace {namespace} is not ready",
+            stateful_set_pod_name(name, ordinal)
+        )),
+        None => CoreError::PortForwarding(format!(
+            "No ready pods for {kind} {name} in namespace {namespace}"
+        )),
+    })?;
+
+    let pod = candidates
+        .iter()
+        .find(|pod| pod.metadata.name.as_deref() == Some(pod_name.as_str()))
+        .ok_or_else(|| CoreError::PortForwarding(format!("Pod {pod_name} disappeared")))?;
+
+    Ok(ResolvedWorkload {
+        remote_port: container_port(pod, port)?,
+        ready_pods: ready_pod_names(&candidates),
+        pod: pod_name,
+    })
+}
+
+/// Map a numeric port or container port name to a port on the given pod
+///
+/// Numeric ports are used as given, since containers need not declare the ports they
+/// listen on.
+///
+/// # Errors
+/// Returns an error if a named port is not declared by any container of the pod
+pub fn container_port(pod: &Pod, port: &PortValue) -> Result<u16, CoreError> {
+    let name = match port {
+        PortValue::Numeric(number) => return Ok(*number),
+        PortValue::Named(name) => name,
+    };
+
+    let number = pod
+        .spec
+        .iter()
+        .flat_map(|spec| spec.containers.iter())
+        .flat_map(|container| container.ports.iter().flatten())
+        .find(|p| p.name.as_deref() == Some(name.as_str()))
+        .map(|p| p.container_port)
+        .ok_or_else(|| {
+            CoreError::PortForwarding(format!(
+                "Port {name} is not declared by pod {}",
+                pod.metadata.name.as_deref().unwrap_or_default()
+            ))
+        })?;
+
+    u16::try_from(number)
+        .ok()
+        .filter(|p| *p > 0)
+        .ok_or_else(|| CoreError::PortForwarding(format!("Invalid container port {number}")))
+}
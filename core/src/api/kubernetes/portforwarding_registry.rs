@@ -0,0 +1,124 @@
+// Port forwarding manager registry
+//
+// Keeps one PortForwardingManager per kube context so forwards against several clusters
+// can run side by side, while every caller using the same context shares its state.
+
+use super::portforwarding::{PortForwardingManager, PortForwardingState};
+use crate::api::kubernetes::client::KubernetesClient;
+use crate::errors::CoreError;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+use tokio::sync::Mutex;
+
+type ManagerMap = HashMap<String, Arc<PortForwardingManager>>;
+
+static PORT_FORWARDING_MANAGERS: OnceLock<RwLock<ManagerMap>> = OnceLock::new();
+
+/// Serializes manager creation so concurrent callers don't build two for one context
+static INIT_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn managers() -> &'static RwLock<ManagerMap> {
+    PORT_FORWARDING_MANAGERS.get_or_init(RwLock::default)
+}
+
+/// Register a port forwarding manager for the client's context
+///
+/// # Errors
+/// Returns an error if a manager is already registered for the context
+pub fn initialize(client: &KubernetesClient) -> Result<(), CoreError> {
+    let context = client.current_context().to_string();
+    let mut registry = managers().write().unwrap_or_else(PoisonError::into_inner);
+    if registry.contains_key(&context) {
+        return Err(CoreError::PortForwarding(format!(
+            "Port forwarding manager already initialized for context {context}"
+        )));
+    }
+
+    let manager = PortForwardingManager::new(client).with_state_persistence(true);
+    registry.insert(context, Arc::new(manager));
+    Ok(())
+}
+
+/// Get the port forwarding manager for a context
+///
+/// Returns `None` if no manager has been initialized for the context yet
+#[must_use]
+pub fn get(context_name: &str) -> Option<Arc<PortForwardingManager>> {
+    managers()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(context_name)
+        .cloned()
+}
+
+/// Get the port forwarding manager for a context, initializing it if necessary
+///
+/// # Errors
+/// Returns an error if:
+/// - The context doesn't exist or the client cannot be created
+/// - Manager initialization fails
+pub async fn get_or_init(context_name: &str) -> Result<Arc<PortForwardingManager>, CoreError> {
+    // Try to get existing instance first
+    if let Some(manager) = get(context_name) {
+        return Ok(manager);
+    }
+
+    let _guard = INIT_LOCK.lock().await;
+    if let Some(manager) = get(context_name) {
+        return Ok(manager);
+    }
+
+    // Initialize if not already initialized
+    let client = KubernetesClient::new_with_context(context_name).await?;
+    initialize(&client)?;
+
+    let manager = get(context_name).ok_or_else(|| {
+        CoreError::PortForwarding("Failed to retrieve manager after initialization".to_string())
+    })?;
+    manager.start_health_monitoring();
+
+    Ok(manager)
+}
+
+/// Check if a manager has been initialized for a context
+#[must_use]
+pub fn is_initialized(context_name: &str) -> bool {
+    managers()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .contains_key(context_name)
+}
+
+/// Contexts with an initialized manager, sorted by name
+#[must_use]
+pub fn contexts() -> Vec<String> {
+    let mut contexts: Vec<String> = managers()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .keys()
+        .cloned()
+        .collect();
+    contexts.sort();
+    contexts
+}
+
+/// Every initialized manager, ordered by context
+#[must_use]
+pub fn all_managers() -> Vec<Arc<PortForwardingManager>> {
+    let registry = managers().read().unwrap_or_else(PoisonError::into_inner);
+    let mut all: Vec<Arc<PortForwardingManager>> = registry.values().cloned().collect();
+    drop(registry);
+    all.sort_by(|a, b| a.context().cmp(b.context()));
+    all
+}
+
+/// Forwards across every context, ordered by context and then forward id
+pub async fn list_all_forwards() -> Vec<PortForwardingState> {
+    let mut forwards = Vec::new();
+    for manager in all_managers() {
+        let mut context_forwards = manager.list_forwards().await;
+        context_forwards.sort_by(|a, b| a.id.cmp(&b.id));
+        forwards.extend(context_forwards);
+    }
+    forwards
+}
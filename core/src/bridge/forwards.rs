@@ -0,0 +1,105 @@
+// Port forward bridge
+//
+// This module converts between the manager's port forward configs and the persisted
+// forward records.
+
+use crate::api::kubernetes::portforwarding::{BindAddress, ForwardTarget, PortForwardingConfig};
+use roro_persistence::{DesiredState, ForwardRecord, ForwardRecordTarget};
+use std::time::Duration;
+
+/// Build the persisted record for a forward
+#[must_use]
+pub fn forward_to_record(
+    forward_id: &str,
+    context: &str,
+    config: &PortForwardingConfig,
+    selector: Option<String>,
+    desired_state: DesiredState,
+) -> ForwardRecord {
+    ForwardRecord {
+        id: forward_id.to_string(),
+        context: context.to_string(),
+        instance_id: config.instance_id.clone(),
+        namespace: config.namespace.clone(),
+        pod: config.pod.clone(),
+        remote_port: config.remote_port,
+        local_port: config.local_port,
+        target: match &config.target {
+            ForwardTarget::Pod => ForwardRecordTarget::Pod,
+            ForwardTarget::Service { name, port } => ForwardRecordTarget::Service {
+                name: name.clone(),
+                port: port.clone(),
+            },
+            ForwardTarget::Workload {
+                kind,
+                name,
+                selection,
+                ordinal,
+            } => ForwardRecordTarget::Workload {
+                resource: *kind,
+                name: name.clone(),
+                selection: *selection,
+                ordinal: *ordinal,
+            },
+        },
+        selector,
+        health_check: config.health_check.clone(),
+        bind_address: (config.bind_address != BindAddress::default())
+            .then(|| config.bind_address.to_string()),
+        group_id: config.group_id.clone(),
+        tls: config.tls,
+        lazy: config.lazy,
+        idle_timeout: config.idle_timeout.map(|timeout| timeout.as_secs().max(1)),
+        transport: config.transport,
+        desired_state,
+    }
+}
+
+/// Rebuild a forward config from its persisted record
+///
+/// The pod is the one last used by the forward and must be re-resolved before starting.
+#[must_use]
+pub fn record_to_forward(record: &ForwardRecord) -> PortForwardingConfig {
+    PortForwardingConfig {
+        namespace: record.namespace.clone(),
+        pod: record.pod.clone(),
+        remote_port: record.remote_port,
+        local_port: record.local_port,
+        instance_id: record.instance_id.clone(),
+        target: match &record.target {
+            ForwardRecordTarget::Pod => ForwardTarget::Pod,
+            ForwardRecordTarget::Service { name, port } => ForwardTarget::Service {
+                name: name.clone(),
+                port: port.clone(),
+            },
+            ForwardRecordTarget::Workload {
+                resource,
+                name,
+                selection,
+                ordinal,
+            } => ForwardTarget::Workload {
+                kind: *resource,
+                name: name.clone(),
+                selection: *selection,
+                ordinal: *ordinal,
+            },
+        },
+        health_check: record.health_check.clone(),
+        // An unreadable address falls back to localhost rather than exposing the forward
+        bind_address: record
+            .bind_address
+            .as_deref()
+            .and_then(|address| address.parse().ok())
+            .unwrap_or_default(),
+        group_id: record.group_id.clone(),
+        tls: record.tls,
+        // Capture is a debugging aid for one session and isn't restored
+        capture: None,
+        ready_timeout: None,
+        // So is fault injection, which would otherwise break a restored forward unexpectedly
+        shaping: None,
+        lazy: record.lazy,
+        idle_timeout: record.idle_timeout.map(Duration::from_secs),
+        transport: record.transport,
+    }
+}
@@ -2,11 +2,10 @@
 // This module transforms data between persistence and domain representations.
 // It allows persistence and domain layers to evolve independently.
 
+pub mod forwards;
+
 // Placeholder submodules - will be implemented when domain/persistence models are ready
 // pub mod execution;
 // pub mod data;
 
-// Bridge functions will be implemented later when models are defined
-// Example:
-// pub fn persistence_to_domain(definition: &PersistenceDefinition) -> Result<DomainDefinition, BridgeError>
-// pub fn domain_to_persistence(result: &DomainResult) -> PersistenceResult
+pub use forwards::{forward_to_record, record_to_forward};
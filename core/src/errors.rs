@@ -1,5 +1,6 @@
 use thiserror::Error;
 
+use crate::api::kubernetes::portforwarding::PortHolder;
 use roro_domain::DomainError;
 use roro_persistence::PersistenceError;
 
@@ -46,7 +47,19 @@ pub enum CoreError {
     #[error("Port conflict: port {0} is already in use")]
     PortConflict(u16),
 
+    /// Port conflict with the local process listening on the port
+    #[error("Port conflict: port {0} is already in use by {1}")]
+    PortHeld(u16, PortHolder),
+
+    /// The cluster denies port-forwarding to a forward's pods
+    #[error("Port forwarding forbidden: {0}")]
+    PortForwardForbidden(String),
+
     /// Port forwarding not found error
     #[error("Port forwarding not found: {0}")]
     PortForwardingNotFound(String),
+
+    /// Pod name matching pods of several workloads
+    #[error("Pod name {0} is ambiguous, matching pods: {}", .1.join(", "))]
+    AmbiguousPod(String, Vec<String>),
 }
@@ -10,3 +10,4 @@ pub mod validation;
 // Public API exports
 pub use api::{get_config_path_string, load_workstation_config, sync_repository};
 pub use errors::CoreError;
+pub use roro_persistence::{DesiredState, ForwardRecord, ForwardRecordTarget};
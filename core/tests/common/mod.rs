@@ -0,0 +1,31 @@
+// Shared test fixtures
+//
+// Builders for the Kubernetes objects several port forwarding test files need. Each test
+// file uses only some of them.
+
+#![allow(dead_code)]
+
+use k8s_openapi::api::core::v1::{Pod, PodCondition, PodStatus};
+
+/// A pod named `name` in `phase` whose Ready condition is true or false
+pub fn pod(name: &str, phase: &str, ready: bool) -> Pod {
+    let mut pod = Pod {
+        status: Some(PodStatus {
+            phase: Some(phase.to_string()),
+            conditions: Some(vec![PodCondition {
+                type_: "Ready".to_string(),
+                status: if ready { "True" } else { "False" }.to_string(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    pod.metadata.name = Some(name.to_string());
+    pod
+}
+
+/// A running pod named `name` that is ready or not
+pub fn running_pod(name: &str, ready: bool) -> Pod {
+    pod(name, "Running", ready)
+}
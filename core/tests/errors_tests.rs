@@ -2,6 +2,7 @@
 //
 // Tests for CoreError enum variants, error transformations, and display formatting.
 
+use roro_core::api::kubernetes::portforwarding::PortHolder;
 use roro_core::CoreError;
 use roro_domain::DomainError;
 use roro_persistence::PersistenceError;
@@ -82,3 +83,28 @@ fn test_core_error_all_variants() {
         assert!(!msg.is_empty());
     }
 }
+
+#[test]
+fn test_core_error_ambiguous_pod_lists_candidates() {
+    let error = CoreError::AmbiguousPod(
+        "ap".to_string(),
+        vec![
+            "api-7d9f-aaaaa".to_string(),
+            "api-gateway-5c6b-bbbbb".to_string(),
+        ],
+    );
+    let msg = format!("{error}");
+    assert!(msg.contains("Pod name ap is ambiguous"));
+    assert!(msg.contains("api-7d9f-aaaaa, api-gateway-5c6b-bbbbb"));
+}
+
+#[test]
+fn test_core_error_port_held_names_the_holder() {
+    let holder = PortHolder::new(4242, "kubectl port-forward svc/api 8080:80".to_string());
+    let msg = format!("{}", CoreError::PortHeld(8080, holder));
+    assert_eq!(
+        msg,
+        "Port conflict: port 8080 is already in use by a kubectl port-forward \
+         (PID 4242: kubectl port-forward svc/api 8080:80)"
+    );
+}
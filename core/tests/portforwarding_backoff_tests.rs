@@ -0,0 +1,67 @@
+// Reconnection backoff tests
+//
+// Tests for exponential delays, jitter bounds and attempt limits.
+
+use roro_core::api::kubernetes::portforwarding::BackoffPolicy;
+use std::time::Duration;
+
+fn policy() -> BackoffPolicy {
+    BackoffPolicy::default()
+        .with_initial(Duration::from_secs(1))
+        .with_max(Duration::from_secs(30))
+        .with_multiplier(2.0)
+        .with_jitter(0.0)
+}
+
+#[test]
+fn test_delay_grows_exponentially() {
+    let policy = policy();
+
+    assert_eq!(policy.delay(1, 0.5), Duration::from_secs(1));
+    assert_eq!(policy.delay(2, 0.5), Duration::from_secs(2));
+    assert_eq!(policy.delay(3, 0.5), Duration::from_secs(4));
+    assert_eq!(policy.delay(5, 0.5), Duration::from_secs(16));
+}
+
+#[test]
+fn test_delay_is_capped_at_max() {
+    let policy = policy();
+
+    assert_eq!(policy.delay(6, 0.5), Duration::from_secs(30));
+    assert_eq!(policy.delay(u32::MAX, 0.5), Duration::from_secs(30));
+}
+
+#[test]
+fn test_jitter_stays_within_bounds() {
+    let policy = policy().with_jitter(0.5);
+
+    assert_eq!(policy.delay(3, 0.0), Duration::from_secs(2));
+    assert_eq!(policy.delay(3, 0.5), Duration::from_secs(4));
+    assert_eq!(policy.delay(3, 1.0), Duration::from_secs(6));
+    assert_eq!(policy.delay(6, 1.0), Duration::from_secs(30));
+
+    for attempt in 1..10 {
+        let delay = policy.next_delay(attempt);
+        assert!(delay <= Duration::from_secs(30));
+        assert!(delay >= policy.delay(attempt, 0.0));
+    }
+}
+
+#[test]
+fn test_attempt_limits() {
+    let limited = policy().with_max_attempts(Some(3));
+    assert!(limited.allows(1));
+    assert!(limited.allows(3));
+    assert!(!limited.allows(4));
+
+    let unlimited = policy().with_max_attempts(None);
+    assert!(unlimited.allows(u32::MAX));
+}
+
+#[test]
+fn test_multiplier_below_one_keeps_delay_constant() {
+    let policy = policy().with_multiplier(0.5);
+
+    assert_eq!(policy.delay(1, 0.5), Duration::from_secs(1));
+    assert_eq!(policy.delay(4, 0.5), Duration::from_secs(1));
+}
@@ -0,0 +1,67 @@
+// Port forward bind address tests
+//
+// Tests for parsing bind addresses and checking port availability against them.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{check_bind_available, BindAddress};
+use roro_core::errors::CoreError;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+#[test]
+fn test_bind_address_parse() {
+    assert_eq!(
+        "localhost".parse::<BindAddress>().ok(),
+        Some(BindAddress::Localhost)
+    );
+    assert_eq!(
+        "::1".parse::<BindAddress>().ok(),
+        Some(BindAddress::Ip(IpAddr::V6(Ipv6Addr::LOCALHOST)))
+    );
+    assert_eq!(
+        "[::1]".parse::<BindAddress>().ok(),
+        Some(BindAddress::Ip(IpAddr::V6(Ipv6Addr::LOCALHOST)))
+    );
+    assert!(matches!(
+        "my-laptop".parse::<BindAddress>(),
+        Err(CoreError::Validation(_))
+    ));
+}
+
+#[test]
+fn test_bind_address_exposure() {
+    assert!(!BindAddress::Localhost.is_exposed());
+    assert!(!BindAddress::Ip(IpAddr::V6(Ipv6Addr::LOCALHOST)).is_exposed());
+    assert!(BindAddress::Ip(IpAddr::V4(Ipv4Addr::UNSPECIFIED)).is_exposed());
+    assert!(BindAddress::Ip(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20))).is_exposed());
+}
+
+#[test]
+fn test_localhost_binds_both_loopbacks() {
+    assert_eq!(
+        BindAddress::Localhost.addresses(),
+        vec![
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(Ipv6Addr::LOCALHOST)
+        ]
+    );
+    assert_eq!(BindAddress::Localhost.to_string(), "localhost");
+}
+
+#[test]
+fn test_check_bind_available_detects_conflict() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    assert!(matches!(
+        check_bind_available(BindAddress::Localhost, port),
+        Err(CoreError::PortConflict(p)) if p == port
+    ));
+    assert!(matches!(
+        check_bind_available(BindAddress::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)), port),
+        Err(CoreError::PortConflict(_))
+    ));
+
+    drop(listener);
+    assert!(check_bind_available(BindAddress::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)), port).is_ok());
+}
@@ -0,0 +1,254 @@
+// HTTP capture tests
+//
+// Tests for HTTP/1.1 stream parsing, the capture ring buffer and HAR export.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{
+    copy_bidirectional_observed, har_log, rfc3339, CaptureConfig, Direction, ForwardMetrics,
+    HttpCapture, HttpParser, MessageKind, ParseEvent,
+};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn completed_bodies(events: &[ParseEvent]) -> Vec<(Vec<u8>, u64)> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            ParseEvent::Complete { body, size } => Some((body.clone(), *size)),
+            ParseEvent::Head(_) => None,
+        })
+        .collect()
+}
+
+/// Feed both directions of a connection through a tap on `capture`
+fn replay(capture: &Arc<HttpCapture>, request: &[u8], response: &[u8]) {
+    let mut tap = capture.tap().expect("capture enabled");
+    tap.observe(Direction::Sent, request);
+    tap.observe(Direction::Received, response);
+    tap.finish();
+}
+
+#[test]
+fn test_parser_reads_content_length_body_across_chunks() {
+    let mut parser = HttpParser::new(MessageKind::Request, 1024);
+
+    let mut events = parser.feed(b"POST /items HTTP/1.1\r\nHost: api\r\nContent-Le");
+    assert!(events.is_empty());
+    events.extend(parser.feed(b"ngth: 5\r\n\r\nhel"));
+    events.extend(parser.feed(b"lo"));
+
+    let ParseEvent::Head(head) = &events[0] else {
+        panic!("expected a head first");
+    };
+    assert_eq!(head.request_line(), Some(("POST", "/items", "HTTP/1.1")));
+    assert_eq!(head.header("HOST"), Some("api"));
+    assert_eq!(completed_bodies(&events), vec![(b"hello".to_vec(), 5)]);
+}
+
+#[test]
+fn test_parser_reads_chunked_body_and_truncates_to_limit() {
+    let mut parser = HttpParser::new(MessageKind::Response, 4);
+    parser.expect_response(false);
+
+    let events = parser.feed(
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6;x=y\r\n world\r\n0\r\n\r\n",
+    );
+
+    assert_eq!(completed_bodies(&events), vec![(b"hell".to_vec(), 11)]);
+}
+
+#[test]
+fn test_parser_handles_pipelined_requests() {
+    let mut parser = HttpParser::new(MessageKind::Request, 0);
+
+    let events = parser.feed(b"GET /a HTTP/1.1\r\n\r\nGET /b HTTP/1.1\r\n\r\n");
+
+    let heads: Vec<_> = events
+        .iter()
+        .filter(|event| matches!(event, ParseEvent::Head(_)))
+        .collect();
+    assert_eq!(heads.len(), 2);
+    assert_eq!(completed_bodies(&events).len(), 2);
+}
+
+#[test]
+fn test_parser_skips_body_of_head_and_no_content_responses() {
+    let mut parser = HttpParser::new(MessageKind::Response, 1024);
+    parser.expect_response(true);
+    parser.expect_response(false);
+
+    let events = parser
+        .feed(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\nHTTP/1.1 204 No Content\r\n\r\n");
+
+    assert_eq!(completed_bodies(&events), vec![(vec![], 0), (vec![], 0)]);
+    assert!(!parser.is_failed());
+}
+
+#[test]
+fn test_parser_reads_response_body_until_close() {
+    let mut parser = HttpParser::new(MessageKind::Response, 1024);
+
+    assert!(completed_bodies(&parser.feed(b"HTTP/1.0 200 OK\r\n\r\npartial")).is_empty());
+    let finished = parser.finish().expect("body delimited by close");
+
+    assert_eq!(
+        finished,
+        ParseEvent::Complete {
+            body: b"partial".to_vec(),
+            size: 7
+        }
+    );
+}
+
+#[test]
+fn test_parser_gives_up_on_non_http_traffic() {
+    let mut parser = HttpParser::new(MessageKind::Request, 0);
+
+    let events = parser.feed(b"\x00\x00\x00\x08\x04\xd2\x16\x2f\r\n\r\n");
+
+    assert!(events.is_empty());
+    assert!(parser.is_failed());
+    assert!(parser.feed(b"GET / HTTP/1.1\r\n\r\n").is_empty());
+}
+
+#[test]
+fn test_tap_pairs_requests_with_responses() {
+    let capture = Arc::new(HttpCapture::new(Some(CaptureConfig::default())));
+
+    replay(
+        &capture,
+        b"GET /health HTTP/1.1\r\nHost: localhost:18080\r\n\r\nGET /missing HTTP/1.1\r\nHost: localhost:18080\r\n\r\n",
+        b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nokHTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+    );
+
+    let exchanges = capture.exchanges();
+    assert_eq!(exchanges.len(), 2);
+    assert_eq!(exchanges[0].id, 1);
+    assert_eq!(exchanges[0].method, "GET");
+    assert_eq!(exchanges[0].url(), "http://localhost:18080/health");
+    assert_eq!(exchanges[0].status, 200);
+    assert_eq!(exchanges[0].response_body_size, 2);
+    assert_eq!(exchanges[0].response_body, None);
+    assert_eq!(exchanges[1].target, "/missing");
+    assert_eq!(exchanges[1].status, 404);
+    assert_eq!(exchanges[1].status_text, "Not Found");
+}
+
+#[test]
+fn test_capture_keeps_bodies_when_asked() {
+    let capture = Arc::new(HttpCapture::new(Some(
+        CaptureConfig::default().with_bodies(true),
+    )));
+
+    replay(
+        &capture,
+        b"POST /echo HTTP/1.1\r\nContent-Length: 4\r\n\r\nping",
+        b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\npong",
+    );
+
+    let exchange = &capture.exchanges()[0];
+    assert_eq!(exchange.request_body.as_deref(), Some(&b"ping"[..]));
+    assert_eq!(exchange.response_body.as_deref(), Some(&b"pong"[..]));
+}
+
+#[test]
+fn test_capture_ring_buffer_drops_oldest() {
+    let capture = Arc::new(HttpCapture::new(Some(
+        CaptureConfig::default().with_max_entries(2),
+    )));
+
+    for path in ["/1", "/2", "/3"] {
+        let request = format!("GET {path} HTTP/1.1\r\n\r\n");
+        replay(
+            &capture,
+            request.as_bytes(),
+            b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n",
+        );
+    }
+
+    let targets: Vec<_> = capture.exchanges().into_iter().map(|e| e.target).collect();
+    assert_eq!(targets, vec!["/2", "/3"]);
+    assert_eq!(capture.exchanges()[1].id, 3);
+}
+
+#[test]
+fn test_capture_off_records_nothing() {
+    let capture = Arc::new(HttpCapture::default());
+    assert!(capture.tap().is_none());
+
+    capture.set_config(Some(CaptureConfig::default()));
+    let mut tap = capture.tap().unwrap();
+    tap.observe(Direction::Sent, b"GET / HTTP/1.1\r\n\r\n");
+    capture.set_config(None);
+    tap.observe(
+        Direction::Received,
+        b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n",
+    );
+
+    assert!(capture.exchanges().is_empty());
+}
+
+#[tokio::test]
+async fn test_observed_copy_shows_every_chunk() {
+    let metrics = ForwardMetrics::default();
+    let capture = Arc::new(HttpCapture::new(Some(CaptureConfig::default())));
+    let tap = Mutex::new(capture.tap().unwrap());
+    let (mut client, local) = tokio::io::duplex(64);
+    let (remote, mut pod) = tokio::io::duplex(64);
+
+    let pod_side = async {
+        let mut request = vec![0u8; 18];
+        pod.read_exact(&mut request).await.unwrap();
+        pod.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .await
+            .unwrap();
+        pod.shutdown().await.unwrap();
+    };
+    let client_side = async {
+        client.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        client.shutdown().await.unwrap();
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+    };
+    let observe = |direction, data: &[u8]| tap.lock().unwrap().observe(direction, data);
+    let copy = copy_bidirectional_observed(local, remote, &metrics, &observe);
+
+    let (copied, (), ()) = tokio::join!(copy, pod_side, client_side);
+
+    assert_eq!(copied.unwrap(), (18, 38));
+    assert_eq!(capture.exchanges()[0].status, 200);
+}
+
+#[test]
+fn test_har_log_describes_exchanges() {
+    let capture = Arc::new(HttpCapture::new(Some(
+        CaptureConfig::default().with_bodies(true),
+    )));
+    replay(
+        &capture,
+        b"POST /search?q=pods&limit=5 HTTP/1.1\r\nHost: api\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}",
+        b"HTTP/1.1 201 Created\r\nContent-Type: text/plain\r\nContent-Length: 2\r\n\r\nok",
+    );
+
+    let har = har_log(&capture.exchanges());
+
+    assert_eq!(har["log"]["version"], "1.2");
+    let entry = &har["log"]["entries"][0];
+    assert_eq!(entry["request"]["method"], "POST");
+    assert_eq!(entry["request"]["url"], "http://api/search?q=pods&limit=5");
+    assert_eq!(entry["request"]["queryString"][1]["name"], "limit");
+    assert_eq!(entry["request"]["postData"]["text"], "{}");
+    assert_eq!(entry["response"]["status"], 201);
+    assert_eq!(entry["response"]["content"]["mimeType"], "text/plain");
+    assert_eq!(entry["response"]["content"]["text"], "ok");
+}
+
+#[test]
+fn test_rfc3339_formats_utc_milliseconds() {
+    let time = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+
+    assert_eq!(rfc3339(time), "2024-02-29T12:34:56.789Z");
+    assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+}
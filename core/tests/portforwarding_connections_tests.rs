@@ -0,0 +1,70 @@
+// Connection draining tests
+//
+// Tests for letting open connections finish when a forward stops.
+
+use roro_core::api::kubernetes::portforwarding::{ConnectionSet, StopSummary};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+#[tokio::test]
+async fn test_drain_waits_for_connections_to_finish() {
+    let connections = ConnectionSet::default();
+    let (finish_tx, finish_rx) = oneshot::channel::<()>();
+    connections.spawn(async move {
+        let _ = finish_rx.await;
+    });
+    assert_eq!(connections.len(), 1);
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let _ = finish_tx.send(());
+    });
+    let summary = connections.drain(Duration::from_secs(5)).await;
+
+    assert_eq!(
+        summary,
+        StopSummary {
+            drained: 1,
+            force_closed: 0
+        }
+    );
+    assert!(connections.is_empty());
+}
+
+#[tokio::test]
+async fn test_drain_force_closes_after_timeout() {
+    let connections = ConnectionSet::default();
+    connections.spawn(std::future::pending());
+    connections.spawn(tokio::time::sleep(Duration::from_millis(10)));
+
+    let summary = connections.drain(Duration::from_millis(200)).await;
+
+    assert_eq!(summary.force_closed, 1);
+    assert_eq!(summary.drained, 1);
+}
+
+#[tokio::test]
+async fn test_drain_now_closes_everything() {
+    let connections = ConnectionSet::default();
+    connections.spawn(std::future::pending());
+    connections.spawn(std::future::pending());
+
+    let summary = connections.drain(Duration::ZERO).await;
+
+    assert_eq!(summary.force_closed, 2);
+    assert!(connections.is_empty());
+}
+
+#[test]
+fn test_summaries_add_up() {
+    let total = StopSummary {
+        drained: 2,
+        force_closed: 1,
+    } + StopSummary {
+        drained: 1,
+        force_closed: 3,
+    };
+
+    assert_eq!(total.drained, 3);
+    assert_eq!(total.force_closed, 4);
+}
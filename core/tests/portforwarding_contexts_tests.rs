@@ -0,0 +1,31 @@
+// Per-context manager registry tests
+//
+// Tests for looking up port forwarding managers by kube context.
+
+use roro_core::api::kubernetes::{contexts, get, get_or_init, is_initialized, list_all_forwards};
+use roro_core::errors::CoreError;
+
+#[test]
+fn test_unknown_context_has_no_manager() {
+    assert!(get("no-such-context-registry").is_none());
+    assert!(!is_initialized("no-such-context-registry"));
+    assert!(!contexts().contains(&"no-such-context-registry".to_string()));
+}
+
+#[tokio::test]
+async fn test_get_or_init_unknown_context_fails_without_registering() {
+    let result = get_or_init("no-such-context-registry").await;
+
+    assert!(matches!(
+        result,
+        Err(CoreError::ContextNotFound(_) | CoreError::Kubeconfig(_) | CoreError::Kubernetes(_))
+    ));
+    assert!(!is_initialized("no-such-context-registry"));
+}
+
+#[tokio::test]
+async fn test_list_all_forwards_without_managers_is_empty() {
+    if contexts().is_empty() {
+        assert!(list_all_forwards().await.is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+// Local DNS responder tests
+//
+// Tests for answering queries about service host names and running the process-wide
+// responder.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{
+    dns_response, dns_server_address, start_dns_server, stop_dns_server, ServiceHost,
+};
+use std::net::Ipv4Addr;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+fn hosts() -> Vec<ServiceHost> {
+    vec![ServiceHost {
+        name: "postgres.db.svc.cluster.local".to_string(),
+        ip: Ipv4Addr::new(127, 1, 0, 1),
+    }]
+}
+
+/// A standard query with recursion desired for `name` and `query_type`
+fn query(name: &str, query_type: u16) -> Vec<u8> {
+    let mut packet = vec![0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in name.split('.') {
+        packet.push(u8::try_from(label.len()).unwrap());
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&query_type.to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    packet
+}
+
+fn rcode(response: &[u8]) -> u8 {
+    response[3] & 0x0f
+}
+
+fn answer_count(response: &[u8]) -> u16 {
+    u16::from_be_bytes([response[6], response[7]])
+}
+
+#[test]
+fn test_known_name_gets_its_address() {
+    let request = query("postgres.db.svc.cluster.local", 1);
+    let response = dns_response(&request, &hosts()).unwrap();
+
+    // Same id, a response, recursion desired echoed
+    assert_eq!(&response[..2], &[0x12, 0x34]);
+    assert_eq!(response[2] & 0x80, 0x80);
+    assert_eq!(response[2] & 0x01, 0x01);
+    assert_eq!(rcode(&response), 0);
+    assert_eq!(answer_count(&response), 1);
+    assert_eq!(&response[response.len() - 4..], &[127, 1, 0, 1]);
+}
+
+#[test]
+fn test_names_match_case_insensitively() {
+    let response = dns_response(&query("Postgres.DB.svc.cluster.local", 1), &hosts()).unwrap();
+
+    assert_eq!(answer_count(&response), 1);
+}
+
+#[test]
+fn test_unknown_name_is_nxdomain() {
+    let request = query("redis.db.svc.cluster.local", 1);
+    let response = dns_response(&request, &hosts()).unwrap();
+
+    assert_eq!(rcode(&response), 3);
+    assert_eq!(answer_count(&response), 0);
+    // The question is echoed back
+    assert_eq!(&response[12..], &request[12..]);
+}
+
+#[test]
+fn test_known_name_without_ipv6_gets_an_empty_answer() {
+    let response = dns_response(&query("postgres.db.svc.cluster.local", 28), &hosts()).unwrap();
+
+    assert_eq!(rcode(&response), 0);
+    assert_eq!(answer_count(&response), 0);
+}
+
+#[test]
+fn test_malformed_queries_are_rejected() {
+    assert!(dns_response(&[0x12, 0x34], &hosts()).is_none());
+
+    let mut truncated = query("postgres.db.svc.cluster.local", 1);
+    truncated.truncate(20);
+    assert_eq!(rcode(&dns_response(&truncated, &hosts()).unwrap()), 1);
+}
+
+#[test]
+fn test_responses_are_not_answered() {
+    let mut response = query("postgres.db.svc.cluster.local", 1);
+    response[2] |= 0x80;
+
+    assert!(dns_response(&response, &hosts()).is_none());
+}
+
+#[tokio::test]
+async fn test_one_responder_runs_per_process() {
+    let address = start_dns_server("127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(dns_server_address(), Some(address));
+    assert!(start_dns_server("127.0.0.1:0".parse().unwrap())
+        .await
+        .is_err());
+
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    client
+        .send_to(&query("unknown.db.svc.cluster.local", 1), address)
+        .await
+        .unwrap();
+    let mut response = [0u8; 512];
+    let (len, _) = tokio::time::timeout(Duration::from_secs(5), client.recv_from(&mut response))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(rcode(&response[..len]), 3);
+
+    assert!(stop_dns_server());
+    assert_eq!(dns_server_address(), None);
+    assert!(!stop_dns_server());
+}
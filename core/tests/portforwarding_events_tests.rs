@@ -0,0 +1,68 @@
+// Port forward event tests
+//
+// Tests for broadcasting status changes to event subscribers.
+
+use roro_core::api::kubernetes::portforwarding::{
+    set_status, PortForwardingConfig, PortForwardingEvent, PortForwardingState,
+    PortForwardingStatus,
+};
+use tokio::sync::broadcast;
+
+fn state(status: PortForwardingStatus) -> PortForwardingState {
+    let mut state = PortForwardingState::new(
+        "dev-api-7d9f-aaaaa-8080",
+        "rancher-desktop",
+        PortForwardingConfig::default(),
+    );
+    state.status = status;
+    state
+}
+
+#[test]
+fn test_set_status_broadcasts_change() {
+    let (events, mut receiver) = broadcast::channel(8);
+    let mut state = state(PortForwardingStatus::Connecting);
+
+    set_status(&mut state, PortForwardingStatus::Active, &events);
+
+    assert_eq!(state.status, PortForwardingStatus::Active);
+    assert_eq!(
+        receiver.try_recv().ok(),
+        Some(PortForwardingEvent::StatusChanged {
+            forward_id: "dev-api-7d9f-aaaaa-8080".to_string(),
+            status: PortForwardingStatus::Active,
+        })
+    );
+}
+
+#[test]
+fn test_set_status_ignores_unchanged_status() {
+    let (events, mut receiver) = broadcast::channel(8);
+    let mut state = state(PortForwardingStatus::Active);
+
+    set_status(&mut state, PortForwardingStatus::Active, &events);
+
+    assert!(receiver.try_recv().is_err());
+}
+
+#[test]
+fn test_set_status_without_subscribers() {
+    let (events, receiver) = broadcast::channel(8);
+    drop(receiver);
+    let mut state = state(PortForwardingStatus::Active);
+
+    set_status(&mut state, PortForwardingStatus::Failed, &events);
+
+    assert_eq!(state.status, PortForwardingStatus::Failed);
+}
+
+#[test]
+fn test_event_forward_id() {
+    let event = PortForwardingEvent::Retargeted {
+        forward_id: "dev-api-8080".to_string(),
+        from_pod: "api-7d9f-aaaaa".to_string(),
+        to_pod: "api-8c1e-xxxxx".to_string(),
+    };
+
+    assert_eq!(event.forward_id(), "dev-api-8080");
+}
@@ -0,0 +1,62 @@
+// Port forward failover tests
+//
+// Tests for choosing a replacement pod when a forward's current pod goes away.
+
+mod common;
+
+use common::running_pod;
+use roro_core::api::kubernetes::portforwarding::{failover_decision, FailoverDecision};
+
+#[test]
+fn test_failover_keeps_ready_current_pod() {
+    let pods = [
+        running_pod("api-7d9f-aaaaa", true),
+        running_pod("api-7d9f-bbbbb", true),
+    ];
+
+    assert_eq!(
+        failover_decision("api-7d9f-bbbbb", &pods),
+        FailoverDecision::Keep
+    );
+}
+
+#[test]
+fn test_failover_retargets_when_current_pod_is_gone() {
+    let pods = [
+        running_pod("api-8c1e-zzzzz", true),
+        running_pod("api-8c1e-yyyyy", false),
+        running_pod("api-8c1e-xxxxx", true),
+    ];
+
+    assert_eq!(
+        failover_decision("api-7d9f-aaaaa", &pods),
+        FailoverDecision::Retarget("api-8c1e-xxxxx".to_string())
+    );
+}
+
+#[test]
+fn test_failover_retargets_when_current_pod_is_unready() {
+    let pods = [
+        running_pod("api-7d9f-aaaaa", false),
+        running_pod("api-8c1e-xxxxx", true),
+    ];
+
+    assert_eq!(
+        failover_decision("api-7d9f-aaaaa", &pods),
+        FailoverDecision::Retarget("api-8c1e-xxxxx".to_string())
+    );
+}
+
+#[test]
+fn test_failover_unavailable_without_ready_pods() {
+    let pods = [running_pod("api-8c1e-xxxxx", false)];
+
+    assert_eq!(
+        failover_decision("api-7d9f-aaaaa", &pods),
+        FailoverDecision::Unavailable
+    );
+    assert_eq!(
+        failover_decision("api-7d9f-aaaaa", &[]),
+        FailoverDecision::Unavailable
+    );
+}
@@ -0,0 +1,73 @@
+// Forward group tests
+//
+// Tests for group port mappings, ids and status summaries.
+
+use roro_core::api::kubernetes::portforwarding::{
+    group_id, group_status, validate_port_mappings, PortForwardingStatus, PortMapping,
+};
+use roro_core::errors::CoreError;
+
+#[test]
+fn test_valid_mappings() {
+    let ports = [PortMapping::new(8080, 18080), PortMapping::new(9090, 19090)];
+    assert!(validate_port_mappings(&ports).is_ok());
+}
+
+#[test]
+fn test_empty_group_rejected() {
+    assert!(matches!(
+        validate_port_mappings(&[]),
+        Err(CoreError::Validation(_))
+    ));
+}
+
+#[test]
+fn test_duplicate_ports_rejected() {
+    let duplicate_local = [PortMapping::new(8080, 18080), PortMapping::new(9090, 18080)];
+    let Err(CoreError::Validation(message)) = validate_port_mappings(&duplicate_local) else {
+        panic!("Expected duplicate local port to be rejected");
+    };
+    assert!(message.contains("Local port 18080"));
+
+    let duplicate_remote = [PortMapping::new(8080, 18080), PortMapping::new(8080, 18081)];
+    let Err(CoreError::Validation(message)) = validate_port_mappings(&duplicate_remote) else {
+        panic!("Expected duplicate remote port to be rejected");
+    };
+    assert!(message.contains("Remote port 8080"));
+}
+
+#[test]
+fn test_zero_port_rejected() {
+    assert!(validate_port_mappings(&[PortMapping::new(0, 18080)]).is_err());
+    assert!(validate_port_mappings(&[PortMapping::new(8080, 0)]).is_err());
+}
+
+#[test]
+fn test_group_id_lists_local_ports() {
+    let ports = [PortMapping::new(8080, 18080), PortMapping::new(9090, 19090)];
+    assert_eq!(
+        group_id("dev", "api-7d9f-aaaaa", &ports),
+        "dev-api-7d9f-aaaaa-group-18080-19090"
+    );
+}
+
+#[test]
+fn test_group_status_reports_least_healthy_member() {
+    let statuses = [
+        PortForwardingStatus::Active,
+        PortForwardingStatus::Reconnecting,
+        PortForwardingStatus::Connecting,
+    ];
+    assert_eq!(
+        group_status(&statuses),
+        Some(PortForwardingStatus::Reconnecting)
+    );
+
+    let failed = [PortForwardingStatus::Failed, PortForwardingStatus::Active];
+    assert_eq!(group_status(&failed), Some(PortForwardingStatus::Failed));
+
+    let healthy = [PortForwardingStatus::Active, PortForwardingStatus::Active];
+    assert_eq!(group_status(&healthy), Some(PortForwardingStatus::Active));
+
+    assert_eq!(group_status(&[]), None);
+}
@@ -0,0 +1,117 @@
+// Port forward health check tests
+//
+// Tests for the HTTP probe run through a forward's tunnel and for recording check results
+// on a forward.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{
+    http_probe, http_probe_request, parse_status_code, record_health, HealthStatus,
+    PortForwardingConfig, PortForwardingState, PortForwardingStatus,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::broadcast;
+
+fn forward(status: PortForwardingStatus) -> PortForwardingState {
+    let mut state = PortForwardingState::new(
+        "dev-api-7d9f-aaaaa-8080",
+        "rancher-desktop",
+        PortForwardingConfig::default(),
+    );
+    state.status = status;
+    state
+}
+
+fn unhealthy() -> HealthStatus {
+    HealthStatus::Unhealthy {
+        reason: "connection refused".to_string(),
+    }
+}
+
+#[test]
+fn test_parse_status_code() {
+    assert_eq!(parse_status_code("HTTP/1.1 200 OK\r\n"), Some(200));
+    assert_eq!(
+        parse_status_code("HTTP/1.0 503 Service Unavailable"),
+        Some(503)
+    );
+    assert_eq!(parse_status_code("SSH-2.0-OpenSSH_9.6\r\n"), None);
+    assert_eq!(parse_status_code(""), None);
+}
+
+#[test]
+fn test_http_probe_request() {
+    let request = http_probe_request("/healthz");
+
+    assert!(request.starts_with("GET /healthz HTTP/1.1\r\n"));
+    assert!(request.ends_with("\r\n\r\n"));
+}
+
+async fn probe_against(response: &'static [u8], expected_status: u16) -> HealthStatus {
+    let (mut client, mut server) = tokio::io::duplex(1024);
+    let server = tokio::spawn(async move {
+        let mut request = [0u8; 256];
+        let read = server.read(&mut request).await.unwrap();
+        assert!(request[..read].starts_with(b"GET /healthz "));
+        server.write_all(response).await.unwrap();
+    });
+
+    let status = http_probe(&mut client, "/healthz", expected_status).await;
+    server.await.unwrap();
+    status
+}
+
+#[tokio::test]
+async fn test_http_probe_healthy_on_expected_status() {
+    let status = probe_against(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n", 200).await;
+
+    assert_eq!(status, HealthStatus::Healthy);
+}
+
+#[tokio::test]
+async fn test_http_probe_unhealthy_on_unexpected_status() {
+    let status = probe_against(b"HTTP/1.1 503 Service Unavailable\r\n\r\n", 200).await;
+
+    let HealthStatus::Unhealthy { reason } = status else {
+        panic!("Expected unhealthy probe");
+    };
+    assert!(reason.contains("returned 503"));
+}
+
+#[tokio::test]
+async fn test_http_probe_unhealthy_on_non_http_response() {
+    let status = probe_against(b"garbage\n", 200).await;
+
+    assert!(matches!(status, HealthStatus::Unhealthy { .. }));
+}
+
+#[test]
+fn test_failed_checks_reconnect_until_a_healthy_check() {
+    let (events, _receiver) = broadcast::channel(8);
+    let mut state = forward(PortForwardingStatus::Active);
+
+    assert!(record_health(&mut state, unhealthy(), &events));
+    assert!(record_health(&mut state, unhealthy(), &events));
+    assert_eq!(state.status, PortForwardingStatus::Reconnecting);
+    assert_eq!(state.health_failures, 2);
+
+    assert!(!record_health(&mut state, HealthStatus::Healthy, &events));
+    assert_eq!(state.status, PortForwardingStatus::Active);
+    assert_eq!(state.health_failures, 0);
+}
+
+#[test]
+fn test_healthy_tunnel_leaves_a_restarting_forward_reconnecting() {
+    let (events, _receiver) = broadcast::channel(8);
+    // The supervisor marked the forward reconnecting after its listener failed
+    let mut state = forward(PortForwardingStatus::Reconnecting);
+
+    assert!(!record_health(&mut state, HealthStatus::Healthy, &events));
+    assert_eq!(state.status, PortForwardingStatus::Reconnecting);
+
+    // A failed check still asks for failover without taking the status over
+    assert!(record_health(&mut state, unhealthy(), &events));
+    assert_eq!(state.health_failures, 0);
+    assert!(!record_health(&mut state, HealthStatus::Healthy, &events));
+    assert_eq!(state.status, PortForwardingStatus::Reconnecting);
+}
@@ -0,0 +1,113 @@
+// Port holder tests
+//
+// Tests for reading listening sockets from /proc, classifying the processes that hold
+// them, and naming the holder in port conflicts.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{
+    identify_port_holder, listening_inodes, parse_cmdline, socket_inode, HolderKind, PortHolder,
+};
+use roro_core::errors::CoreError;
+
+const TCP_TABLE: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41231 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 41377 1 0000000000000000 20 4 30 10 -1
+   2: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 18544 1 0000000000000000 100 0 0 10 0
+";
+
+#[test]
+fn test_listening_inodes_skip_other_ports_and_connected_sockets() {
+    assert_eq!(listening_inodes(TCP_TABLE, 8080), vec![41231]);
+    assert_eq!(listening_inodes(TCP_TABLE, 22), vec![18544]);
+    assert!(listening_inodes(TCP_TABLE, 9090).is_empty());
+}
+
+#[test]
+fn test_listening_inodes_read_tcp6_addresses() {
+    let table = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 52110 1 0000000000000000 100 0 0 10 0
+";
+    assert_eq!(listening_inodes(table, 8080), vec![52110]);
+}
+
+#[test]
+fn test_socket_inode_parses_only_socket_links() {
+    assert_eq!(socket_inode("socket:[41231]"), Some(41231));
+    assert_eq!(socket_inode("pipe:[41231]"), None);
+    assert_eq!(socket_inode("/dev/null"), None);
+}
+
+#[test]
+fn test_parse_cmdline_joins_arguments() {
+    assert_eq!(
+        parse_cmdline(b"kubectl\0port-forward\0svc/api\08080:80\0"),
+        "kubectl port-forward svc/api 8080:80"
+    );
+    assert_eq!(parse_cmdline(b""), "");
+}
+
+#[test]
+fn test_holder_kind_recognises_roro_and_kubectl_port_forwards() {
+    assert_eq!(
+        HolderKind::from_command("/usr/local/bin/kubectl port-forward svc/api 8080:80"),
+        HolderKind::KubectlPortForward
+    );
+    assert_eq!(
+        HolderKind::from_command("kubectl -n dev port-forward pod/api 8080"),
+        HolderKind::KubectlPortForward
+    );
+    assert_eq!(
+        HolderKind::from_command("kubectl get pods"),
+        HolderKind::Other
+    );
+    assert_eq!(
+        HolderKind::from_command("/opt/roro/roro-kube-cli capture api --remote-port 8080"),
+        HolderKind::Roro
+    );
+    assert_eq!(
+        HolderKind::from_command("python3 -m http.server 8080"),
+        HolderKind::Other
+    );
+}
+
+#[test]
+fn test_holder_display_names_the_process() {
+    let holder = PortHolder::new(31337, "python3 -m http.server 8080".to_string());
+    assert_eq!(
+        holder.to_string(),
+        "PID 31337 (python3 -m http.server 8080)"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_find_port_holder_finds_this_process() {
+    use roro_core::api::kubernetes::portforwarding::find_port_holder;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let holder = find_port_holder(port).expect("listener should be found");
+    assert_eq!(holder.pid, std::process::id());
+
+    drop(listener);
+    assert_eq!(find_port_holder(port), None);
+}
+
+#[tokio::test]
+async fn test_conflicts_with_this_process_stay_unnamed() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    assert!(matches!(
+        identify_port_holder(CoreError::PortConflict(port)).await,
+        CoreError::PortConflict(p) if p == port
+    ));
+    assert!(matches!(
+        identify_port_holder(CoreError::Validation("bad".to_string())).await,
+        CoreError::Validation(_)
+    ));
+}
@@ -0,0 +1,46 @@
+// App instance start report tests
+//
+// Tests for summarising which forwards of an app instance started and which failed.
+
+use roro_core::api::kubernetes::portforwarding::{AppForwardOutcome, InstanceStartReport};
+use roro_core::errors::CoreError;
+
+fn report() -> InstanceStartReport {
+    InstanceStartReport {
+        forwards: vec![
+            AppForwardOutcome {
+                forward: "service/api:http".to_string(),
+                result: Ok("dev-api-18080".to_string()),
+            },
+            AppForwardOutcome {
+                forward: "deployment/web:3000".to_string(),
+                result: Err(CoreError::PortConflict(3000)),
+            },
+            AppForwardOutcome {
+                forward: "pod/db:5432".to_string(),
+                result: Ok("dev-db-5432".to_string()),
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_report_lists_started_forwards_in_order() {
+    assert_eq!(report().started(), ["dev-api-18080", "dev-db-5432"]);
+}
+
+#[test]
+fn test_report_lists_failures_with_their_errors() {
+    let report = report();
+    let failed = report.failed();
+
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].0, "deployment/web:3000");
+    assert!(matches!(failed[0].1, CoreError::PortConflict(3000)));
+    assert!(!report.is_complete());
+}
+
+#[test]
+fn test_empty_report_is_complete() {
+    assert!(InstanceStartReport::default().is_complete());
+}
@@ -0,0 +1,64 @@
+// Lazy forward tests
+//
+// Tests for deciding when a woken lazy forward has been idle long enough to close its
+// tunnel.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{idle_remaining, ForwardMetricsSnapshot};
+use std::time::{Duration, SystemTime};
+
+const TIMEOUT: Duration = Duration::from_secs(90);
+
+fn last_active(ago: Duration, now: SystemTime) -> ForwardMetricsSnapshot {
+    ForwardMetricsSnapshot {
+        last_activity: Some(now - ago),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_open_connections_keep_the_whole_timeout() {
+    let now = SystemTime::now();
+    let metrics = ForwardMetricsSnapshot {
+        open_connections: 1,
+        ..last_active(Duration::from_secs(150), now)
+    };
+
+    assert_eq!(idle_remaining(&metrics, TIMEOUT, now), Some(TIMEOUT));
+}
+
+#[test]
+fn test_idle_time_runs_from_last_activity() {
+    let now = SystemTime::now();
+    let metrics = last_active(Duration::from_secs(40), now);
+
+    assert_eq!(
+        idle_remaining(&metrics, TIMEOUT, now),
+        Some(Duration::from_secs(50))
+    );
+}
+
+#[test]
+fn test_forward_is_idle_once_timeout_has_passed() {
+    let now = SystemTime::now();
+
+    assert_eq!(
+        idle_remaining(&last_active(TIMEOUT, now), TIMEOUT, now),
+        None
+    );
+    assert_eq!(
+        idle_remaining(&last_active(Duration::from_secs(100), now), TIMEOUT, now),
+        None
+    );
+}
+
+#[test]
+fn test_no_activity_yet_leaves_the_whole_timeout() {
+    let now = SystemTime::now();
+
+    assert_eq!(
+        idle_remaining(&ForwardMetricsSnapshot::default(), TIMEOUT, now),
+        Some(TIMEOUT)
+    );
+}
@@ -0,0 +1,113 @@
+// Dedicated service address tests
+//
+// Tests for the address range dedicated to services and the host names published for them.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{
+    allocate_service_ip, is_service_ip, service_host_names, service_hosts, BindAddress,
+    ForwardTarget, PortForwardingConfig, PortForwardingState, PortForwardingStatus,
+};
+use roro_domain::PortValue;
+use std::net::{IpAddr, Ipv4Addr};
+
+fn forward(service: &str, namespace: &str, bind_address: BindAddress) -> PortForwardingState {
+    let mut state = PortForwardingState::new(
+        &format!("{namespace}-{service}"),
+        "rancher-desktop",
+        PortForwardingConfig {
+            namespace: namespace.to_string(),
+            pod: format!("{service}-0"),
+            remote_port: 5432,
+            local_port: 5432,
+            instance_id: "myapp".to_string(),
+            target: ForwardTarget::Service {
+                name: service.to_string(),
+                port: PortValue::Numeric(5432),
+            },
+            bind_address,
+            ..Default::default()
+        },
+    );
+    state.status = PortForwardingStatus::Active;
+    state
+}
+
+fn dedicated(last_octet: u8) -> BindAddress {
+    BindAddress::Ip(IpAddr::V4(Ipv4Addr::new(127, 1, 0, last_octet)))
+}
+
+#[test]
+fn test_service_ip_range_leaves_system_loopback_alone() {
+    assert!(is_service_ip("127.1.0.1".parse().unwrap()));
+    assert!(is_service_ip("127.200.3.4".parse().unwrap()));
+    assert!(!is_service_ip("127.0.0.1".parse().unwrap()));
+    assert!(!is_service_ip("127.0.1.1".parse().unwrap()));
+    assert!(!is_service_ip("10.0.0.1".parse().unwrap()));
+    assert!(!is_service_ip("::1".parse().unwrap()));
+}
+
+#[test]
+fn test_service_host_names_cover_cluster_dns_forms() {
+    assert_eq!(
+        service_host_names("postgres", "db"),
+        vec![
+            "postgres",
+            "postgres.db",
+            "postgres.db.svc",
+            "postgres.db.svc.cluster.local"
+        ]
+    );
+}
+
+#[test]
+fn test_only_dedicated_service_forwards_are_published() {
+    let mut pod_forward = forward("worker", "jobs", dedicated(3));
+    pod_forward.config.target = ForwardTarget::Pod;
+    let hosts = service_hosts(&[
+        forward("postgres", "db", dedicated(1)),
+        forward("api", "dev", BindAddress::Localhost),
+        pod_forward,
+    ]);
+
+    assert_eq!(hosts.len(), 4);
+    assert!(hosts
+        .iter()
+        .all(|host| host.ip == Ipv4Addr::new(127, 1, 0, 1)));
+    assert_eq!(hosts[3].name, "postgres.db.svc.cluster.local");
+}
+
+#[test]
+fn test_short_names_resolve_to_the_first_namespace() {
+    let hosts = service_hosts(&[
+        forward("postgres", "db", dedicated(1)),
+        forward("postgres", "analytics", dedicated(2)),
+    ]);
+
+    let lookup = |name: &str| hosts.iter().find(|host| host.name == name).map(|h| h.ip);
+    assert_eq!(lookup("postgres"), Some(Ipv4Addr::new(127, 1, 0, 1)));
+    assert_eq!(lookup("postgres.db"), Some(Ipv4Addr::new(127, 1, 0, 1)));
+    assert_eq!(
+        lookup("postgres.analytics"),
+        Some(Ipv4Addr::new(127, 1, 0, 2))
+    );
+    assert_eq!(
+        hosts.iter().filter(|host| host.name == "postgres").count(),
+        1
+    );
+}
+
+#[test]
+fn test_service_addresses_are_unique_across_contexts() {
+    let in_use = [Ipv4Addr::new(127, 1, 0, 1)];
+
+    let kind = allocate_service_ip("kind-dev", "db", "postgres", &in_use).unwrap();
+    let staging = allocate_service_ip("staging", "db", "postgres", &in_use).unwrap();
+
+    assert_ne!(kind, staging);
+    assert!(!in_use.contains(&kind) && !in_use.contains(&staging));
+    assert_eq!(
+        allocate_service_ip("kind-dev", "db", "postgres", &[]).unwrap(),
+        kind
+    );
+}
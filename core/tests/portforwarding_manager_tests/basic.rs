@@ -15,6 +15,7 @@ async fn test_port_forward_creation() {
         remote_port: 8080,
         local_port: 9000,
         instance_id: "test-instance".to_string(),
+        ..Default::default()
     };
 
     let result = manager.start_forward(config.clone()).await;
@@ -46,6 +47,7 @@ async fn test_stop_forward() {
         remote_port: 8080,
         local_port: 9400,
         instance_id: "test-instance".to_string(),
+        ..Default::default()
     };
 
     let result = manager.start_forward(config).await;
@@ -76,6 +78,7 @@ async fn test_list_forwards() {
         remote_port: 8080,
         local_port: 9500,
         instance_id: "test-instance-1".to_string(),
+        ..Default::default()
     };
 
     let config2 = PortForwardingConfig {
@@ -84,6 +87,7 @@ async fn test_list_forwards() {
         remote_port: 8081,
         local_port: 9501,
         instance_id: "test-instance-2".to_string(),
+        ..Default::default()
     };
 
     let result1 = manager.start_forward(config1).await;
@@ -108,6 +112,7 @@ async fn test_get_forward() {
         remote_port: 8080,
         local_port: 9700,
         instance_id: "test-instance".to_string(),
+        ..Default::default()
     };
 
     let result = manager.start_forward(config).await;
@@ -135,6 +140,7 @@ async fn test_duplicate_forward_id() {
         remote_port: 8080,
         local_port: 9800,
         instance_id: "test-instance".to_string(),
+        ..Default::default()
     };
 
     let result1 = manager.start_forward(config.clone()).await;
@@ -148,4 +154,3 @@ async fn test_duplicate_forward_id() {
         }
     }
 }
-
@@ -3,7 +3,23 @@
 // Tests for multiple ports per instance and filtering by instance
 
 use super::create_test_manager;
-use roro_core::api::kubernetes::portforwarding::PortForwardingConfig;
+use roro_core::api::kubernetes::portforwarding::{PortForwardingConfig, StartMode, StopMode};
+use roro_core::errors::CoreError;
+use roro_domain::AppConfig;
+
+/// An app forwarding two pod ports to fixed local ports
+fn two_port_app(first: u16, second: u16) -> AppConfig {
+    serde_json::from_value(serde_json::json!({
+        "name": "shop",
+        "description": "Test app",
+        "manifestsPath": "k8s",
+        "portForwarding": [
+            { "localport": first.to_string(), "name": "api", "port": 8080, "kind": "pod" },
+            { "localport": second.to_string(), "name": "web", "port": 3000, "kind": "pod" },
+        ],
+    }))
+    .expect("app config should parse")
+}
 
 #[tokio::test]
 async fn test_multiple_ports_per_instance() {
@@ -15,6 +31,7 @@ async fn test_multiple_ports_per_instance() {
         remote_port: 8080,
         local_port: 9300,
         instance_id: "test-instance".to_string(),
+        ..Default::default()
     };
 
     let config2 = PortForwardingConfig {
@@ -23,6 +40,7 @@ async fn test_multiple_ports_per_instance() {
         remote_port: 8081,
         local_port: 9301,
         instance_id: "test-instance".to_string(),
+        ..Default::default()
     };
 
     let result1 = manager.start_forward(config1).await;
@@ -61,6 +79,7 @@ async fn test_list_forwards_by_instance() {
         remote_port: 8080,
         local_port: 9600,
         instance_id: "instance-a".to_string(),
+        ..Default::default()
     };
 
     let config2 = PortForwardingConfig {
@@ -69,6 +88,7 @@ async fn test_list_forwards_by_instance() {
         remote_port: 8081,
         local_port: 9601,
         instance_id: "instance-a".to_string(),
+        ..Default::default()
     };
 
     let config3 = PortForwardingConfig {
@@ -77,6 +97,7 @@ async fn test_list_forwards_by_instance() {
         remote_port: 8082,
         local_port: 9602,
         instance_id: "instance-b".to_string(),
+        ..Default::default()
     };
 
     let result1 = manager.start_forward(config1).await;
@@ -103,3 +124,113 @@ async fn test_list_forwards_by_instance() {
     // This test mainly verifies the list_forwards_by_instance method works when forwards exist
 }
 
+#[tokio::test]
+async fn test_all_or_nothing_start_returns_first_error() {
+    let manager = create_test_manager().await;
+    let _held = std::net::TcpListener::bind("127.0.0.1:9700").expect("Port should be available");
+
+    let result = manager
+        .start_app_instance(
+            &two_port_app(9700, 9701),
+            "default",
+            "shop-a",
+            StartMode::AllOrNothing,
+        )
+        .await;
+
+    assert!(matches!(result, Err(CoreError::PortConflict(9700))));
+    assert!(manager.list_forwards_by_instance("shop-a").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_all_or_nothing_start_rolls_back_started_forwards() {
+    let manager = create_test_manager().await.with_state_persistence(true);
+    let _held = std::net::TcpListener::bind("127.0.0.1:9741").expect("Port should be available");
+    // CoreDNS runs in most clusters; a lazy forward to it starts without opening a tunnel
+    let app: AppConfig = serde_json::from_value(serde_json::json!({
+        "name": "shop",
+        "description": "Test app",
+        "manifestsPath": "k8s",
+        "portForwarding": [
+            {
+                "localport": "9740",
+                "name": "coredns",
+                "port": 53,
+                "kind": "deployment",
+                "lazy": true,
+            },
+            { "localport": "9741", "name": "coredns", "port": 9153, "kind": "deployment" },
+        ],
+    }))
+    .expect("app config should parse");
+
+    let result = manager
+        .start_app_instance(&app, "kube-system", "shop-d", StartMode::AllOrNothing)
+        .await;
+
+    // Without CoreDNS the first forward fails too and there is nothing to roll back
+    if !matches!(result, Err(CoreError::PortConflict(9741))) {
+        return;
+    }
+    assert!(manager.list_forwards_by_instance("shop-d").await.is_empty());
+    assert!(std::net::TcpListener::bind("127.0.0.1:9740").is_ok());
+    let saved = manager
+        .saved_forwards()
+        .await
+        .expect("saved state should load");
+    assert!(saved.iter().all(|record| record.instance_id != "shop-d"));
+}
+
+#[tokio::test]
+async fn test_best_effort_start_reports_each_forward() {
+    let manager = create_test_manager().await;
+    let _held = [
+        std::net::TcpListener::bind("127.0.0.1:9710").expect("Port should be available"),
+        std::net::TcpListener::bind("127.0.0.1:9711").expect("Port should be available"),
+    ];
+
+    let report = manager
+        .start_app_instance(
+            &two_port_app(9710, 9711),
+            "default",
+            "shop-b",
+            StartMode::BestEffort,
+        )
+        .await
+        .expect("best effort start should report failures");
+
+    assert!(!report.is_complete());
+    assert!(report.started().is_empty());
+    let failed: Vec<&str> = report
+        .failed()
+        .iter()
+        .map(|(forward, _)| *forward)
+        .collect();
+    assert_eq!(failed, ["pod/api:8080", "pod/web:3000"]);
+}
+
+#[tokio::test]
+async fn test_invalid_app_starts_nothing() {
+    let manager = create_test_manager().await;
+    let mut app = two_port_app(9720, 9721);
+    app.name = String::new();
+
+    let result = manager
+        .start_app_instance(&app, "default", "shop-c", StartMode::BestEffort)
+        .await;
+
+    assert!(matches!(result, Err(CoreError::Domain(_))));
+}
+
+#[tokio::test]
+async fn test_stop_unknown_instance_is_not_found() {
+    let manager = create_test_manager().await;
+
+    let result = manager
+        .stop_app_instance("no-such-instance", StopMode::Now)
+        .await;
+
+    assert!(
+        matches!(result, Err(CoreError::PortForwardingNotFound(id)) if id == "no-such-instance")
+    );
+}
@@ -3,6 +3,7 @@
 // Tests for port conflict detection and finding available ports
 
 use super::create_test_manager;
+use roro_core::api::kubernetes::portforwarding::BindAddress;
 use roro_core::errors::CoreError;
 
 #[tokio::test]
@@ -12,7 +13,9 @@ async fn test_port_conflict_detection() {
     let listener =
         std::net::TcpListener::bind("127.0.0.1:9100").expect("Port should be available for test");
 
-    let result = manager.check_port_available(9100);
+    let result = manager
+        .check_port_available(BindAddress::default(), 9100)
+        .await;
     assert!(result.is_err());
     match result {
         Err(CoreError::PortConflict(9100)) => {}
@@ -21,7 +24,9 @@ async fn test_port_conflict_detection() {
 
     drop(listener);
 
-    let result = manager.check_port_available(9100);
+    let result = manager
+        .check_port_available(BindAddress::default(), 9100)
+        .await;
     assert!(result.is_ok());
 }
 
@@ -32,11 +37,10 @@ async fn test_find_available_port() {
     let listener =
         std::net::TcpListener::bind("127.0.0.1:9200").expect("Port should be available for test");
 
-    let result = manager.find_available_port(9200);
+    let result = manager.find_available_port(BindAddress::default(), 9200);
     assert!(result.is_ok());
     let port = result.expect("Port should be found");
     assert!(port > 9200);
 
     drop(listener);
 }
-
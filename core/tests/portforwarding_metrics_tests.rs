@@ -0,0 +1,89 @@
+// Port forward metrics tests
+//
+// Tests for per-forward traffic and connection counters.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{
+    copy_bidirectional_with_metrics, copy_with_metrics, Direction, ForwardMetrics,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[test]
+fn test_metrics_start_empty() {
+    let snapshot = ForwardMetrics::default().snapshot();
+
+    assert_eq!(snapshot.bytes_sent, 0);
+    assert_eq!(snapshot.total_connections, 0);
+    assert!(snapshot.last_activity.is_none());
+}
+
+#[test]
+fn test_metrics_track_connection_lifecycle() {
+    let metrics = ForwardMetrics::default();
+
+    metrics.record_accepted();
+    metrics.record_opened();
+    metrics.record_accepted();
+    metrics.record_failed();
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.total_connections, 2);
+    assert_eq!(snapshot.open_connections, 1);
+    assert_eq!(snapshot.failed_connections, 1);
+    assert!(snapshot.last_activity.is_some());
+
+    metrics.record_closed();
+    metrics.record_closed();
+    assert_eq!(metrics.snapshot().open_connections, 0);
+}
+
+#[tokio::test]
+async fn test_copy_with_metrics_counts_bytes() {
+    let metrics = ForwardMetrics::default();
+    let mut reader: &[u8] = b"hello world";
+    let mut writer = Vec::new();
+
+    let copied = copy_with_metrics(&mut reader, &mut writer, &metrics, Direction::Received)
+        .await
+        .unwrap();
+
+    assert_eq!(copied, 11);
+    assert_eq!(writer, b"hello world");
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.bytes_received, 11);
+    assert_eq!(snapshot.bytes_sent, 0);
+}
+
+#[tokio::test]
+async fn test_bidirectional_copy_keeps_reading_after_half_close() {
+    let metrics = ForwardMetrics::default();
+    let (local, mut client) = tokio::io::duplex(64);
+    let (remote, mut pod) = tokio::io::duplex(64);
+
+    let copy = tokio::spawn(async move {
+        copy_bidirectional_with_metrics(local, remote, &metrics)
+            .await
+            .map(|copied| (copied, metrics.snapshot()))
+    });
+
+    // The client sends its request and half-closes
+    client.write_all(b"ping").await.unwrap();
+    client.shutdown().await.unwrap();
+
+    // The pod sees the request followed by EOF, then still answers
+    let mut request = Vec::new();
+    pod.read_to_end(&mut request).await.unwrap();
+    assert_eq!(request, b"ping");
+    pod.write_all(b"pong").await.unwrap();
+    drop(pod);
+
+    let mut response = Vec::new();
+    client.read_to_end(&mut response).await.unwrap();
+    assert_eq!(response, b"pong");
+
+    let ((sent, received), snapshot) = copy.await.unwrap().unwrap();
+    assert_eq!((sent, received), (4, 4));
+    assert_eq!(snapshot.bytes_sent, 4);
+    assert_eq!(snapshot.bytes_received, 4);
+}
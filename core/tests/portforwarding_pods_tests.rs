@@ -0,0 +1,184 @@
+// Pod name matching tests
+//
+// Tests for resolving a configured pod name to a ready pod, skipping terminating pods and
+// reporting names that match several workloads.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+mod common;
+
+use common::pod;
+use k8s_openapi::api::core::v1::Pod;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::OwnerReference;
+use roro_core::api::kubernetes::portforwarding::{match_pods, matching_pods, PodMatch};
+use std::collections::BTreeMap;
+
+/// A running pod owned by the `ReplicaSet` of Deployment `deployment` with template `hash`
+fn deployment_pod(deployment: &str, hash: &str, suffix: &str, ready: bool) -> Pod {
+    let mut pod = pod(&format!("{deployment}-{hash}-{suffix}"), "Running", ready);
+    pod.metadata.owner_references = Some(vec![OwnerReference {
+        api_version: "apps/v1".to_string(),
+        kind: "ReplicaSet".to_string(),
+        name: format!("{deployment}-{hash}"),
+        uid: format!("{deployment}-{hash}-uid"),
+        controller: Some(true),
+        ..Default::default()
+    }]);
+    pod.metadata.labels = Some(BTreeMap::from([(
+        "pod-template-hash".to_string(),
+        hash.to_string(),
+    )]));
+    pod
+}
+
+fn terminating(mut pod: Pod) -> Pod {
+    pod.metadata.deletion_timestamp =
+        Some(serde_json::from_value(serde_json::json!("2024-01-01T00:00:00Z")).unwrap());
+    pod
+}
+
+#[test]
+fn test_exact_name_is_used_when_ready() {
+    let pods = [
+        pod("api", "Running", true),
+        pod("api-other", "Running", true),
+    ];
+
+    assert_eq!(match_pods("api", &pods), PodMatch::Ready("api".to_string()));
+}
+
+#[test]
+fn test_exact_name_that_is_not_ready_is_reported() {
+    let pods = [pod("api", "Pending", false)];
+
+    assert_eq!(
+        match_pods("api", &pods),
+        PodMatch::NotReady(vec!["api".to_string()])
+    );
+}
+
+#[test]
+fn test_prefix_prefers_ready_pod_over_crash_looping_and_pending_pods() {
+    let pods = [
+        deployment_pod("api", "7d9f", "aaaaa", false),
+        deployment_pod("api", "7d9f", "bbbbb", true),
+        {
+            let mut pending = deployment_pod("api", "7d9f", "00000", false);
+            pending.status.as_mut().unwrap().phase = Some("Pending".to_string());
+            pending
+        },
+    ];
+
+    assert_eq!(
+        match_pods("api", &pods),
+        PodMatch::Ready("api-7d9f-bbbbb".to_string())
+    );
+}
+
+#[test]
+fn test_terminating_pods_are_skipped() {
+    let pods = [
+        terminating(deployment_pod("api", "7d9f", "aaaaa", true)),
+        deployment_pod("api", "8e0a", "bbbbb", true),
+    ];
+
+    assert_eq!(
+        match_pods("api", &pods),
+        PodMatch::Ready("api-8e0a-bbbbb".to_string())
+    );
+    assert_eq!(match_pods("api-7d9f", &pods[..1]), PodMatch::NotFound);
+}
+
+#[test]
+fn test_workload_named_exactly_wins_over_longer_names() {
+    let pods = [
+        deployment_pod("api-gateway", "5c6b", "aaaaa", true),
+        deployment_pod("api", "7d9f", "bbbbb", true),
+    ];
+
+    assert_eq!(
+        match_pods("api", &pods),
+        PodMatch::Ready("api-7d9f-bbbbb".to_string())
+    );
+    assert_eq!(
+        match_pods("api-gateway", &pods),
+        PodMatch::Ready("api-gateway-5c6b-aaaaa".to_string())
+    );
+}
+
+#[test]
+fn test_rollout_pods_belong_to_one_workload() {
+    let pods = [
+        deployment_pod("api", "7d9f", "aaaaa", false),
+        deployment_pod("api", "8e0a", "bbbbb", true),
+    ];
+
+    assert_eq!(
+        match_pods("ap", &pods),
+        PodMatch::Ready("api-8e0a-bbbbb".to_string())
+    );
+}
+
+#[test]
+fn test_prefix_of_several_workloads_is_ambiguous() {
+    let pods = [
+        deployment_pod("api-gateway", "5c6b", "aaaaa", true),
+        deployment_pod("api", "7d9f", "bbbbb", true),
+        pod("web", "Running", true),
+    ];
+
+    assert_eq!(
+        match_pods("ap", &pods),
+        PodMatch::Ambiguous(vec![
+            "api-7d9f-bbbbb".to_string(),
+            "api-gateway-5c6b-aaaaa".to_string(),
+        ])
+    );
+}
+
+#[test]
+fn test_matching_pods_that_are_not_ready_are_listed() {
+    let pods = [
+        deployment_pod("api", "7d9f", "bbbbb", false),
+        deployment_pod("api", "7d9f", "aaaaa", false),
+    ];
+
+    assert_eq!(
+        match_pods("api", &pods),
+        PodMatch::NotReady(vec![
+            "api-7d9f-aaaaa".to_string(),
+            "api-7d9f-bbbbb".to_string(),
+        ])
+    );
+}
+
+#[test]
+fn test_unknown_name_is_not_found() {
+    let pods = [pod("web", "Running", true)];
+
+    assert_eq!(match_pods("api", &pods), PodMatch::NotFound);
+    assert_eq!(match_pods("api", &[]), PodMatch::NotFound);
+}
+
+#[test]
+fn test_matching_pods_returns_the_whole_matched_workload() {
+    let pods = [
+        deployment_pod("api", "7d9f", "aaaaa", false),
+        deployment_pod("api", "7d9f", "bbbbb", true),
+        terminating(deployment_pod("api", "7d9f", "ccccc", true)),
+        deployment_pod("api-gateway", "5c6b", "ddddd", true),
+        pod("web", "Running", true),
+    ];
+
+    let matched: Vec<&str> = matching_pods("api", &pods)
+        .unwrap()
+        .into_iter()
+        .filter_map(|pod| pod.metadata.name.as_deref())
+        .collect();
+    assert_eq!(matched, vec!["api-7d9f-aaaaa", "api-7d9f-bbbbb"]);
+    assert!(matches!(
+        matching_pods("ap", &pods),
+        Err(PodMatch::Ambiguous(_))
+    ));
+    assert_eq!(matching_pods("db", &pods), Err(PodMatch::NotFound));
+}
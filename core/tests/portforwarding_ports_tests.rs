@@ -0,0 +1,70 @@
+// Local port allocation tests
+//
+// Tests for choosing local ports from `localport` policies.
+
+use roro_core::api::kubernetes::portforwarding::{choose_local_port, AUTO_PORT_RANGE};
+use roro_core::errors::CoreError;
+use roro_domain::LocalPortPolicy;
+
+#[test]
+fn test_fixed_port_conflicts_when_taken() {
+    assert_eq!(
+        choose_local_port(LocalPortPolicy::Fixed(8080), None, |_| true).ok(),
+        Some(8080)
+    );
+    assert!(matches!(
+        choose_local_port(LocalPortPolicy::Fixed(8080), Some(9000), |port| port
+            != 8080),
+        Err(CoreError::PortConflict(8080))
+    ));
+}
+
+#[test]
+fn test_preferred_port_falls_back_upwards() {
+    let taken = [8080, 8081];
+
+    assert_eq!(
+        choose_local_port(LocalPortPolicy::Preferred(8080), None, |port| !taken
+            .contains(&port))
+        .ok(),
+        Some(8082)
+    );
+}
+
+#[test]
+fn test_range_uses_first_free_port_or_fails() {
+    assert_eq!(
+        choose_local_port(LocalPortPolicy::Range(30000, 30002), None, |port| port
+            == 30002)
+        .ok(),
+        Some(30002)
+    );
+    assert!(matches!(
+        choose_local_port(LocalPortPolicy::Range(30000, 30002), None, |_| false),
+        Err(CoreError::PortForwarding(_))
+    ));
+}
+
+#[test]
+fn test_remembered_port_is_reused_when_allowed_and_free() {
+    assert_eq!(
+        choose_local_port(LocalPortPolicy::Range(30000, 30100), Some(30042), |_| true).ok(),
+        Some(30042)
+    );
+    assert_eq!(
+        choose_local_port(LocalPortPolicy::Auto, Some(12345), |_| true).ok(),
+        Some(12345)
+    );
+}
+
+#[test]
+fn test_remembered_port_is_ignored_outside_policy_or_taken() {
+    assert_eq!(
+        choose_local_port(LocalPortPolicy::Range(30000, 30100), Some(8080), |_| true).ok(),
+        Some(30000)
+    );
+    assert_eq!(
+        choose_local_port(LocalPortPolicy::Auto, Some(12345), |port| port != 12345).ok(),
+        Some(*AUTO_PORT_RANGE.start())
+    );
+}
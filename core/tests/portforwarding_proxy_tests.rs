@@ -0,0 +1,239 @@
+// Reverse proxy tests
+//
+// Tests for routing host names to forwards and relaying connections through the proxy.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{
+    find_route, proxy_addresses, proxy_routes, serve_connection, start_proxy, BindAddress,
+    ForwardTarget, PortForwardingConfig, PortForwardingState, PortForwardingStatus, ProxyConfig,
+    ProxyRoute,
+};
+use roro_core::CoreError;
+use roro_domain::PortValue;
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+fn state(id: &str, config: PortForwardingConfig) -> PortForwardingState {
+    let mut state = PortForwardingState::new(id, "rancher-desktop", config);
+    state.status = PortForwardingStatus::Active;
+    state
+}
+
+fn service_forward(
+    id: &str,
+    service: &str,
+    remote_port: u16,
+    local_port: u16,
+) -> PortForwardingState {
+    state(
+        id,
+        PortForwardingConfig {
+            namespace: "dev".to_string(),
+            pod: format!("{service}-7d9f-aaaaa"),
+            remote_port,
+            local_port,
+            instance_id: "myapp".to_string(),
+            target: ForwardTarget::Service {
+                name: service.to_string(),
+                port: PortValue::Numeric(remote_port),
+            },
+            ..Default::default()
+        },
+    )
+}
+
+fn route(host: &str, target: SocketAddr) -> ProxyRoute {
+    ProxyRoute {
+        host: host.to_string(),
+        forward_id: format!("{host}-forward"),
+        context: "rancher-desktop".to_string(),
+        target,
+    }
+}
+
+/// Send `request` through a proxy listening on loopback and return the whole response
+async fn proxy_request(routes: Vec<ProxyRoute>, request: &[u8]) -> String {
+    proxy_request_listing(routes, request, true).await
+}
+
+/// Send `request` through the proxy, listing routes for unknown hosts or not
+async fn proxy_request_listing(
+    routes: Vec<ProxyRoute>,
+    request: &[u8],
+    list_routes: bool,
+) -> String {
+    let (mut client, proxy_side) = tokio::io::duplex(64 * 1024);
+    let proxy =
+        tokio::spawn(async move { serve_connection(proxy_side, &routes, list_routes).await });
+
+    client.write_all(request).await.unwrap();
+    let mut response = Vec::new();
+    client.read_to_end(&mut response).await.unwrap();
+    // Closing the client ends the relay in the other direction too
+    drop(client);
+    proxy.await.unwrap().unwrap();
+    String::from_utf8(response).unwrap()
+}
+
+#[test]
+fn test_service_forwards_are_routed_by_service_and_instance() {
+    let routes = proxy_routes(&[service_forward("api-8080", "api", 8080, 18080)]);
+
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0].host, "api.myapp.localhost");
+    assert_eq!(routes[0].forward_id, "api-8080");
+    assert_eq!(routes[0].target, "127.0.0.1:18080".parse().unwrap());
+}
+
+#[test]
+fn test_pod_forwards_are_routed_by_pod_name() {
+    let routes = proxy_routes(&[state(
+        "pod-forward",
+        PortForwardingConfig {
+            pod: "Grafana_0".to_string(),
+            remote_port: 3000,
+            local_port: 13000,
+            instance_id: "monitoring".to_string(),
+            ..Default::default()
+        },
+    )]);
+
+    assert_eq!(routes[0].host, "grafana-0.monitoring.localhost");
+}
+
+#[test]
+fn test_conflicting_hosts_fall_back_to_the_remote_port() {
+    let routes = proxy_routes(&[
+        service_forward("api-8080", "api", 8080, 18080),
+        service_forward("api-9090", "api", 9090, 19090),
+    ]);
+
+    assert_eq!(routes[0].host, "api.myapp.localhost");
+    assert_eq!(routes[1].host, "api-9090.myapp.localhost");
+}
+
+#[test]
+fn test_tls_forwards_are_not_routed() {
+    let mut forward = service_forward("api-8443", "api", 8443, 18443);
+    forward.config.tls = true;
+
+    assert!(proxy_routes(&[forward]).is_empty());
+}
+
+#[test]
+fn test_unspecified_bind_address_is_reached_over_loopback() {
+    let mut forward = service_forward("api-8080", "api", 8080, 18080);
+    forward.config.bind_address = BindAddress::Ip("0.0.0.0".parse().unwrap());
+
+    assert_eq!(
+        proxy_routes(&[forward])[0].target,
+        "127.0.0.1:18080".parse().unwrap()
+    );
+}
+
+#[test]
+fn test_find_route_ignores_port_case_and_trailing_dot() {
+    let routes = vec![route("api.myapp.localhost", "127.0.0.1:1".parse().unwrap())];
+
+    assert!(find_route(&routes, "api.myapp.localhost").is_some());
+    assert!(find_route(&routes, "API.MyApp.localhost:8080").is_some());
+    assert!(find_route(&routes, "api.myapp.localhost.").is_some());
+    assert!(find_route(&routes, "web.myapp.localhost").is_none());
+}
+
+#[tokio::test]
+async fn test_request_is_relayed_to_the_routed_forward() {
+    let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target = backend.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = backend.accept().await.unwrap();
+        let mut received = vec![0u8; 1024];
+        let read = stream.read(&mut received).await.unwrap();
+        received.truncate(read);
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok")
+            .await
+            .unwrap();
+        received
+    });
+
+    let request = b"GET /health HTTP/1.1\r\nHost: api.myapp.localhost:8080\r\n\r\n";
+    let response = proxy_request(
+        vec![
+            route("web.myapp.localhost", "127.0.0.1:1".parse().unwrap()),
+            route("api.myapp.localhost", target),
+        ],
+        request,
+    )
+    .await;
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("ok"));
+    // The request reaches the forward unchanged
+    assert_eq!(server.await.unwrap(), request);
+}
+
+#[tokio::test]
+async fn test_unknown_host_lists_routes() {
+    let response = proxy_request(
+        vec![route("api.myapp.localhost", "127.0.0.1:1".parse().unwrap())],
+        b"GET / HTTP/1.1\r\nHost: web.myapp.localhost\r\n\r\n",
+    )
+    .await;
+
+    assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+    assert!(response.contains("api.myapp.localhost"));
+}
+
+#[tokio::test]
+async fn test_unknown_host_does_not_list_routes_when_exposed() {
+    let response = proxy_request_listing(
+        vec![route("api.myapp.localhost", "127.0.0.1:1".parse().unwrap())],
+        b"GET / HTTP/1.1\r\nHost: web.myapp.localhost\r\n\r\n",
+        false,
+    )
+    .await;
+
+    assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+    assert!(!response.contains("api.myapp.localhost"));
+}
+
+#[tokio::test]
+async fn test_proxy_on_all_interfaces_requires_expose_to_network() {
+    let config = ProxyConfig {
+        bind_address: BindAddress::Ip("0.0.0.0".parse().unwrap()),
+        port: 0,
+        expose_to_network: false,
+    };
+
+    let error = start_proxy(config).await.unwrap_err();
+    assert!(matches!(error, CoreError::Validation(_)), "{error:?}");
+    assert!(proxy_addresses().is_none());
+}
+
+#[tokio::test]
+async fn test_missing_host_is_a_bad_request() {
+    let response = proxy_request(Vec::new(), b"GET / HTTP/1.1\r\n\r\n").await;
+
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request"));
+}
+
+#[tokio::test]
+async fn test_unreachable_forward_is_a_bad_gateway() {
+    // Bind then drop a listener to get a port nothing listens on
+    let closed = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let response = proxy_request(
+        vec![route("api.myapp.localhost", closed)],
+        b"GET / HTTP/1.1\r\nHost: api.myapp.localhost\r\n\r\n",
+    )
+    .await;
+
+    assert!(response.starts_with("HTTP/1.1 502 Bad Gateway"));
+}
@@ -1,6 +1,6 @@
-// Port forwarding singleton tests
+// Port forwarding registry tests
 //
-// Tests for the port forwarding manager singleton initialization, retrieval, and state management.
+// Tests for the per-context port forwarding manager registry initialization, retrieval, and state management.
 
 mod initialization;
 mod retrieval;
@@ -12,4 +12,3 @@ use roro_core::errors::CoreError;
 pub async fn create_test_client() -> Result<KubernetesClient, CoreError> {
     KubernetesClient::new().await
 }
-
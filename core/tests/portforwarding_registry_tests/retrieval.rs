@@ -3,7 +3,9 @@
 // Tests for getting and retrieving the singleton instance
 
 use super::create_test_client;
-use roro_core::api::kubernetes::portforwarding_singleton::{get, get_or_init, initialize, is_initialized};
+use roro_core::api::kubernetes::portforwarding_registry::{
+    get, get_or_init, initialize, is_initialized,
+};
 use roro_core::errors::CoreError;
 use std::sync::Arc;
 
@@ -11,7 +13,7 @@ use std::sync::Arc;
 async fn test_singleton_get_before_initialization() {
     // Note: This test may pass or fail depending on test execution order
     // If another test initialized the singleton first, get() will return Some
-    let manager = get();
+    let manager = get("rancher-desktop");
     // We can't assert None here due to test execution order, but we can verify it doesn't panic
     let _ = manager;
 }
@@ -25,7 +27,7 @@ async fn test_singleton_get_after_initialization() {
         let _ = initialize(&client);
 
         // Now get should return Some
-        let manager = get();
+        let manager = get(client.current_context());
         assert!(manager.is_some());
 
         // Verify we can use the manager
@@ -47,8 +49,8 @@ async fn test_singleton_get_or_init_initializes() {
     match result {
         Ok(manager) => {
             // Success - singleton is now initialized
-            assert!(is_initialized());
-            let manager2 = get();
+            assert!(is_initialized("rancher-desktop"));
+            let manager2 = get("rancher-desktop");
             assert!(manager2.is_some());
 
             // Verify we can use the manager
@@ -76,8 +78,8 @@ async fn test_singleton_get_or_init_returns_existing() {
         let _ = initialize(&client);
 
         // Now get_or_init should return the existing instance
-        let result1 = get_or_init("rancher-desktop").await;
-        let result2 = get_or_init("rancher-desktop").await;
+        let result1 = get_or_init(client.current_context()).await;
+        let result2 = get_or_init(client.current_context()).await;
 
         match (result1, result2) {
             (Ok(manager1), Ok(manager2)) => {
@@ -131,4 +133,3 @@ async fn test_singleton_get_or_init_invalid_context() {
         }
     }
 }
-
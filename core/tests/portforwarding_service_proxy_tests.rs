@@ -0,0 +1,202 @@
+// Service proxy transport tests
+//
+// Tests for building API server service proxy paths, rejecting paths that climb out of
+// them, choosing which headers are relayed, and relaying requests from a local connection
+// through a stand-in API server.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{
+    is_relayed_header, service_proxy_path, ForwardMetrics, ServiceProxy,
+};
+use roro_core::CoreError;
+use roro_domain::PortValue;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+#[test]
+fn test_path_names_service_port_by_number_or_name() {
+    assert_eq!(
+        service_proxy_path("dev", "api", &PortValue::Numeric(8080), "/health?deep=1").unwrap(),
+        "/api/v1/namespaces/dev/services/api:8080/proxy/health?deep=1"
+    );
+    assert_eq!(
+        service_proxy_path("dev", "api", &PortValue::Named("http".to_string()), "/").unwrap(),
+        "/api/v1/namespaces/dev/services/api:http/proxy/"
+    );
+}
+
+#[test]
+fn test_path_keeps_only_the_path_of_absolute_targets() {
+    let port = PortValue::Numeric(80);
+
+    assert_eq!(
+        service_proxy_path("dev", "web", &port, "http://localhost:8080/a/b?c=d").unwrap(),
+        "/api/v1/namespaces/dev/services/web:80/proxy/a/b?c=d"
+    );
+    assert_eq!(
+        service_proxy_path("dev", "web", &port, "http://localhost:8080").unwrap(),
+        "/api/v1/namespaces/dev/services/web:80/proxy/"
+    );
+    assert_eq!(
+        service_proxy_path("dev", "web", &port, "*").unwrap(),
+        "/api/v1/namespaces/dev/services/web:80/proxy/"
+    );
+}
+
+#[test]
+fn test_path_rejects_dot_segments_even_when_encoded() {
+    let port = PortValue::Numeric(80);
+
+    for target in [
+        "/../../secrets",
+        "/a/./b",
+        "/%2e%2e/secrets",
+        "/%2E./secrets",
+        "/a/..%2f..%2fsecrets",
+        "http://localhost:8080/..",
+    ] {
+        assert!(
+            matches!(
+                service_proxy_path("dev", "web", &port, target),
+                Err(CoreError::Validation(_))
+            ),
+            "{target} should be rejected"
+        );
+    }
+    // Dots within a segment, and in the query, are ordinary characters
+    assert_eq!(
+        service_proxy_path("dev", "web", &port, "/v1.2/..hidden?next=../a").unwrap(),
+        "/api/v1/namespaces/dev/services/web:80/proxy/v1.2/..hidden?next=../a"
+    );
+}
+
+#[test]
+fn test_connection_headers_and_credentials_are_not_relayed() {
+    for name in [
+        "Connection",
+        "Host",
+        "Transfer-Encoding",
+        "Authorization",
+        "upgrade",
+    ] {
+        assert!(!is_relayed_header(name), "{name} should not be relayed");
+    }
+    for name in ["Content-Type", "Accept", "Cookie", "X-Request-Id"] {
+        assert!(is_relayed_header(name), "{name} should be relayed");
+    }
+}
+
+/// A proxy whose API server answers one request with `response` and reports the request
+/// head it received
+async fn proxy_answering(
+    response: &'static str,
+) -> (ServiceProxy, tokio::sync::oneshot::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let (head_tx, head_rx) = tokio::sync::oneshot::channel();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut head = Vec::new();
+        let mut buffer = [0u8; 1024];
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = stream.read(&mut buffer).await.unwrap();
+            head.extend_from_slice(&buffer[..read]);
+        }
+        let _ = head_tx.send(String::from_utf8_lossy(&head).to_string());
+        stream.write_all(response.as_bytes()).await.unwrap();
+        let _ = stream.read(&mut buffer).await;
+    });
+
+    let config = kube::Config::new(format!("http://{address}").parse().unwrap());
+    let client = kube::Client::try_from(config).unwrap();
+    let proxy = ServiceProxy::new(client, "dev", "api", &PortValue::Numeric(8080));
+    (proxy, head_rx)
+}
+
+/// Send `request` through `proxy` and read the response until the proxy closes the
+/// connection or `done` says it is complete
+async fn exchange(proxy: ServiceProxy, request: &str, done: fn(&str) -> bool) -> String {
+    let (mut client, local) = tokio::io::duplex(64 * 1024);
+    let serving = tokio::spawn(async move {
+        proxy
+            .serve(local, &ForwardMetrics::default(), &|_, _| {})
+            .await
+    });
+
+    client.write_all(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !done(&String::from_utf8_lossy(&response)) {
+        let read = client.read(&mut buffer).await.unwrap();
+        if read == 0 {
+            break;
+        }
+        response.extend_from_slice(&buffer[..read]);
+    }
+    drop(client);
+    serving.await.unwrap().unwrap();
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[tokio::test]
+async fn test_request_is_relayed_through_the_service_proxy_path() {
+    let (proxy, head) = proxy_answering(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nhello",
+    )
+    .await;
+
+    let response = exchange(
+        proxy,
+        "GET /health?deep=1 HTTP/1.1\r\nHost: localhost:18080\r\nX-Request-Id: abc\r\n\r\n",
+        |response| response.ends_with("0\r\n\r\n"),
+    )
+    .await;
+
+    let head = head.await.unwrap();
+    assert!(head.starts_with(
+        "GET /api/v1/namespaces/dev/services/api:8080/proxy/health?deep=1 HTTP/1.1\r\n"
+    ));
+    assert!(head.to_ascii_lowercase().contains("x-request-id: abc\r\n"));
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("content-type: text/plain\r\n"));
+    assert!(response.contains("Transfer-Encoding: chunked\r\n"));
+    assert!(response.ends_with("\r\n\r\n5\r\nhello\r\n0\r\n\r\n"));
+}
+
+#[tokio::test]
+async fn test_upgrade_requests_are_refused() {
+    let (proxy, _) = proxy_answering("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await;
+
+    let response = exchange(
+        proxy,
+        "GET /ws HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\r\n",
+        |_| false,
+    )
+    .await;
+
+    assert!(response.starts_with("HTTP/1.1 501 Not Implemented\r\n"));
+}
+
+#[tokio::test]
+async fn test_dot_segment_request_gets_bad_request() {
+    let (proxy, _) = proxy_answering("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await;
+
+    let response = exchange(
+        proxy,
+        "GET /%2e%2e/%2e%2e/secrets HTTP/1.1\r\nHost: localhost\r\n\r\n",
+        |_| false,
+    )
+    .await;
+
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+}
+
+#[tokio::test]
+async fn test_malformed_request_gets_bad_request() {
+    let (proxy, _) = proxy_answering("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await;
+
+    let response = exchange(proxy, "GET /\r\nnot http at all\r\n\r\n", |_| false).await;
+
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+}
@@ -0,0 +1,154 @@
+// Service port forward resolution tests
+//
+// Tests for mapping service ports through targetPort and picking ready backing pods.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+mod common;
+
+use common::pod;
+use k8s_openapi::api::core::v1::{
+    Container, ContainerPort, ObjectReference, Pod, PodSpec, Service, ServicePort, ServiceSpec,
+};
+use k8s_openapi::api::discovery::v1::{Endpoint, EndpointConditions, EndpointSlice};
+use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+use roro_core::api::kubernetes::portforwarding::{
+    find_service_port, is_pod_ready, ready_endpoint_pods, target_container_port,
+};
+use roro_core::errors::CoreError;
+use roro_domain::PortValue;
+
+fn service_with_ports(ports: Vec<ServicePort>) -> Service {
+    let mut service = Service::default();
+    service.metadata.name = Some("api-service".to_string());
+    service.spec = Some(ServiceSpec {
+        ports: Some(ports),
+        ..Default::default()
+    });
+    service
+}
+
+fn pod_with_named_port(name: &str, port: i32) -> Pod {
+    let mut pod = Pod::default();
+    pod.metadata.name = Some("api-abc123".to_string());
+    pod.spec = Some(PodSpec {
+        containers: vec![Container {
+            name: "api".to_string(),
+            ports: Some(vec![ContainerPort {
+                name: Some(name.to_string()),
+                container_port: port,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+    pod
+}
+
+fn endpoint(pod: &str, ready: Option<bool>) -> Endpoint {
+    Endpoint {
+        conditions: Some(EndpointConditions {
+            ready,
+            ..Default::default()
+        }),
+        target_ref: Some(ObjectReference {
+            kind: Some("Pod".to_string()),
+            name: Some(pod.to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_find_service_port_by_number_and_name() {
+    let service = service_with_ports(vec![
+        ServicePort {
+            name: Some("http".to_string()),
+            port: 80,
+            ..Default::default()
+        },
+        ServicePort {
+            name: Some("prometheus".to_string()),
+            port: 9090,
+            ..Default::default()
+        },
+    ]);
+
+    let by_number = find_service_port(&service, &PortValue::Numeric(80)).unwrap();
+    assert_eq!(by_number.name.as_deref(), Some("http"));
+
+    let by_name = find_service_port(&service, &PortValue::Named("prometheus".to_string())).unwrap();
+    assert_eq!(by_name.port, 9090);
+}
+
+#[test]
+fn test_find_service_port_missing() {
+    let service = service_with_ports(vec![ServicePort {
+        port: 80,
+        ..Default::default()
+    }]);
+
+    match find_service_port(&service, &PortValue::Named("grpc".to_string())) {
+        Err(CoreError::PortForwarding(msg)) => {
+            assert!(msg.contains("api-service"));
+            assert!(msg.contains("grpc"));
+        }
+        other => panic!("Expected PortForwarding error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_target_container_port_mapping() {
+    let pod = pod_with_named_port("metrics", 9102);
+
+    let defaulted = ServicePort {
+        port: 8080,
+        ..Default::default()
+    };
+    assert_eq!(target_container_port(&defaulted, &pod).unwrap(), 8080);
+
+    let numeric = ServicePort {
+        port: 80,
+        target_port: Some(IntOrString::Int(3000)),
+        ..Default::default()
+    };
+    assert_eq!(target_container_port(&numeric, &pod).unwrap(), 3000);
+
+    let named = ServicePort {
+        port: 9090,
+        target_port: Some(IntOrString::String("metrics".to_string())),
+        ..Default::default()
+    };
+    assert_eq!(target_container_port(&named, &pod).unwrap(), 9102);
+
+    let unknown = ServicePort {
+        port: 9090,
+        target_port: Some(IntOrString::String("admin".to_string())),
+        ..Default::default()
+    };
+    assert!(target_container_port(&unknown, &pod).is_err());
+}
+
+#[test]
+fn test_is_pod_ready() {
+    assert!(is_pod_ready(&pod("api-abc123", "Running", true)));
+    assert!(!is_pod_ready(&pod("api-abc123", "Running", false)));
+    assert!(!is_pod_ready(&pod("api-abc123", "Pending", true)));
+    assert!(!is_pod_ready(&Pod::default()));
+}
+
+#[test]
+fn test_ready_endpoint_pods_skips_unready() {
+    let slice = EndpointSlice {
+        endpoints: vec![
+            endpoint("api-1", Some(false)),
+            endpoint("api-2", Some(true)),
+            endpoint("api-3", None),
+        ],
+        ..Default::default()
+    };
+
+    assert_eq!(ready_endpoint_pods(&[slice]), vec!["api-2", "api-3"]);
+}
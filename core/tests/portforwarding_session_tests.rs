@@ -0,0 +1,99 @@
+// Port forwarding session tests
+//
+// Tests for running the port-forward protocol over a websocket: relaying data past the
+// port announcements, keepalive pings, pod errors and closing the socket.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use futures::{SinkExt, StreamExt};
+use roro_core::api::kubernetes::portforwarding::spawn_session;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+const KEEPALIVE: Duration = Duration::from_hours(1);
+
+/// A session over an in-memory websocket, and the API server's end of it with the port
+/// already announced on both channels
+async fn session(keepalive: Duration) -> (DuplexStream, WebSocketStream<DuplexStream>) {
+    let (client, server) = tokio::io::duplex(64 * 1024);
+    let client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+    let mut server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+    let stream = spawn_session(client, "api-0", keepalive);
+
+    for channel in [0u8, 1] {
+        server
+            .send(Message::binary(vec![channel, 0x90, 0x1f]))
+            .await
+            .unwrap();
+    }
+    (stream, server)
+}
+
+async fn next_message(server: &mut WebSocketStream<DuplexStream>) -> Message {
+    tokio::time::timeout(Duration::from_secs(5), server.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_data_is_relayed_both_ways_after_the_port_announcements() {
+    let (mut stream, mut server) = session(KEEPALIVE).await;
+
+    server
+        .send(Message::binary(b"\0hello".to_vec()))
+        .await
+        .unwrap();
+    let mut received = [0u8; 5];
+    stream.read_exact(&mut received).await.unwrap();
+    assert_eq!(&received, b"hello");
+
+    stream.write_all(b"query").await.unwrap();
+    assert_eq!(
+        next_message(&mut server).await,
+        Message::binary(b"\0query".to_vec())
+    );
+}
+
+#[tokio::test]
+async fn test_open_session_pings_the_api_server() {
+    let (_stream, mut server) = session(Duration::from_millis(50)).await;
+
+    assert!(matches!(next_message(&mut server).await, Message::Ping(_)));
+}
+
+#[tokio::test]
+async fn test_pod_error_ends_the_stream() {
+    let (mut stream, mut server) = session(KEEPALIVE).await;
+
+    server
+        .send(Message::binary(b"\x01connection refused".to_vec()))
+        .await
+        .unwrap();
+
+    let mut received = Vec::new();
+    stream.read_to_end(&mut received).await.unwrap();
+    assert!(received.is_empty());
+}
+
+#[tokio::test]
+async fn test_closing_the_stream_closes_the_socket_but_still_delivers_pod_data() {
+    let (mut stream, mut server) = session(KEEPALIVE).await;
+
+    stream.shutdown().await.unwrap();
+    server
+        .send(Message::binary(b"\0late reply".to_vec()))
+        .await
+        .unwrap();
+    assert!(matches!(next_message(&mut server).await, Message::Close(_)));
+    // Polling again flushes the API server's reply to the close
+    let _ = tokio::time::timeout(Duration::from_secs(5), server.next()).await;
+
+    let mut received = Vec::new();
+    stream.read_to_end(&mut received).await.unwrap();
+    assert_eq!(received, b"late reply");
+}
@@ -0,0 +1,215 @@
+// Traffic shaping tests
+//
+// Tests for planning latency, bandwidth and faults per chunk, and for shaped copies
+// delaying, discarding and resetting connections.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{
+    copy_bidirectional_shaped, ChunkPlan, ForwardMetrics, TrafficShaper, TrafficShaping,
+};
+use std::io::ErrorKind;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[test]
+fn test_default_shaping_is_inactive() {
+    assert!(!TrafficShaping::default().is_active());
+    assert_eq!(TrafficShaping::default().to_string(), "off");
+    assert!(TrafficShaping::default().with_blackhole(true).is_active());
+}
+
+#[test]
+fn test_chunk_delay_adds_latency_jitter_and_transfer_time() {
+    let shaping = TrafficShaping::default()
+        .with_latency(Duration::from_millis(100))
+        .with_jitter(Duration::from_millis(50))
+        .with_bandwidth(Some(1000));
+
+    assert_eq!(shaping.chunk_delay(0, 0.0), Duration::from_millis(100));
+    assert_eq!(shaping.chunk_delay(0, 1.0), Duration::from_millis(150));
+    // 500 bytes at 1000 B/s take half a second
+    assert_eq!(shaping.chunk_delay(500, 0.5), Duration::from_millis(625));
+}
+
+#[test]
+fn test_plan_chunk_resets_below_probability() {
+    let shaping = TrafficShaping::default().with_reset_probability(0.25);
+
+    assert_eq!(shaping.plan_chunk(10, 0.1, 0.0), ChunkPlan::Reset);
+    assert_eq!(
+        shaping.plan_chunk(10, 0.25, 0.0),
+        ChunkPlan::Copy {
+            latency: Duration::ZERO,
+            transfer: Duration::ZERO
+        }
+    );
+}
+
+#[test]
+fn test_blackhole_discards_before_resetting() {
+    let shaping = TrafficShaping::default()
+        .with_reset_probability(1.0)
+        .with_blackhole(true);
+
+    assert_eq!(shaping.plan_chunk(10, 0.0, 0.0), ChunkPlan::Discard);
+}
+
+#[test]
+fn test_validate_rejects_bad_probability_and_zero_bandwidth() {
+    assert!(TrafficShaping::default()
+        .with_reset_probability(1.0)
+        .validate()
+        .is_ok());
+    assert!(TrafficShaping::default()
+        .with_reset_probability(-0.1)
+        .validate()
+        .is_err());
+    assert!(TrafficShaping::default()
+        .with_reset_probability(f64::NAN)
+        .validate()
+        .is_err());
+    assert!(TrafficShaping::default()
+        .with_bandwidth(Some(0))
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn test_display_lists_active_settings() {
+    let shaping = TrafficShaping::default()
+        .with_latency(Duration::from_millis(200))
+        .with_jitter(Duration::from_millis(50))
+        .with_bandwidth(Some(64000))
+        .with_reset_probability(0.01)
+        .with_blackhole(true);
+
+    assert_eq!(
+        shaping.to_string(),
+        "latency 200ms ±50ms, 64000 B/s, 1% resets, blackhole"
+    );
+}
+
+#[test]
+fn test_shaper_treats_inactive_settings_as_off() {
+    let shaper = TrafficShaper::new(Some(TrafficShaping::default()));
+    assert!(!shaper.is_active());
+    assert_eq!(shaper.plan_chunk(10), None);
+
+    shaper.set(Some(TrafficShaping::default().with_blackhole(true)));
+    assert!(shaper.is_active());
+    assert_eq!(shaper.plan_chunk(10), Some(ChunkPlan::Discard));
+
+    shaper.set(None);
+    assert_eq!(shaper.shaping(), None);
+}
+
+#[tokio::test]
+async fn test_shaped_copy_adds_latency() {
+    let metrics = Arc::new(ForwardMetrics::default());
+    let shaper = Arc::new(TrafficShaper::new(Some(
+        TrafficShaping::default().with_latency(Duration::from_millis(50)),
+    )));
+    let (local, mut client) = tokio::io::duplex(64);
+    let (remote, mut pod) = tokio::io::duplex(64);
+
+    let copy = tokio::spawn({
+        let metrics = Arc::clone(&metrics);
+        async move { copy_bidirectional_shaped(local, remote, &metrics, &|_, _| {}, &shaper).await }
+    });
+
+    let started = tokio::time::Instant::now();
+    client.write_all(b"ping").await.unwrap();
+    client.shutdown().await.unwrap();
+    let mut request = Vec::new();
+    pod.read_to_end(&mut request).await.unwrap();
+    assert_eq!(request, b"ping");
+    assert!(started.elapsed() >= Duration::from_millis(50));
+
+    drop(pod);
+    assert_eq!(copy.await.unwrap().unwrap(), (4, 0));
+}
+
+#[tokio::test]
+async fn test_pipelined_chunks_are_delayed_once() {
+    let metrics = Arc::new(ForwardMetrics::default());
+    let shaper = Arc::new(TrafficShaper::new(Some(
+        TrafficShaping::default().with_latency(Duration::from_millis(100)),
+    )));
+    let (local, mut client) = tokio::io::duplex(64);
+    let (remote, mut pod) = tokio::io::duplex(64);
+
+    let copy = tokio::spawn({
+        let metrics = Arc::clone(&metrics);
+        async move { copy_bidirectional_shaped(local, remote, &metrics, &|_, _| {}, &shaper).await }
+    });
+
+    let started = tokio::time::Instant::now();
+    for chunk in [b"one ", b"two ", b"six ", b"ten "] {
+        client.write_all(chunk).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    client.shutdown().await.unwrap();
+    let mut request = Vec::new();
+    pod.read_to_end(&mut request).await.unwrap();
+    assert_eq!(request, b"one two six ten ");
+    // Each chunk waits the latency from when it was read, not after the one before it
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(100));
+    assert!(elapsed < Duration::from_millis(300), "took {elapsed:?}");
+
+    drop(pod);
+    assert_eq!(copy.await.unwrap().unwrap(), (16, 0));
+}
+
+#[tokio::test]
+async fn test_blackhole_swallows_data_without_closing() {
+    let metrics = Arc::new(ForwardMetrics::default());
+    let shaper = Arc::new(TrafficShaper::new(Some(
+        TrafficShaping::default().with_blackhole(true),
+    )));
+    let (local, mut client) = tokio::io::duplex(64);
+    let (remote, mut pod) = tokio::io::duplex(64);
+
+    let copy = tokio::spawn({
+        let metrics = Arc::clone(&metrics);
+        let shaper = Arc::clone(&shaper);
+        async move { copy_bidirectional_shaped(local, remote, &metrics, &|_, _| {}, &shaper).await }
+    });
+
+    client.write_all(b"lost").await.unwrap();
+    let mut buffer = [0u8; 4];
+    let read = tokio::time::timeout(Duration::from_millis(100), pod.read(&mut buffer)).await;
+    assert!(read.is_err(), "blackholed data reached the pod");
+
+    // Lifting the blackhole lets later data through on the same connection
+    shaper.set(None);
+    client.write_all(b"seen").await.unwrap();
+    pod.read_exact(&mut buffer).await.unwrap();
+    assert_eq!(&buffer, b"seen");
+    assert_eq!(metrics.snapshot().bytes_sent, 4);
+
+    drop(client);
+    drop(pod);
+    copy.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_injected_reset_closes_both_directions() {
+    let metrics = ForwardMetrics::default();
+    let shaper = TrafficShaper::new(Some(TrafficShaping::default().with_reset_probability(1.0)));
+    let (local, mut client) = tokio::io::duplex(64);
+    let (remote, mut pod) = tokio::io::duplex(64);
+
+    client.write_all(b"ping").await.unwrap();
+    let error = copy_bidirectional_shaped(local, remote, &metrics, &|_, _| {}, &shaper)
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::ConnectionReset);
+
+    // Neither side got data, and both see the connection end
+    let mut buffer = Vec::new();
+    assert_eq!(pod.read_to_end(&mut buffer).await.unwrap(), 0);
+    assert_eq!(client.read_to_end(&mut buffer).await.unwrap(), 0);
+}
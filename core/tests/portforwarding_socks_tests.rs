@@ -0,0 +1,228 @@
+// SOCKS proxy tests
+//
+// Tests for cluster host name parsing, where the proxy may listen, and the SOCKS5
+// handshake, with tunnels replaced by local TCP connections.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{
+    parse_cluster_host, serve_socks_connection, BindAddress, ClusterService, SocksConfig,
+    TunnelStream,
+};
+use roro_core::CoreError;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+fn service(name: &str, namespace: &str) -> ClusterService {
+    ClusterService {
+        name: name.to_string(),
+        namespace: namespace.to_string(),
+    }
+}
+
+/// CONNECT request for a domain name
+fn connect_request(host: &str, port: u16) -> Vec<u8> {
+    let mut request = vec![0x05, 0x01, 0x00, 0x03, u8::try_from(host.len()).unwrap()];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    request
+}
+
+/// Start serving a client whose tunnels connect to `target`, recording what was requested
+fn serve(
+    target: Option<SocketAddr>,
+    requested: Arc<Mutex<Vec<(ClusterService, u16)>>>,
+) -> (DuplexStream, JoinHandle<std::io::Result<()>>) {
+    let (client, proxy_side) = tokio::io::duplex(64 * 1024);
+    let task = tokio::spawn(async move {
+        let connect = |service: ClusterService, port: u16| async move {
+            requested.lock().unwrap().push((service, port));
+            let Some(target) = target else {
+                return Err(CoreError::PortForwarding("no ready pods".to_string()));
+            };
+            let stream = TcpStream::connect(target)
+                .await
+                .map_err(|e| CoreError::PortForwarding(e.to_string()))?;
+            Ok(Box::new(stream) as Box<dyn TunnelStream>)
+        };
+        serve_socks_connection(proxy_side, "default", connect).await
+    });
+    (client, task)
+}
+
+/// Send the greeting and check the no-authentication method is selected
+async fn greet(client: &mut DuplexStream) {
+    client.write_all(&[0x05, 0x01, 0x00]).await.unwrap();
+    let mut choice = [0u8; 2];
+    client.read_exact(&mut choice).await.unwrap();
+    assert_eq!(choice, [0x05, 0x00]);
+}
+
+async fn read_reply_code(client: &mut DuplexStream) -> u8 {
+    let mut reply = [0u8; 10];
+    client.read_exact(&mut reply).await.unwrap();
+    assert_eq!(reply[0], 0x05);
+    reply[1]
+}
+
+#[test]
+fn test_parse_fully_qualified_service_name() {
+    assert_eq!(
+        parse_cluster_host("api.dev.svc.cluster.local", "default"),
+        Some(service("api", "dev"))
+    );
+    assert_eq!(
+        parse_cluster_host("API.Dev.svc.cluster.local.", "default"),
+        Some(service("api", "dev"))
+    );
+}
+
+#[test]
+fn test_parse_shorthand_service_names() {
+    assert_eq!(
+        parse_cluster_host("api.dev.svc", "default"),
+        Some(service("api", "dev"))
+    );
+    assert_eq!(
+        parse_cluster_host("api.dev", "default"),
+        Some(service("api", "dev"))
+    );
+    assert_eq!(
+        parse_cluster_host("api", "staging"),
+        Some(service("api", "staging"))
+    );
+}
+
+#[test]
+fn test_parse_rejects_names_outside_the_cluster() {
+    assert_eq!(parse_cluster_host("www.example.com", "default"), None);
+    assert_eq!(parse_cluster_host("api_v2.dev", "default"), None);
+    assert_eq!(parse_cluster_host("-api.dev", "default"), None);
+    assert_eq!(parse_cluster_host("", "default"), None);
+}
+
+#[tokio::test]
+async fn test_connect_is_tunnelled_to_the_resolved_service() {
+    let backend = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target = backend.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut stream, _) = backend.accept().await.unwrap();
+        let mut buffer = [0u8; 4];
+        stream.read_exact(&mut buffer).await.unwrap();
+        stream.write_all(&buffer).await.unwrap();
+    });
+
+    let requested = Arc::default();
+    let (mut client, task) = serve(Some(target), Arc::clone(&requested));
+    greet(&mut client).await;
+    client
+        .write_all(&connect_request("api.dev.svc.cluster.local", 8080))
+        .await
+        .unwrap();
+    assert_eq!(read_reply_code(&mut client).await, 0x00);
+
+    client.write_all(b"ping").await.unwrap();
+    let mut echoed = [0u8; 4];
+    client.read_exact(&mut echoed).await.unwrap();
+    assert_eq!(&echoed, b"ping");
+
+    drop(client);
+    task.await.unwrap().unwrap();
+    assert_eq!(
+        requested.lock().unwrap().as_slice(),
+        &[(service("api", "dev"), 8080)]
+    );
+}
+
+#[tokio::test]
+async fn test_unreachable_service_is_refused() {
+    let (mut client, task) = serve(None, Arc::default());
+    greet(&mut client).await;
+    client
+        .write_all(&connect_request("api.dev", 8080))
+        .await
+        .unwrap();
+
+    assert_eq!(read_reply_code(&mut client).await, 0x04);
+    task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_names_outside_the_cluster_are_refused_without_resolving() {
+    let requested = Arc::default();
+    let (mut client, task) = serve(None, Arc::clone(&requested));
+    greet(&mut client).await;
+    client
+        .write_all(&connect_request("www.example.com", 443))
+        .await
+        .unwrap();
+
+    assert_eq!(read_reply_code(&mut client).await, 0x04);
+    task.await.unwrap().unwrap();
+    assert!(requested.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_ip_destinations_are_not_supported() {
+    let (mut client, task) = serve(None, Arc::default());
+    greet(&mut client).await;
+    client
+        .write_all(&[0x05, 0x01, 0x00, 0x01, 10, 0, 0, 1, 0x1f, 0x90])
+        .await
+        .unwrap();
+
+    assert_eq!(read_reply_code(&mut client).await, 0x08);
+    task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_bind_command_is_not_supported() {
+    let (mut client, task) = serve(None, Arc::default());
+    greet(&mut client).await;
+    let mut request = connect_request("api.dev", 8080);
+    request[1] = 0x02;
+    client.write_all(&request).await.unwrap();
+
+    assert_eq!(read_reply_code(&mut client).await, 0x07);
+    task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_clients_requiring_authentication_are_rejected() {
+    let (mut client, task) = serve(None, Arc::default());
+    // Offer username/password only
+    client.write_all(&[0x05, 0x01, 0x02]).await.unwrap();
+
+    let mut choice = [0u8; 2];
+    client.read_exact(&mut choice).await.unwrap();
+    assert_eq!(choice, [0x05, 0xff]);
+    assert!(task.await.unwrap().is_err());
+}
+
+#[test]
+fn test_exposed_bind_address_requires_expose_to_network() {
+    for bind in ["0.0.0.0", "192.168.1.20"] {
+        let mut config = SocksConfig {
+            bind_address: bind.parse::<BindAddress>().unwrap(),
+            ..SocksConfig::default()
+        };
+        assert!(
+            matches!(config.validate(), Err(CoreError::Validation(_))),
+            "{bind} should need exposeToNetwork"
+        );
+
+        config.expose_to_network = true;
+        assert!(config.validate().is_ok());
+    }
+
+    for bind in ["localhost", "127.0.0.1", "::1"] {
+        let config = SocksConfig {
+            bind_address: bind.parse::<BindAddress>().unwrap(),
+            ..SocksConfig::default()
+        };
+        assert!(config.validate().is_ok(), "{bind} should be allowed");
+    }
+}
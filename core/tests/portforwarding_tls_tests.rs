@@ -0,0 +1,142 @@
+// Local TLS termination tests
+//
+// Tests for the local CA and the certificates it issues, checked by real TLS handshakes.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_core::api::kubernetes::portforwarding::{is_permitted_host, LocalCa};
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsConnector;
+
+/// Serve one TLS connection that echoes a line, and connect to it as `server_name`
+async fn handshake(server: &Arc<LocalCa>, trusted: &LocalCa, server_name: &str) -> Vec<u8> {
+    let acceptor = server.acceptor().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let serve = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let Ok(mut tls) = acceptor.accept(stream).await else {
+            return;
+        };
+        let mut buffer = [0u8; 5];
+        tls.read_exact(&mut buffer).await.unwrap();
+        tls.write_all(&buffer).await.unwrap();
+        tls.shutdown().await.unwrap();
+    });
+
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from(trusted.certificate_der().to_vec()))
+        .unwrap();
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(config));
+
+    let stream = TcpStream::connect(address).await.unwrap();
+    let name = ServerName::try_from(server_name.to_string()).unwrap();
+    let result = match connector.connect(name, stream).await {
+        Ok(mut tls) => {
+            tls.write_all(b"hello").await.unwrap();
+            let mut echoed = Vec::new();
+            tls.read_to_end(&mut echoed).await.unwrap();
+            echoed
+        }
+        Err(_) => Vec::new(),
+    };
+    serve.abort();
+    result
+}
+
+#[tokio::test]
+async fn test_localhost_certificate_is_trusted() {
+    let ca = Arc::new(LocalCa::generate().unwrap());
+
+    assert_eq!(handshake(&ca, &ca, "localhost").await, b"hello");
+}
+
+#[tokio::test]
+async fn test_ip_address_connections_are_trusted() {
+    let ca = Arc::new(LocalCa::generate().unwrap());
+
+    assert_eq!(handshake(&ca, &ca, "127.0.0.1").await, b"hello");
+}
+
+#[tokio::test]
+async fn test_certificates_are_issued_per_host() {
+    let ca = Arc::new(LocalCa::generate().unwrap());
+
+    assert_eq!(handshake(&ca, &ca, "api.myapp.localhost").await, b"hello");
+    assert_eq!(
+        handshake(&ca, &ca, "api.dev.svc.cluster.local").await,
+        b"hello"
+    );
+}
+
+#[test]
+fn test_ca_only_issues_for_local_names() {
+    let ca = LocalCa::generate().unwrap();
+
+    for host in [
+        "localhost",
+        "api.localhost",
+        "api.dev.svc",
+        "127.0.0.2",
+        "::1",
+    ] {
+        assert!(is_permitted_host(host), "{host} should be permitted");
+    }
+    for host in ["example.com", "notlocalhost", "192.168.1.10", "api.dev"] {
+        assert!(!is_permitted_host(host), "{host} should not be permitted");
+    }
+    assert!(ca.issue("example.com").is_err());
+    // The CA certificate carries the nameConstraints extension (OID 2.5.29.30)
+    assert!(ca
+        .certificate_der()
+        .windows(3)
+        .any(|oid| oid == [0x55, 0x1d, 0x1e]));
+}
+
+#[tokio::test]
+async fn test_certificate_outside_the_name_constraints_is_rejected() {
+    let ca = Arc::new(LocalCa::generate().unwrap());
+
+    assert!(handshake(&ca, &ca, "example.com").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_other_ca_is_rejected() {
+    let ca = Arc::new(LocalCa::generate().unwrap());
+    let other = LocalCa::generate().unwrap();
+
+    assert!(handshake(&ca, &other, "localhost").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_ca_reloaded_from_pem_keeps_issuing_trusted_certificates() {
+    let original = LocalCa::generate().unwrap();
+    let pem = original.certificate_pem();
+    assert!(pem.starts_with("-----BEGIN CERTIFICATE-----"));
+    assert!(original.key_pem().contains("BEGIN PRIVATE KEY"));
+
+    let reloaded = Arc::new(LocalCa::from_pem(&pem, &original.key_pem()).unwrap());
+
+    assert_eq!(reloaded.certificate_der(), original.certificate_der());
+    assert_eq!(handshake(&reloaded, &original, "localhost").await, b"hello");
+}
+
+#[test]
+fn test_from_pem_rejects_swapped_files() {
+    let ca = LocalCa::generate().unwrap();
+
+    assert!(LocalCa::from_pem(&ca.key_pem(), &ca.certificate_pem()).is_err());
+    assert!(LocalCa::from_pem("not a pem", &ca.key_pem()).is_err());
+}
@@ -0,0 +1,242 @@
+// Workload port forward tests
+//
+// Tests for picking a pod among a workload's pods, translating workload selectors, mapping
+// named container ports and rotating round-robin connections across ready pods.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+mod common;
+
+use common::running_pod;
+use k8s_openapi::api::core::v1::{Container, ContainerPort, Pod, PodSpec};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{
+    LabelSelector, LabelSelectorRequirement, Time,
+};
+use roro_core::api::kubernetes::portforwarding::{
+    container_port, ready_pod_names, select_pod, selector_query, stateful_set_pod_name,
+    ForwardTarget, PortForwardingConfig, PortForwardingState, PortForwardingStatus,
+};
+use roro_core::errors::CoreError;
+use roro_domain::{PodSelection, PortValue, ResourceKind};
+
+/// Creation time `minute` minutes after midnight, built through serde to stay independent
+/// of the date-time library behind `Time`
+fn created_at(minute: u32) -> Time {
+    serde_json::from_value(serde_json::json!(format!(
+        "2024-01-01T{:02}:{:02}:00Z",
+        minute / 60,
+        minute % 60
+    )))
+    .unwrap()
+}
+
+fn pod(name: &str, ready: bool, created: u32) -> Pod {
+    let mut pod = running_pod(name, ready);
+    pod.metadata.creation_timestamp = Some(created_at(created));
+    pod
+}
+
+fn round_robin_state(ready_pods: &[&str]) -> PortForwardingState {
+    let mut state = PortForwardingState::new(
+        "dev-api-aaaaa-8080",
+        "dev",
+        PortForwardingConfig {
+            namespace: "default".to_string(),
+            pod: "api-aaaaa".to_string(),
+            remote_port: 8080,
+            local_port: 8080,
+            instance_id: "dev".to_string(),
+            target: ForwardTarget::Workload {
+                kind: ResourceKind::Deployment,
+                name: "api".to_string(),
+                selection: PodSelection::RoundRobin,
+                ordinal: None,
+            },
+            ..Default::default()
+        },
+    );
+    state.status = PortForwardingStatus::Active;
+    state.ready_pods = ready_pods.iter().map(ToString::to_string).collect();
+    state
+}
+
+#[test]
+fn test_first_ready_skips_unready_pods() {
+    let pods = [
+        pod("api-aaaaa", false, 100),
+        pod("api-ccccc", true, 300),
+        pod("api-bbbbb", true, 200),
+    ];
+
+    assert_eq!(
+        select_pod(&pods, PodSelection::FirstReady).as_deref(),
+        Some("api-bbbbb")
+    );
+    assert_eq!(
+        select_pod(&pods, PodSelection::RoundRobin).as_deref(),
+        Some("api-bbbbb")
+    );
+}
+
+#[test]
+fn test_newest_picks_latest_ready_pod() {
+    let pods = [
+        pod("api-aaaaa", true, 300),
+        pod("api-bbbbb", true, 100),
+        pod("api-ccccc", false, 500),
+    ];
+
+    assert_eq!(
+        select_pod(&pods, PodSelection::Newest).as_deref(),
+        Some("api-aaaaa")
+    );
+}
+
+#[test]
+fn test_newest_breaks_ties_by_name() {
+    let pods = [pod("api-ccccc", true, 100), pod("api-bbbbb", true, 100)];
+
+    assert_eq!(
+        select_pod(&pods, PodSelection::Newest).as_deref(),
+        Some("api-bbbbb")
+    );
+}
+
+#[test]
+fn test_no_ready_pods_selects_nothing() {
+    let pods = [pod("api-aaaaa", false, 100)];
+
+    assert_eq!(select_pod(&pods, PodSelection::FirstReady), None);
+    assert_eq!(select_pod(&[], PodSelection::Newest), None);
+}
+
+#[test]
+fn test_ready_pod_names_are_sorted() {
+    let pods = [
+        pod("db-2", true, 0),
+        pod("db-0", true, 0),
+        pod("db-1", false, 0),
+    ];
+
+    assert_eq!(ready_pod_names(&pods), vec!["db-0", "db-2"]);
+}
+
+#[test]
+fn test_terminating_pods_are_not_selected() {
+    let mut terminating = pod("api-bbbbb", true, 200);
+    terminating.metadata.deletion_timestamp = Some(created_at(300));
+    let pods = [pod("api-aaaaa", true, 100), terminating];
+
+    assert_eq!(
+        select_pod(&pods, PodSelection::Newest),
+        Some("api-aaaaa".to_string())
+    );
+    assert_eq!(ready_pod_names(&pods), vec!["api-aaaaa"]);
+}
+
+#[test]
+fn test_stateful_set_pod_name() {
+    assert_eq!(stateful_set_pod_name("db", 0), "db-0");
+    assert_eq!(stateful_set_pod_name("db", 12), "db-12");
+}
+
+fn requirement(key: &str, operator: &str, values: &[&str]) -> LabelSelectorRequirement {
+    LabelSelectorRequirement {
+        key: key.to_string(),
+        operator: operator.to_string(),
+        values: (!values.is_empty()).then(|| values.iter().map(ToString::to_string).collect()),
+    }
+}
+
+#[test]
+fn test_selector_query_translates_match_expressions() {
+    let selector = LabelSelector {
+        match_labels: None,
+        match_expressions: Some(vec![
+            requirement("tier", "In", &["api", "web"]),
+            requirement("env", "NotIn", &["dev"]),
+            requirement("app", "Exists", &[]),
+            requirement("canary", "DoesNotExist", &[]),
+        ]),
+    };
+    assert_eq!(
+        selector_query(&selector).unwrap().as_deref(),
+        Some("tier in (api,web),env notin (dev),app,!canary")
+    );
+}
+
+#[test]
+fn test_selector_query_combines_labels_and_expressions() {
+    let selector = LabelSelector {
+        match_labels: Some([("app".to_string(), "api".to_string())].into()),
+        match_expressions: Some(vec![requirement("env", "In", &["prod"])]),
+    };
+    assert_eq!(
+        selector_query(&selector).unwrap().as_deref(),
+        Some("app=api,env in (prod)")
+    );
+}
+
+#[test]
+fn test_selector_query_rejects_empty_selectors_and_unknown_operators() {
+    assert_eq!(selector_query(&LabelSelector::default()).unwrap(), None);
+
+    let selector = LabelSelector {
+        match_labels: None,
+        match_expressions: Some(vec![requirement("app", "Matches", &["api"])]),
+    };
+    assert!(matches!(
+        selector_query(&selector),
+        Err(CoreError::PortForwarding(_))
+    ));
+}
+
+#[test]
+fn test_container_port_maps_names_and_passes_numbers() {
+    let mut api = pod("api-aaaaa", true, 0);
+    api.spec = Some(PodSpec {
+        containers: vec![Container {
+            name: "api".to_string(),
+            ports: Some(vec![ContainerPort {
+                name: Some("http".to_string()),
+                container_port: 8080,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+
+    assert_eq!(
+        container_port(&api, &PortValue::Named("http".to_string())).ok(),
+        Some(8080)
+    );
+    assert_eq!(
+        container_port(&api, &PortValue::Numeric(9090)).ok(),
+        Some(9090)
+    );
+    assert!(container_port(&api, &PortValue::Named("grpc".to_string())).is_err());
+}
+
+#[test]
+fn test_round_robin_rotates_across_ready_pods() {
+    let state = round_robin_state(&["api-aaaaa", "api-bbbbb", "api-ccccc"]);
+
+    let pods: Vec<&str> = (0..4).map(|turn| state.connection_pod(turn)).collect();
+    assert_eq!(pods, ["api-aaaaa", "api-bbbbb", "api-ccccc", "api-aaaaa"]);
+}
+
+#[test]
+fn test_round_robin_falls_back_to_current_pod() {
+    let state = round_robin_state(&[]);
+    assert_eq!(state.connection_pod(3), "api-aaaaa");
+
+    let mut first_ready = round_robin_state(&["api-aaaaa", "api-bbbbb"]);
+    first_ready.config.target = ForwardTarget::Workload {
+        kind: ResourceKind::Deployment,
+        name: "api".to_string(),
+        selection: PodSelection::FirstReady,
+        ordinal: None,
+    };
+    assert_eq!(first_ready.connection_pod(1), "api-aaaaa");
+}
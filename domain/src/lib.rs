@@ -12,6 +12,7 @@ pub use errors::DomainError;
 pub use handlers::{HandlerRegistry, OperationHandler};
 pub use processor::DomainProcessor;
 pub use types::{
-    AppConfig, DomainEntity, EntityState, PortForwardingConfig, PortValue, ProcessingContext,
-    ProcessingResult,
+    AppConfig, DomainEntity, EntityState, ForwardTransport, HealthCheckConfig, LocalPortPolicy,
+    PodSelection, PortForwardingConfig, PortValue, ProcessingContext, ProcessingResult,
+    ResourceKind,
 };
@@ -0,0 +1,74 @@
+// Health check configuration
+//
+// This module defines the HealthCheckConfig probe declared per port forward in app.json.
+
+use crate::errors::DomainError;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_EXPECTED_STATUS: u16 = 200;
+const DEFAULT_TIMEOUT_SECONDS: u64 = 5;
+
+/// Probe run through a port forward's tunnel to check the remote port is serving
+///
+/// Without a `path` the probe only checks that a stream to the remote port can be opened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheckConfig {
+    /// HTTP path to request (e.g., "/healthz")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// HTTP status the probe must return
+    #[serde(rename = "expectedStatus", default = "default_expected_status")]
+    pub expected_status: u16,
+    /// Seconds before the probe is considered failed
+    #[serde(rename = "timeoutSeconds", default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_expected_status() -> u16 {
+    DEFAULT_EXPECTED_STATUS
+}
+
+fn default_timeout_seconds() -> u64 {
+    DEFAULT_TIMEOUT_SECONDS
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            expected_status: DEFAULT_EXPECTED_STATUS,
+            timeout_seconds: DEFAULT_TIMEOUT_SECONDS,
+        }
+    }
+}
+
+impl HealthCheckConfig {
+    /// Validate the health check configuration
+    ///
+    /// # Errors
+    /// Returns `DomainError::PortForwardingValidation` if validation fails
+    pub fn validate(&self) -> Result<(), DomainError> {
+        if let Some(path) = &self.path {
+            if !path.starts_with('/') {
+                return Err(DomainError::PortForwardingValidation(format!(
+                    "healthCheck.path '{path}' must start with '/'"
+                )));
+            }
+        }
+
+        if !(100..=599).contains(&self.expected_status) {
+            return Err(DomainError::PortForwardingValidation(format!(
+                "healthCheck.expectedStatus {} must be an HTTP status code",
+                self.expected_status
+            )));
+        }
+
+        if self.timeout_seconds == 0 {
+            return Err(DomainError::PortForwardingValidation(
+                "healthCheck.timeoutSeconds cannot be 0".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
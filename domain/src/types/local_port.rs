@@ -0,0 +1,80 @@
+// Local port policy
+//
+// This module defines how a port forward's `localport` value chooses a local port.
+
+use crate::errors::DomainError;
+use std::fmt;
+use std::str::FromStr;
+
+/// How a port forward picks its local port, parsed from `localport`
+///
+/// Accepted forms:
+/// - `"8080"`: exactly this port
+/// - `"8080+"`: this port, or the next free port above it
+/// - `"30000-30100"`: any free port in the inclusive range
+/// - `"auto"`: any free port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalPortPolicy {
+    Fixed(u16),
+    Preferred(u16),
+    Range(u16, u16),
+    Auto,
+}
+
+fn parse_port(value: &str, localport: &str) -> Result<u16, DomainError> {
+    match value.trim().parse::<u16>() {
+        Ok(0) => Err(DomainError::PortForwardingValidation(
+            "localport cannot be 0".to_string(),
+        )),
+        Ok(port) => Ok(port),
+        Err(_) => Err(DomainError::PortForwardingValidation(format!(
+            "localport '{localport}' must be a valid port number (1-65535), a range like \
+             '30000-30100', a preferred port like '8080+', or 'auto'"
+        ))),
+    }
+}
+
+impl FromStr for LocalPortPolicy {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        if value.is_empty() {
+            return Err(DomainError::PortForwardingValidation(
+                "localport cannot be empty".to_string(),
+            ));
+        }
+
+        if value.eq_ignore_ascii_case("auto") {
+            return Ok(Self::Auto);
+        }
+
+        if let Some(preferred) = value.strip_suffix('+') {
+            return parse_port(preferred, s).map(Self::Preferred);
+        }
+
+        if let Some((start, end)) = value.split_once('-') {
+            let start = parse_port(start, s)?;
+            let end = parse_port(end, s)?;
+            if start > end {
+                return Err(DomainError::PortForwardingValidation(format!(
+                    "localport range '{s}' must start at or below its end"
+                )));
+            }
+            return Ok(Self::Range(start, end));
+        }
+
+        parse_port(value, s).map(Self::Fixed)
+    }
+}
+
+impl fmt::Display for LocalPortPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(port) => write!(f, "{port}"),
+            Self::Preferred(port) => write!(f, "{port}+"),
+            Self::Range(start, end) => write!(f, "{start}-{end}"),
+            Self::Auto => f.write_str("auto"),
+        }
+    }
+}
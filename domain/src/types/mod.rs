@@ -4,10 +4,16 @@
 
 mod app_config;
 mod entity;
+mod health_check;
+mod local_port;
 mod port;
 mod port_forwarding;
+mod resource;
 
 pub use app_config::AppConfig;
 pub use entity::{DomainEntity, EntityState, ProcessingContext, ProcessingResult};
+pub use health_check::HealthCheckConfig;
+pub use local_port::LocalPortPolicy;
 pub use port::PortValue;
 pub use port_forwarding::PortForwardingConfig;
+pub use resource::{ForwardTransport, PodSelection, ResourceKind};
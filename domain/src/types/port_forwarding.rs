@@ -3,21 +3,74 @@
 // This module defines the PortForwardingConfig type and its validation.
 
 use crate::errors::DomainError;
+use crate::types::health_check::HealthCheckConfig;
+use crate::types::local_port::LocalPortPolicy;
 use crate::types::port::PortValue;
+use crate::types::resource::{ForwardTransport, PodSelection, ResourceKind};
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 
-/// Port forwarding configuration for a Kubernetes service
+/// Port forwarding configuration for a Kubernetes service or workload
+// The flags are independent app.json keys rather than states of one setting
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortForwardingConfig {
-    /// Local port to forward to
+    /// Local port to forward to: a port, a range, a preferred port, or "auto"
+    /// (see `LocalPortPolicy`)
     #[serde(rename = "localport")]
     pub local_port: String,
-    /// Name of the Kubernetes service
+    /// Name of the Kubernetes resource
     pub name: String,
-    /// Port on the service (can be numeric or named)
+    /// Port on the service, or container port of a workload's pods (can be numeric or named)
     pub port: PortValue,
-    /// Kind of Kubernetes resource (e.g., "service")
+    /// Kind of Kubernetes resource: "service", "deployment", "statefulset", "daemonset" or
+    /// "pod" (see `ResourceKind`)
     pub kind: String,
+    /// Probe run through the tunnel to check the forward is serving
+    #[serde(
+        rename = "healthCheck",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub health_check: Option<HealthCheckConfig>,
+    /// Local address to listen on: "localhost" (IPv4 and IPv6 loopback) or an IP address
+    #[serde(
+        rename = "bindAddress",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub bind_address: Option<String>,
+    /// Opt-in required to listen on all interfaces ("0.0.0.0" or "::")
+    #[serde(rename = "exposeToNetwork", default)]
+    pub expose_to_network: bool,
+    /// Serve HTTPS locally with a certificate from the local CA, forwarding plaintext
+    #[serde(default)]
+    pub tls: bool,
+    /// Listen on a loopback address of the service's own (e.g. 127.1.0.1) on the service
+    /// port, so several services can use the same port; `localport` is then ignored
+    #[serde(rename = "dedicatedAddress", default)]
+    pub dedicated_address: bool,
+    /// How a ready pod is picked among a workload's pods; defaults to the first ready pod
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<PodSelection>,
+    /// Forward to one `StatefulSet` pod by ordinal, e.g. 0 for `db-0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ordinal: Option<u32>,
+    /// Bind the local port right away but open the tunnel only when the first connection
+    /// arrives
+    #[serde(default)]
+    pub lazy: bool,
+    /// Seconds without connections after which a lazy forward closes its tunnel again
+    #[serde(
+        rename = "idleTimeout",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub idle_timeout: Option<u64>,
+    /// How connections reach the service or pod; defaults to port-forwarding with the
+    /// service proxy as fallback for services
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<ForwardTransport>,
 }
 
 impl PortForwardingConfig {
@@ -26,26 +79,8 @@ impl PortForwardingConfig {
     /// # Errors
     /// Returns `DomainError::PortForwardingValidation` if validation fails
     pub fn validate(&self) -> Result<(), DomainError> {
-        // Validate local_port is not empty
-        if self.local_port.is_empty() {
-            return Err(DomainError::PortForwardingValidation(
-                "localport cannot be empty".to_string(),
-            ));
-        }
-
-        // Validate local_port is a valid port number if it's numeric
-        if let Ok(port_num) = self.local_port.parse::<u16>() {
-            if port_num == 0 {
-                return Err(DomainError::PortForwardingValidation(
-                    "localport cannot be 0".to_string(),
-                ));
-            }
-        } else {
-            return Err(DomainError::PortForwardingValidation(format!(
-                "localport '{}' must be a valid port number (1-65535)",
-                self.local_port
-            )));
-        }
+        // Validate local_port is a port, range, preferred port, or "auto"
+        self.local_port_policy()?;
 
         // Validate name is not empty
         if self.name.is_empty() {
@@ -60,6 +95,113 @@ impl PortForwardingConfig {
                 "kind cannot be empty".to_string(),
             ));
         }
+        let kind = self.resource_kind()?;
+        self.validate_pod_selection(kind)?;
+
+        if let Some(health_check) = &self.health_check {
+            health_check.validate()?;
+        }
+
+        if let Some(bind_address) = &self.bind_address {
+            if self.dedicated_address {
+                return Err(DomainError::PortForwardingValidation(
+                    "bindAddress cannot be combined with dedicatedAddress".to_string(),
+                ));
+            }
+            Self::validate_bind_address(bind_address, self.expose_to_network)?;
+        }
+
+        if self.dedicated_address && kind != ResourceKind::Service {
+            return Err(DomainError::PortForwardingValidation(format!(
+                "dedicatedAddress is only supported for services, not kind '{kind}'"
+            )));
+        }
+
+        if self.transport == Some(ForwardTransport::ServiceProxy) && kind != ResourceKind::Service {
+            return Err(DomainError::PortForwardingValidation(format!(
+                "transport serviceProxy is only supported for services, not kind '{kind}'"
+            )));
+        }
+
+        match self.idle_timeout {
+            Some(_) if !self.lazy => {
+                return Err(DomainError::PortForwardingValidation(
+                    "idleTimeout requires lazy".to_string(),
+                ));
+            }
+            Some(0) => {
+                return Err(DomainError::PortForwardingValidation(
+                    "idleTimeout must be at least 1 second".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Parse `kind` into the resource kind the forward targets
+    ///
+    /// # Errors
+    /// Returns `DomainError::PortForwardingValidation` if `kind` is not a supported kind
+    pub fn resource_kind(&self) -> Result<ResourceKind, DomainError> {
+        self.kind.parse()
+    }
+
+    /// Parse `localport` into the policy used to choose the local port
+    ///
+    /// # Errors
+    /// Returns `DomainError::PortForwardingValidation` if `localport` is not a valid policy
+    pub fn local_port_policy(&self) -> Result<LocalPortPolicy, DomainError> {
+        self.local_port.parse()
+    }
+
+    fn validate_pod_selection(&self, kind: ResourceKind) -> Result<(), DomainError> {
+        if self.selection.is_some() && !kind.selects_pods() {
+            return Err(DomainError::PortForwardingValidation(format!(
+                "selection is not supported for kind '{kind}'"
+            )));
+        }
+
+        if self.ordinal.is_some() {
+            if kind != ResourceKind::StatefulSet {
+                return Err(DomainError::PortForwardingValidation(format!(
+                    "ordinal is only supported for statefulsets, not kind '{kind}'"
+                )));
+            }
+            if self.selection.is_some() {
+                return Err(DomainError::PortForwardingValidation(
+                    "ordinal cannot be combined with selection".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_bind_address(
+        bind_address: &str,
+        expose_to_network: bool,
+    ) -> Result<(), DomainError> {
+        if bind_address.eq_ignore_ascii_case("localhost") {
+            return Ok(());
+        }
+
+        let ip = bind_address
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .map_err(|_| {
+                DomainError::PortForwardingValidation(format!(
+                    "bindAddress '{bind_address}' must be \"localhost\" or an IP address"
+                ))
+            })?;
+
+        if ip.is_unspecified() && !expose_to_network {
+            return Err(DomainError::PortForwardingValidation(format!(
+                "bindAddress '{bind_address}' listens on all interfaces and requires exposeToNetwork"
+            )));
+        }
 
         Ok(())
     }
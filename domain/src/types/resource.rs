@@ -0,0 +1,96 @@
+// Port forward resources
+//
+// This module defines the kinds of Kubernetes resource a port forward can target and how a
+// ready pod is picked among the pods of a workload.
+
+use crate::errors::DomainError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Kind of resource a port forward targets, parsed case-insensitively from `kind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceKind {
+    /// A service, forwarded to a ready backing pod through its `targetPort`
+    Service,
+    /// A Deployment's pods, found through its selector
+    Deployment,
+    /// A `StatefulSet`'s pods, or one of them by ordinal
+    StatefulSet,
+    /// A `DaemonSet`'s pods, found through its selector
+    DaemonSet,
+    /// A pod matched by exact name, or pods whose names start with `name`
+    Pod,
+}
+
+impl ResourceKind {
+    /// Whether the kind selects among several pods, so `selection` applies to it
+    #[must_use]
+    pub fn selects_pods(self) -> bool {
+        !matches!(self, Self::Service)
+    }
+}
+
+impl FromStr for ResourceKind {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "service" => Ok(Self::Service),
+            "deployment" => Ok(Self::Deployment),
+            "statefulset" => Ok(Self::StatefulSet),
+            "daemonset" => Ok(Self::DaemonSet),
+            "pod" => Ok(Self::Pod),
+            _ => Err(DomainError::PortForwardingValidation(format!(
+                "kind '{s}' must be one of service, deployment, statefulset, daemonset or pod"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Service => "service",
+            Self::Deployment => "deployment",
+            Self::StatefulSet => "statefulset",
+            Self::DaemonSet => "daemonset",
+            Self::Pod => "pod",
+        })
+    }
+}
+
+/// How a forward picks among the ready pods of a workload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PodSelection {
+    /// The first ready pod by name, kept until it goes away
+    #[default]
+    FirstReady,
+    /// The most recently created ready pod, e.g. the one from the latest rollout
+    Newest,
+    /// Each new connection goes to the next ready pod in turn
+    RoundRobin,
+}
+
+/// How a forward's connections reach the cluster
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ForwardTransport {
+    /// Port-forward tunnels, switching service forwards to the service proxy when the
+    /// cluster forbids `pods/portforward`
+    #[default]
+    Auto,
+    /// Port-forward tunnels only
+    PortForward,
+    /// HTTP requests through the API server's `services/proxy` subresource; services only
+    ServiceProxy,
+}
+
+impl ForwardTransport {
+    #[must_use]
+    pub fn is_auto(&self) -> bool {
+        *self == Self::Auto
+    }
+}
@@ -0,0 +1,104 @@
+// Bind address configuration tests
+//
+// Tests for validating per-forward bind addresses, dedicated addresses and network exposure
+// opt-in.
+
+use roro_domain::{DomainError, PortForwardingConfig, PortValue};
+
+fn config(bind_address: &str, expose_to_network: bool) -> PortForwardingConfig {
+    PortForwardingConfig {
+        local_port: "8080".to_string(),
+        name: "api".to_string(),
+        port: PortValue::Numeric(80),
+        kind: "service".to_string(),
+        health_check: None,
+        bind_address: Some(bind_address.to_string()),
+        expose_to_network,
+        tls: false,
+        dedicated_address: false,
+        selection: None,
+        ordinal: None,
+        lazy: false,
+        idle_timeout: None,
+        transport: None,
+    }
+}
+
+#[test]
+fn test_bind_address_accepts_loopback_and_interfaces() {
+    assert!(config("localhost", false).validate().is_ok());
+    assert!(config("::1", false).validate().is_ok());
+    assert!(config("127.0.0.1", false).validate().is_ok());
+    assert!(config("192.168.1.20", false).validate().is_ok());
+}
+
+#[test]
+fn test_bind_address_rejects_invalid_address() {
+    let result = config("my-laptop", false).validate();
+
+    if let Err(DomainError::PortForwardingValidation(msg)) = result {
+        assert!(msg.contains("must be \"localhost\" or an IP address"));
+    } else {
+        panic!("Expected PortForwardingValidation error");
+    }
+}
+
+#[test]
+fn test_bind_address_all_interfaces_requires_opt_in() {
+    assert!(config("0.0.0.0", false).validate().is_err());
+    assert!(config("::", false).validate().is_err());
+    assert!(config("0.0.0.0", true).validate().is_ok());
+}
+
+#[test]
+fn test_bind_address_parses_from_json() {
+    let Ok(config) = serde_json::from_str::<PortForwardingConfig>(
+        r#"{
+            "localport": "8080",
+            "name": "api",
+            "port": 80,
+            "kind": "service",
+            "bindAddress": "0.0.0.0",
+            "exposeToNetwork": true
+        }"#,
+    ) else {
+        panic!("Failed to parse port forwarding config");
+    };
+
+    assert_eq!(config.bind_address.as_deref(), Some("0.0.0.0"));
+    assert!(config.expose_to_network);
+}
+
+#[test]
+fn test_dedicated_address_excludes_bind_address() {
+    let mut with_bind = config("127.0.0.1", false);
+    with_bind.dedicated_address = true;
+
+    if let Err(DomainError::PortForwardingValidation(msg)) = with_bind.validate() {
+        assert!(msg.contains("dedicatedAddress"));
+    } else {
+        panic!("Expected PortForwardingValidation error");
+    }
+
+    let mut dedicated = config("localhost", false);
+    dedicated.bind_address = None;
+    dedicated.dedicated_address = true;
+    assert!(dedicated.validate().is_ok());
+}
+
+#[test]
+fn test_dedicated_address_parses_from_json() {
+    let Ok(config) = serde_json::from_str::<PortForwardingConfig>(
+        r#"{
+            "localport": "auto",
+            "name": "postgres",
+            "port": 5432,
+            "kind": "service",
+            "dedicatedAddress": true
+        }"#,
+    ) else {
+        panic!("Failed to parse port forwarding config");
+    };
+
+    assert!(config.dedicated_address);
+}
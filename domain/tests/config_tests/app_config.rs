@@ -79,12 +79,32 @@ fn test_app_config_validation_with_port_forward() {
                 name: "api-service".to_string(),
                 port: PortValue::Numeric(5555),
                 kind: "service".to_string(),
+                health_check: None,
+                bind_address: None,
+                expose_to_network: false,
+                tls: false,
+                dedicated_address: false,
+                selection: None,
+                ordinal: None,
+                lazy: false,
+                idle_timeout: None,
+                transport: None,
             },
             PortForwardingConfig {
                 local_port: "2222".to_string(),
                 name: "metrics-service".to_string(),
                 port: PortValue::Named("prometheus".to_string()),
                 kind: "service".to_string(),
+                health_check: None,
+                bind_address: None,
+                expose_to_network: false,
+                tls: false,
+                dedicated_address: false,
+                selection: None,
+                ordinal: None,
+                lazy: false,
+                idle_timeout: None,
+                transport: None,
             },
         ],
     };
@@ -103,6 +123,16 @@ fn test_app_config_validation_invalid_port_forward() {
             name: "api-service".to_string(),
             port: PortValue::Numeric(5555),
             kind: "service".to_string(),
+            health_check: None,
+            bind_address: None,
+            expose_to_network: false,
+            tls: false,
+            dedicated_address: false,
+            selection: None,
+            ordinal: None,
+            lazy: false,
+            idle_timeout: None,
+            transport: None,
         }],
     };
 
@@ -190,6 +220,16 @@ fn test_app_config_serialize() {
             name: "api-service".to_string(),
             port: PortValue::Numeric(5555),
             kind: "service".to_string(),
+            health_check: None,
+            bind_address: None,
+            expose_to_network: false,
+            tls: false,
+            dedicated_address: false,
+            selection: None,
+            ordinal: None,
+            lazy: false,
+            idle_timeout: None,
+            transport: None,
         }],
     };
 
@@ -212,12 +252,32 @@ fn test_app_config_round_trip() {
                 name: "api-service".to_string(),
                 port: PortValue::Numeric(5555),
                 kind: "service".to_string(),
+                health_check: None,
+                bind_address: None,
+                expose_to_network: false,
+                tls: false,
+                dedicated_address: false,
+                selection: None,
+                ordinal: None,
+                lazy: false,
+                idle_timeout: None,
+                transport: None,
             },
             PortForwardingConfig {
                 local_port: "2222".to_string(),
                 name: "metrics-service".to_string(),
                 port: PortValue::Named("prometheus".to_string()),
                 kind: "service".to_string(),
+                health_check: None,
+                bind_address: None,
+                expose_to_network: false,
+                tls: false,
+                dedicated_address: false,
+                selection: None,
+                ordinal: None,
+                lazy: false,
+                idle_timeout: None,
+                transport: None,
             },
         ],
     };
@@ -245,4 +305,3 @@ fn test_app_config_round_trip() {
         assert_eq!(orig.kind, deser.kind);
     }
 }
-
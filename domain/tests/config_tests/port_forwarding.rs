@@ -2,7 +2,7 @@
 //
 // Tests for PortForwardingConfig creation and validation.
 
-use roro_domain::{DomainError, PortForwardingConfig, PortValue};
+use roro_domain::{DomainError, ForwardTransport, PortForwardingConfig, PortValue};
 
 #[test]
 fn test_port_forward_config_creation() {
@@ -11,6 +11,16 @@ fn test_port_forward_config_creation() {
         name: "api-service".to_string(),
         port: PortValue::Numeric(5555),
         kind: "service".to_string(),
+        health_check: None,
+        bind_address: None,
+        expose_to_network: false,
+        tls: false,
+        dedicated_address: false,
+        selection: None,
+        ordinal: None,
+        lazy: false,
+        idle_timeout: None,
+        transport: None,
     };
 
     assert_eq!(config.local_port, "3333");
@@ -26,6 +36,16 @@ fn test_port_forward_config_validation_success() {
         name: "api-service".to_string(),
         port: PortValue::Numeric(5555),
         kind: "service".to_string(),
+        health_check: None,
+        bind_address: None,
+        expose_to_network: false,
+        tls: false,
+        dedicated_address: false,
+        selection: None,
+        ordinal: None,
+        lazy: false,
+        idle_timeout: None,
+        transport: None,
     };
 
     assert!(config.validate().is_ok());
@@ -38,6 +58,16 @@ fn test_port_forward_config_validation_empty_localport() {
         name: "api-service".to_string(),
         port: PortValue::Numeric(5555),
         kind: "service".to_string(),
+        health_check: None,
+        bind_address: None,
+        expose_to_network: false,
+        tls: false,
+        dedicated_address: false,
+        selection: None,
+        ordinal: None,
+        lazy: false,
+        idle_timeout: None,
+        transport: None,
     };
 
     let result = config.validate();
@@ -56,6 +86,16 @@ fn test_port_forward_config_validation_zero_localport() {
         name: "api-service".to_string(),
         port: PortValue::Numeric(5555),
         kind: "service".to_string(),
+        health_check: None,
+        bind_address: None,
+        expose_to_network: false,
+        tls: false,
+        dedicated_address: false,
+        selection: None,
+        ordinal: None,
+        lazy: false,
+        idle_timeout: None,
+        transport: None,
     };
 
     let result = config.validate();
@@ -74,6 +114,16 @@ fn test_port_forward_config_validation_invalid_localport() {
         name: "api-service".to_string(),
         port: PortValue::Numeric(5555),
         kind: "service".to_string(),
+        health_check: None,
+        bind_address: None,
+        expose_to_network: false,
+        tls: false,
+        dedicated_address: false,
+        selection: None,
+        ordinal: None,
+        lazy: false,
+        idle_timeout: None,
+        transport: None,
     };
 
     let result = config.validate();
@@ -92,6 +142,16 @@ fn test_port_forward_config_validation_empty_name() {
         name: "".to_string(),
         port: PortValue::Numeric(5555),
         kind: "service".to_string(),
+        health_check: None,
+        bind_address: None,
+        expose_to_network: false,
+        tls: false,
+        dedicated_address: false,
+        selection: None,
+        ordinal: None,
+        lazy: false,
+        idle_timeout: None,
+        transport: None,
     };
 
     let result = config.validate();
@@ -110,6 +170,16 @@ fn test_port_forward_config_validation_empty_kind() {
         name: "api-service".to_string(),
         port: PortValue::Numeric(5555),
         kind: "".to_string(),
+        health_check: None,
+        bind_address: None,
+        expose_to_network: false,
+        tls: false,
+        dedicated_address: false,
+        selection: None,
+        ordinal: None,
+        lazy: false,
+        idle_timeout: None,
+        transport: None,
     };
 
     let result = config.validate();
@@ -155,3 +225,80 @@ fn test_port_forward_config_deserialize_named_port() {
     assert_eq!(config.kind, "service");
 }
 
+#[test]
+fn test_port_forward_config_deserialize_lazy() {
+    let json = r#"{
+        "localport": "8080",
+        "name": "api",
+        "port": 8080,
+        "kind": "service",
+        "lazy": true,
+        "idleTimeout": 300
+    }"#;
+
+    let config: PortForwardingConfig =
+        serde_json::from_str(json).expect("deserialization should succeed");
+    assert!(config.lazy);
+    assert_eq!(config.idle_timeout, Some(300));
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_port_forward_config_validation_idle_timeout() {
+    let mut config: PortForwardingConfig = serde_json::from_str(
+        r#"{"localport": "8080", "name": "api", "port": 8080, "kind": "service"}"#,
+    )
+    .expect("deserialization should succeed");
+    assert!(!config.lazy);
+
+    config.idle_timeout = Some(300);
+    match config.validate() {
+        Err(DomainError::PortForwardingValidation(msg)) => {
+            assert!(msg.contains("idleTimeout requires lazy"));
+        }
+        other => panic!("Expected PortForwardingValidation error, got {other:?}"),
+    }
+
+    config.lazy = true;
+    config.idle_timeout = Some(0);
+    match config.validate() {
+        Err(DomainError::PortForwardingValidation(msg)) => {
+            assert!(msg.contains("at least 1 second"));
+        }
+        other => panic!("Expected PortForwardingValidation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_port_forward_config_deserialize_transport() {
+    let json = r#"{
+        "localport": "8080",
+        "name": "api",
+        "port": "http",
+        "kind": "service",
+        "transport": "serviceProxy"
+    }"#;
+
+    let config: PortForwardingConfig =
+        serde_json::from_str(json).expect("deserialization should succeed");
+    assert_eq!(config.transport, Some(ForwardTransport::ServiceProxy));
+    assert!(config.validate().is_ok());
+
+    let serialized = serde_json::to_value(&config).expect("serialization should succeed");
+    assert_eq!(serialized["transport"], "serviceProxy");
+}
+
+#[test]
+fn test_port_forward_config_validation_service_proxy_requires_service() {
+    let config: PortForwardingConfig = serde_json::from_str(
+        r#"{"localport": "8080", "name": "api", "port": 8080, "kind": "deployment", "transport": "serviceProxy"}"#,
+    )
+    .expect("deserialization should succeed");
+
+    match config.validate() {
+        Err(DomainError::PortForwardingValidation(msg)) => {
+            assert!(msg.contains("transport serviceProxy is only supported for services"));
+        }
+        other => panic!("Expected PortForwardingValidation error, got {other:?}"),
+    }
+}
@@ -0,0 +1,71 @@
+// Health check configuration tests
+//
+// Tests for parsing and validating per-forward health check probes.
+
+use roro_domain::{DomainError, HealthCheckConfig, PortForwardingConfig};
+
+#[test]
+fn test_health_check_defaults_when_omitted() {
+    let Ok(config) = serde_json::from_str::<PortForwardingConfig>(
+        r#"{"localport": "8080", "name": "api", "port": 80, "kind": "service"}"#,
+    ) else {
+        panic!("Failed to parse port forwarding config");
+    };
+
+    assert!(config.health_check.is_none());
+}
+
+#[test]
+fn test_health_check_parses_probe() {
+    let Ok(config) = serde_json::from_str::<PortForwardingConfig>(
+        r#"{
+            "localport": "8080",
+            "name": "api",
+            "port": "http",
+            "kind": "service",
+            "healthCheck": {"path": "/healthz", "expectedStatus": 204}
+        }"#,
+    ) else {
+        panic!("Failed to parse port forwarding config");
+    };
+
+    assert_eq!(
+        config.health_check,
+        Some(HealthCheckConfig {
+            path: Some("/healthz".to_string()),
+            expected_status: 204,
+            timeout_seconds: 5,
+        })
+    );
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_health_check_validation_rejects_relative_path() {
+    let health_check = HealthCheckConfig {
+        path: Some("healthz".to_string()),
+        ..Default::default()
+    };
+
+    let result = health_check.validate();
+    if let Err(DomainError::PortForwardingValidation(msg)) = result {
+        assert!(msg.contains("must start with '/'"));
+    } else {
+        panic!("Expected PortForwardingValidation error");
+    }
+}
+
+#[test]
+fn test_health_check_validation_rejects_invalid_status_and_timeout() {
+    let bad_status = HealthCheckConfig {
+        expected_status: 42,
+        ..Default::default()
+    };
+    let bad_timeout = HealthCheckConfig {
+        timeout_seconds: 0,
+        ..Default::default()
+    };
+
+    assert!(bad_status.validate().is_err());
+    assert!(bad_timeout.validate().is_err());
+}
@@ -0,0 +1,45 @@
+// Local port policy tests
+//
+// Tests for parsing `localport` values into allocation policies.
+
+use roro_domain::{DomainError, LocalPortPolicy};
+
+fn parse(value: &str) -> Result<LocalPortPolicy, DomainError> {
+    value.parse()
+}
+
+#[test]
+fn test_local_port_policy_forms() {
+    assert_eq!(parse("8080").ok(), Some(LocalPortPolicy::Fixed(8080)));
+    assert_eq!(parse("8080+").ok(), Some(LocalPortPolicy::Preferred(8080)));
+    assert_eq!(
+        parse("30000-30100").ok(),
+        Some(LocalPortPolicy::Range(30000, 30100))
+    );
+    assert_eq!(parse("auto").ok(), Some(LocalPortPolicy::Auto));
+    assert_eq!(parse("AUTO").ok(), Some(LocalPortPolicy::Auto));
+}
+
+#[test]
+fn test_local_port_policy_round_trips_through_display() {
+    for value in ["8080", "8080+", "30000-30100", "auto"] {
+        assert_eq!(
+            parse(value).map(|p| p.to_string()).ok().as_deref(),
+            Some(value)
+        );
+    }
+}
+
+#[test]
+fn test_local_port_policy_rejects_invalid_values() {
+    assert!(parse("").is_err());
+    assert!(parse("0").is_err());
+    assert!(parse("0-100").is_err());
+    assert!(parse("70000").is_err());
+    assert!(parse("http").is_err());
+
+    let Err(DomainError::PortForwardingValidation(msg)) = parse("30100-30000") else {
+        panic!("Expected PortForwardingValidation error");
+    };
+    assert!(msg.contains("must start at or below its end"));
+}
@@ -0,0 +1,126 @@
+// Workload port forward configuration tests
+//
+// Tests for parsing resource kinds and validating pod selection strategies and ordinals.
+
+use roro_domain::{DomainError, PodSelection, PortForwardingConfig, PortValue, ResourceKind};
+
+fn config(kind: &str) -> PortForwardingConfig {
+    PortForwardingConfig {
+        local_port: "5432".to_string(),
+        name: "db".to_string(),
+        port: PortValue::Numeric(5432),
+        kind: kind.to_string(),
+        health_check: None,
+        bind_address: None,
+        expose_to_network: false,
+        tls: false,
+        dedicated_address: false,
+        selection: None,
+        ordinal: None,
+        lazy: false,
+        idle_timeout: None,
+        transport: None,
+    }
+}
+
+fn validation_message(config: &PortForwardingConfig) -> String {
+    match config.validate() {
+        Err(DomainError::PortForwardingValidation(msg)) => msg,
+        other => panic!("Expected PortForwardingValidation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_resource_kinds_parse_case_insensitively() {
+    assert_eq!("service".parse().ok(), Some(ResourceKind::Service));
+    assert_eq!("Deployment".parse().ok(), Some(ResourceKind::Deployment));
+    assert_eq!("StatefulSet".parse().ok(), Some(ResourceKind::StatefulSet));
+    assert_eq!("daemonset".parse().ok(), Some(ResourceKind::DaemonSet));
+    assert_eq!("POD".parse().ok(), Some(ResourceKind::Pod));
+    assert_eq!(ResourceKind::StatefulSet.to_string(), "statefulset");
+}
+
+#[test]
+fn test_unknown_kind_is_rejected() {
+    let msg = validation_message(&config("ingress"));
+    assert!(msg.contains("kind 'ingress' must be one of"));
+}
+
+#[test]
+fn test_selection_is_accepted_for_workloads() {
+    for kind in ["deployment", "statefulset", "daemonset", "pod"] {
+        for selection in [
+            PodSelection::FirstReady,
+            PodSelection::Newest,
+            PodSelection::RoundRobin,
+        ] {
+            let mut config = config(kind);
+            config.selection = Some(selection);
+            assert!(config.validate().is_ok(), "{kind} with {selection:?}");
+        }
+    }
+}
+
+#[test]
+fn test_selection_is_rejected_for_services() {
+    let mut config = config("service");
+    config.selection = Some(PodSelection::RoundRobin);
+
+    assert!(validation_message(&config).contains("selection is not supported"));
+}
+
+#[test]
+fn test_ordinal_requires_statefulset() {
+    let mut stateful = config("statefulset");
+    stateful.ordinal = Some(0);
+    assert!(stateful.validate().is_ok());
+
+    let mut deployment = config("deployment");
+    deployment.ordinal = Some(0);
+    assert!(validation_message(&deployment).contains("only supported for statefulsets"));
+}
+
+#[test]
+fn test_ordinal_cannot_be_combined_with_selection() {
+    let mut config = config("statefulset");
+    config.ordinal = Some(1);
+    config.selection = Some(PodSelection::Newest);
+
+    assert!(validation_message(&config).contains("cannot be combined with selection"));
+}
+
+#[test]
+fn test_dedicated_address_requires_service() {
+    let mut config = config("deployment");
+    config.dedicated_address = true;
+
+    assert!(validation_message(&config).contains("only supported for services"));
+}
+
+#[test]
+fn test_workload_forward_parses_from_json() {
+    let Ok(config) = serde_json::from_str::<PortForwardingConfig>(
+        r#"{
+            "localport": "8080",
+            "name": "api",
+            "port": "http",
+            "kind": "deployment",
+            "selection": "roundRobin"
+        }"#,
+    ) else {
+        panic!("Expected config to parse");
+    };
+
+    assert_eq!(config.resource_kind().ok(), Some(ResourceKind::Deployment));
+    assert_eq!(config.selection, Some(PodSelection::RoundRobin));
+    assert_eq!(config.ordinal, None);
+    assert!(config.validate().is_ok());
+
+    let Ok(config) = serde_json::from_str::<PortForwardingConfig>(
+        r#"{ "localport": "5432", "name": "db", "port": 5432, "kind": "statefulset", "ordinal": 0 }"#,
+    ) else {
+        panic!("Expected config to parse");
+    };
+    assert_eq!(config.ordinal, Some(0));
+    assert!(config.validate().is_ok());
+}
@@ -5,11 +5,15 @@
 
 mod pod_list;
 mod port_forward_item;
+mod proxy_panel;
+mod restore_banner;
 mod workspace_config;
 
 pub use pod_list::PodList;
 #[allow(unused_imports)]
 pub use port_forward_item::PortForwardItem;
+pub use proxy_panel::ProxyPanel;
+pub use restore_banner::RestoreBanner;
 #[allow(unused_imports)]
 pub use workspace_config::WorkspaceConfig;
 
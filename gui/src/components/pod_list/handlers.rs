@@ -3,7 +3,10 @@
 // This module provides event handlers for port forwarding operations.
 
 use dioxus::prelude::*;
-use roro_core::api::kubernetes::{get_or_init, PortForwardingConfig, PortForwardingStatus};
+use roro_core::api::kubernetes::{
+    get_or_init, PortForwardingConfig, PortForwardingEvent, PortForwardingStatus, StopMode,
+};
+use tokio::sync::broadcast::error::RecvError;
 
 /// Open a URL in the default browser
 pub fn open_browser(local_port: u16) {
@@ -56,6 +59,7 @@ pub fn create_start_handler(
                         remote_port,
                         local_port,
                         instance_id: instance_id.clone(),
+                        ..Default::default()
                     };
 
                     error.set(None);
@@ -84,10 +88,13 @@ pub fn create_start_handler(
 }
 
 /// Create a handler for stopping a port forward
+///
+/// `StopMode::Drain` lets open connections finish first; `StopMode::Now` closes them.
 pub fn create_stop_handler(
     forward_id: Signal<Option<String>>,
     status: Signal<Option<PortForwardingStatus>>,
     error: Signal<Option<String>>,
+    mode: StopMode,
 ) -> impl Fn(Event<MouseData>) + 'static {
     move |_| {
         let mut forward_id = forward_id;
@@ -97,8 +104,8 @@ pub fn create_stop_handler(
         spawn(async move {
             if let Some(id) = forward_id_val {
                 match get_or_init("rancher-desktop").await {
-                    Ok(manager) => match manager.stop_forward(&id).await {
-                        Ok(()) => {
+                    Ok(manager) => match manager.stop_forward_with(&id, mode).await {
+                        Ok(_) => {
                             forward_id.set(None);
                             status.set(None);
                         }
@@ -116,3 +123,49 @@ pub fn create_stop_handler(
         });
     }
 }
+
+/// Keep a forward's status signal in sync with the manager's event stream
+///
+/// Runs for the lifetime of the component; events for other forwards are ignored.
+pub async fn watch_forward_events(
+    forward_id: Signal<Option<String>>,
+    status: Signal<Option<PortForwardingStatus>>,
+) {
+    let mut forward_id = forward_id;
+    let mut status = status;
+    let mut events = match get_or_init("rancher-desktop").await {
+        Ok(manager) => manager.subscribe(),
+        Err(e) => {
+            eprintln!(
+                "[PodPortButton] Failed to subscribe to port forward events: {:?}",
+                e
+            );
+            return;
+        }
+    };
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+
+        if forward_id.read().as_deref() != Some(event.forward_id()) {
+            continue;
+        }
+
+        match event {
+            PortForwardingEvent::StatusChanged {
+                status: new_status, ..
+            } => {
+                status.set(Some(new_status));
+            }
+            PortForwardingEvent::Stopped { .. } => {
+                forward_id.set(None);
+                status.set(None);
+            }
+            _ => {}
+        }
+    }
+}
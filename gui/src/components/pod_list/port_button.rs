@@ -14,7 +14,7 @@
 )]
 
 use dioxus::prelude::*;
-use roro_core::api::kubernetes::PortForwardingStatus;
+use roro_core::api::kubernetes::{PortForwardingStatus, StopMode};
 
 use super::handlers;
 
@@ -50,12 +50,16 @@ pub fn PodPortButton(props: PodPortButtonProps) -> Element {
         instance_id.clone(),
     );
 
-    let handle_stop = handlers::create_stop_handler(forward_id, status, error);
+    use_future(move || handlers::watch_forward_events(forward_id, status));
+
+    let handle_stop = handlers::create_stop_handler(forward_id, status, error, StopMode::Drain);
+    let handle_stop_now = handlers::create_stop_handler(forward_id, status, error, StopMode::Now);
 
     let is_active = status.read().as_ref().is_some_and(|s| {
         matches!(
             s,
             PortForwardingStatus::Active
+                | PortForwardingStatus::Idle
                 | PortForwardingStatus::Connecting
                 | PortForwardingStatus::Reconnecting
         )
@@ -83,9 +87,16 @@ pub fn PodPortButton(props: PodPortButtonProps) -> Element {
             if is_active {
                 button {
                     class: "px-2 py-1 bg-red-500 text-white text-xs rounded hover:bg-red-600",
+                    title: "Stop accepting connections and let open ones finish",
                     onclick: handle_stop,
                     "Stop"
                 }
+                button {
+                    class: "px-2 py-1 bg-red-700 text-white text-xs rounded hover:bg-red-800",
+                    title: "Stop and close open connections immediately",
+                    onclick: handle_stop_now,
+                    "Stop now"
+                }
             } else {
                 button {
                     class: "px-2 py-1 bg-blue-500 text-white text-xs rounded hover:bg-blue-600",
@@ -0,0 +1,139 @@
+// Captured HTTP traffic panel
+//
+// Lists the HTTP exchanges captured on a forward and exports them as a HAR file.
+
+use dioxus::prelude::*;
+use roro_core::api::kubernetes::{get_or_init, HttpExchange};
+use std::time::Duration;
+
+/// How often the panel refreshes its list of exchanges
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Captured traffic panel props
+#[derive(Props, PartialEq, Clone)]
+pub struct CapturePanelProps {
+    pub forward_id: String,
+}
+
+/// Captured traffic panel, newest exchanges first
+///
+/// # Note
+/// Dioxus requires component functions to use `PascalCase` naming convention.
+#[allow(non_snake_case)] // Required by Dioxus: component functions must use PascalCase
+pub fn CapturePanel(props: CapturePanelProps) -> Element {
+    let mut exchanges = use_signal(Vec::<HttpExchange>::new);
+    let mut message = use_signal(|| None::<String>);
+
+    let forward_id = props.forward_id.clone();
+    use_future(move || {
+        let forward_id = forward_id.clone();
+        async move {
+            loop {
+                if let Ok(manager) = get_or_init("rancher-desktop").await {
+                    match manager.captured_exchanges(&forward_id).await {
+                        Ok(captured) => exchanges.set(captured),
+                        // The forward has stopped; keep showing what was captured
+                        Err(_) => break,
+                    }
+                }
+                tokio::time::sleep(REFRESH_INTERVAL).await;
+            }
+        }
+    });
+
+    let export_id = props.forward_id.clone();
+    let handle_export = move |_| {
+        let forward_id = export_id.clone();
+        spawn(async move {
+            let result = match get_or_init("rancher-desktop").await {
+                Ok(manager) => match manager.export_har(&forward_id, None).await {
+                    Ok((path, count)) => {
+                        format!("Exported {} exchange(s) to {}", count, path.display())
+                    }
+                    Err(e) => format!("Failed to export: {:?}", e),
+                },
+                Err(e) => format!("Failed to get manager: {:?}", e),
+            };
+            println!("[CapturePanel] {}", result);
+            message.set(Some(result));
+        });
+    };
+
+    let clear_id = props.forward_id.clone();
+    let handle_clear = move |_| {
+        let forward_id = clear_id.clone();
+        spawn(async move {
+            if let Ok(manager) = get_or_init("rancher-desktop").await {
+                if manager.clear_capture(&forward_id).await.is_ok() {
+                    exchanges.set(Vec::new());
+                }
+            }
+        });
+    };
+
+    let count = exchanges.read().len();
+
+    rsx! {
+        div {
+            class: "mt-3 border-t border-gray-200 pt-3",
+            div {
+                class: "flex items-center justify-between mb-2",
+                span {
+                    class: "text-sm font-medium text-gray-700",
+                    "Captured requests ({count})"
+                }
+                div {
+                    class: "flex items-center gap-2",
+                    button {
+                        class: "px-2 py-1 text-sm bg-gray-200 text-gray-700 rounded hover:bg-gray-300",
+                        onclick: handle_clear,
+                        "Clear"
+                    }
+                    button {
+                        class: "px-2 py-1 text-sm bg-blue-500 text-white rounded hover:bg-blue-600",
+                        title: "Write the captured requests to ~/.roro/captures as a HAR file",
+                        onclick: handle_export,
+                        "Export HAR"
+                    }
+                }
+            }
+            if count == 0 {
+                p {
+                    class: "text-sm text-gray-500",
+                    "No requests captured yet"
+                }
+            }
+            div {
+                class: "max-h-64 overflow-y-auto font-mono text-xs",
+                for exchange in exchanges.read().iter().rev() {
+                    div {
+                        key: "{exchange.id}",
+                        class: "flex gap-3 py-1 border-b border-gray-100",
+                        span {
+                            class: status_color(exchange.status),
+                            "{exchange.status}"
+                        }
+                        span { class: "w-14 text-gray-800", "{exchange.method}" }
+                        span { class: "flex-1 truncate text-gray-800", "{exchange.target}" }
+                        span { class: "text-gray-500", {format!("{} ms", exchange.latency.as_millis())} }
+                        span { class: "text-gray-500", "{exchange.response_body_size} B" }
+                    }
+                }
+            }
+            if let Some(msg) = message.read().as_ref() {
+                p {
+                    class: "mt-2 text-sm text-gray-600",
+                    {msg.clone()}
+                }
+            }
+        }
+    }
+}
+
+fn status_color(status: u16) -> &'static str {
+    match status {
+        200..=399 => "text-green-600",
+        400..=499 => "text-yellow-600",
+        _ => "text-red-600",
+    }
+}
@@ -3,7 +3,12 @@
 // This module provides event handlers for port forwarding operations.
 
 use dioxus::prelude::*;
-use roro_core::api::kubernetes::{get_or_init, PortForwardingConfig, PortForwardingStatus};
+use roro_core::api::kubernetes::{
+    get_or_init, BindAddress, CaptureConfig, PortForwardingConfig, PortForwardingEvent,
+    PortForwardingStatus, StopMode,
+};
+use roro_core::CoreError;
+use tokio::sync::broadcast::error::RecvError;
 
 /// Create a handler for starting a port forward
 #[allow(clippy::too_many_arguments)]
@@ -25,7 +30,7 @@ pub fn create_start_handler(
         let pod = pod.clone();
         let instance_id = instance_id.clone();
         spawn(async move {
-            // Get or initialize the singleton manager
+            // Get or initialize the manager for this context
             match get_or_init("rancher-desktop").await {
                 Ok(manager) => {
                     let config = PortForwardingConfig {
@@ -34,6 +39,7 @@ pub fn create_start_handler(
                         remote_port,
                         local_port,
                         instance_id,
+                        ..Default::default()
                     };
 
                     println!(
@@ -70,7 +76,18 @@ pub fn create_start_handler(
                         }
                         Err(e) => {
                             eprintln!("[PortForwardItem] Failed to start port forward: {:?}", e);
-                            error.set(Some(format!("{:?}", e)));
+                            // Conflicts name the holder and suggest a free port instead
+                            let message = match &e {
+                                CoreError::PortConflict(port) | CoreError::PortHeld(port, _) => {
+                                    match manager.find_available_port(BindAddress::default(), *port)
+                                    {
+                                        Ok(free) => format!("{e}. Port {free} is free"),
+                                        Err(_) => e.to_string(),
+                                    }
+                                }
+                                _ => format!("{:?}", e),
+                            };
+                            error.set(Some(message));
                             status.set(Some(PortForwardingStatus::Failed));
                         }
                     }
@@ -86,10 +103,13 @@ pub fn create_start_handler(
 }
 
 /// Create a handler for stopping a port forward
+///
+/// `StopMode::Drain` lets open connections finish first; `StopMode::Now` closes them.
 pub fn create_stop_handler(
     forward_id: Signal<Option<String>>,
     status: Signal<Option<PortForwardingStatus>>,
     error: Signal<Option<String>>,
+    mode: StopMode,
 ) -> impl Fn(Event<MouseData>) + 'static {
     move |_| {
         let mut forward_id = forward_id;
@@ -100,14 +120,17 @@ pub fn create_stop_handler(
             if let Some(id) = forward_id_val {
                 println!("[PortForwardItem] Stopping port forward: {}", id);
 
-                // Get the singleton manager (should already be initialized)
+                // Get the manager for this context (should already be initialized)
                 match get_or_init("rancher-desktop").await {
                     Ok(manager) => {
                         error.set(None);
 
-                        match manager.stop_forward(&id).await {
-                            Ok(()) => {
-                                println!("[PortForwardItem] Port forward stopped successfully");
+                        match manager.stop_forward_with(&id, mode).await {
+                            Ok(summary) => {
+                                println!(
+                                    "[PortForwardItem] Port forward stopped successfully ({} drained, {} force-closed)",
+                                    summary.drained, summary.force_closed
+                                );
                                 forward_id.set(None);
                                 status.set(None); // Reset to not started
                             }
@@ -126,3 +149,133 @@ pub fn create_stop_handler(
         });
     }
 }
+
+/// Create a handler that turns HTTP capture on or off for a running forward
+pub fn create_capture_toggle_handler(
+    forward_id: Signal<Option<String>>,
+    inspecting: Signal<bool>,
+    error: Signal<Option<String>>,
+) -> impl Fn(Event<MouseData>) + 'static {
+    move |_| {
+        let mut inspecting = inspecting;
+        let mut error = error;
+        let forward_id_val = forward_id.read().clone();
+        let enable = !*inspecting.read();
+        spawn(async move {
+            let Some(id) = forward_id_val else {
+                return;
+            };
+            match get_or_init("rancher-desktop").await {
+                Ok(manager) => {
+                    let config = enable.then(CaptureConfig::default);
+                    match manager.set_capture(&id, config).await {
+                        Ok(()) => inspecting.set(enable),
+                        Err(e) => {
+                            eprintln!("[PortForwardItem] Failed to toggle capture: {:?}", e);
+                            error.set(Some(format!("{:?}", e)));
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("[PortForwardItem] Failed to get manager: {:?}", e);
+                    error.set(Some(format!("Failed to get manager: {:?}", e)));
+                }
+            }
+        });
+    }
+}
+
+/// Create a handler that swallows or restores all traffic through a running forward
+///
+/// Other shaping settings on the forward are kept.
+pub fn create_blackhole_toggle_handler(
+    forward_id: Signal<Option<String>>,
+    blackholed: Signal<bool>,
+    error: Signal<Option<String>>,
+) -> impl Fn(Event<MouseData>) + 'static {
+    move |_| {
+        let mut blackholed = blackholed;
+        let mut error = error;
+        let forward_id_val = forward_id.read().clone();
+        let enable = !*blackholed.read();
+        spawn(async move {
+            let Some(id) = forward_id_val else {
+                return;
+            };
+            match get_or_init("rancher-desktop").await {
+                Ok(manager) => {
+                    let shaping = match manager.shaping(&id).await {
+                        Ok(shaping) => shaping.unwrap_or_default().with_blackhole(enable),
+                        Err(e) => {
+                            error.set(Some(format!("{:?}", e)));
+                            return;
+                        }
+                    };
+                    match manager.set_shaping(&id, Some(shaping)).await {
+                        Ok(()) => blackholed.set(enable),
+                        Err(e) => {
+                            eprintln!("[PortForwardItem] Failed to toggle blackhole: {:?}", e);
+                            error.set(Some(format!("{:?}", e)));
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("[PortForwardItem] Failed to get manager: {:?}", e);
+                    error.set(Some(format!("Failed to get manager: {:?}", e)));
+                }
+            }
+        });
+    }
+}
+
+/// Keep a forward's status and shaping signals in sync with the manager's event stream
+///
+/// Runs for the lifetime of the component; events for other forwards are ignored.
+pub async fn watch_forward_events(
+    forward_id: Signal<Option<String>>,
+    status: Signal<Option<PortForwardingStatus>>,
+    shaped: Signal<bool>,
+) {
+    let mut forward_id = forward_id;
+    let mut status = status;
+    let mut shaped = shaped;
+    let mut events = match get_or_init("rancher-desktop").await {
+        Ok(manager) => manager.subscribe(),
+        Err(e) => {
+            eprintln!(
+                "[PortForwardItem] Failed to subscribe to port forward events: {:?}",
+                e
+            );
+            return;
+        }
+    };
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+
+        if forward_id.read().as_deref() != Some(event.forward_id()) {
+            continue;
+        }
+
+        match event {
+            PortForwardingEvent::StatusChanged {
+                status: new_status, ..
+            } => {
+                status.set(Some(new_status));
+            }
+            PortForwardingEvent::ShapingChanged { active, .. } => {
+                shaped.set(active);
+            }
+            PortForwardingEvent::Stopped { .. } => {
+                forward_id.set(None);
+                status.set(None);
+                shaped.set(false);
+            }
+            _ => {}
+        }
+    }
+}
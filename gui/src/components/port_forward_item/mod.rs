@@ -12,11 +12,13 @@
     unused_imports
 )]
 
+mod capture_panel;
 mod handlers;
 mod ui;
 
+use capture_panel::CapturePanel;
 use dioxus::prelude::*;
-use roro_core::api::kubernetes::PortForwardingStatus;
+use roro_core::api::kubernetes::{PortForwardingStatus, StopMode};
 
 /// Port forwarding item component props
 #[derive(Props, PartialEq, Clone)]
@@ -45,6 +47,13 @@ pub fn PortForwardItem(props: PortForwardItemProps) -> Element {
     // State for error message
     let error = use_signal(|| None::<String>);
 
+    // Whether HTTP traffic through the forward is being captured
+    let mut inspecting = use_signal(|| false);
+
+    // Whether traffic through the forward is degraded, and whether it is blackholed
+    let shaped = use_signal(|| false);
+    let mut blackholed = use_signal(|| false);
+
     // Clone props for use in closures
     let namespace = props.namespace.clone();
     let pod = props.pod.clone();
@@ -64,14 +73,33 @@ pub fn PortForwardItem(props: PortForwardItemProps) -> Element {
         instance_id.clone(),
     );
 
+    // Follow status changes pushed by the manager
+    use_future(move || handlers::watch_forward_events(forward_id, status, shaped));
+
     // Stop port forward handler
-    let handle_stop = handlers::create_stop_handler(forward_id, status, error);
+    let handle_stop = handlers::create_stop_handler(forward_id, status, error, StopMode::Drain);
+    let handle_stop_now = handlers::create_stop_handler(forward_id, status, error, StopMode::Now);
+
+    // Inspect traffic handler
+    let handle_inspect = handlers::create_capture_toggle_handler(forward_id, inspecting, error);
+
+    // Fault injection handler
+    let handle_blackhole = handlers::create_blackhole_toggle_handler(forward_id, blackholed, error);
+
+    // A new forward starts without capture or fault injection
+    use_effect(move || {
+        if forward_id.read().is_none() {
+            inspecting.set(false);
+            blackholed.set(false);
+        }
+    });
 
     // Determine if forward is active
     let is_active = status.read().as_ref().is_some_and(|s| {
         matches!(
             s,
             PortForwardingStatus::Active
+                | PortForwardingStatus::Idle
                 | PortForwardingStatus::Connecting
                 | PortForwardingStatus::Reconnecting
         )
@@ -106,12 +134,38 @@ pub fn PortForwardItem(props: PortForwardItemProps) -> Element {
                         class: format!("text-sm font-medium {status_color}"),
                         {status_text}
                     }
+                    if *shaped.read() {
+                        span {
+                            class: "px-2 py-0.5 text-xs font-medium bg-orange-100 text-orange-700 rounded",
+                            title: "Latency, bandwidth limits or faults are applied to this forward",
+                            "Faults injected"
+                        }
+                    }
                     if is_active {
+                        button {
+                            class: "px-3 py-1 bg-gray-200 text-gray-700 rounded hover:bg-gray-300",
+                            title: "Capture HTTP requests through this forward",
+                            onclick: handle_inspect,
+                            if *inspecting.read() { "Stop inspecting" } else { "Inspect" }
+                        }
+                        button {
+                            class: "px-3 py-1 bg-gray-200 text-gray-700 rounded hover:bg-gray-300",
+                            title: "Keep connections open but discard everything sent through them",
+                            onclick: handle_blackhole,
+                            if *blackholed.read() { "Restore traffic" } else { "Blackhole" }
+                        }
                         button {
                             class: "px-3 py-1 bg-red-500 text-white rounded hover:bg-red-600",
+                            title: "Stop accepting connections and let open ones finish",
                             onclick: handle_stop,
                             "Stop"
                         }
+                        button {
+                            class: "px-3 py-1 bg-red-700 text-white rounded hover:bg-red-800",
+                            title: "Stop and close open connections immediately",
+                            onclick: handle_stop_now,
+                            "Stop now"
+                        }
                     } else {
                         button {
                             class: "px-3 py-1 bg-blue-500 text-white rounded hover:bg-blue-600",
@@ -121,6 +175,11 @@ pub fn PortForwardItem(props: PortForwardItemProps) -> Element {
                     }
                 }
             }
+            if *inspecting.read() {
+                if let Some(id) = forward_id.read().clone() {
+                    CapturePanel { key: "{id}", forward_id: id }
+                }
+            }
             if let Some(err_msg) = error.read().as_ref() {
                 div {
                     class: "mt-2 p-2 bg-red-50 border border-red-200 rounded text-sm text-red-700",
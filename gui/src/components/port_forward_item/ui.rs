@@ -16,6 +16,7 @@ pub fn get_status_display(status: Option<&PortForwardingStatus>) -> (&'static st
         None => ("Not Started", "text-gray-600"),
         Some(PortForwardingStatus::Connecting) => ("Connecting...", "text-yellow-600"),
         Some(PortForwardingStatus::Active) => ("Active", "text-green-600"),
+        Some(PortForwardingStatus::Idle) => ("Idle", "text-gray-500"),
         Some(PortForwardingStatus::Failed) => ("Failed", "text-red-600"),
         Some(PortForwardingStatus::Reconnecting) => ("Reconnecting...", "text-yellow-600"),
     }
@@ -0,0 +1,134 @@
+// Reverse proxy panel
+//
+// Starts and stops the local reverse proxy and lists the host names it routes to forwards
+
+#![allow(clippy::uninlined_format_args)]
+
+use dioxus::prelude::*;
+use roro_core::api::kubernetes::portforwarding::{
+    current_proxy_routes, proxy_addresses, start_proxy, stop_proxy, DEFAULT_PROXY_PORT,
+};
+use roro_core::api::kubernetes::{ProxyConfig, ProxyRoute};
+use std::time::Duration;
+
+/// How often the panel refreshes the route list
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Reverse proxy panel
+///
+/// Routes are listed whether or not the proxy runs, so users can see the host names first.
+///
+/// # Note
+/// Dioxus requires component functions to use `PascalCase` naming convention.
+#[allow(non_snake_case)] // Required by Dioxus: component functions must use PascalCase
+pub fn ProxyPanel() -> Element {
+    let mut routes = use_signal(Vec::<ProxyRoute>::new);
+    let mut running_port =
+        use_signal(|| proxy_addresses().and_then(|a| a.first().map(|a| a.port())));
+    let mut port_input = use_signal(|| DEFAULT_PROXY_PORT.to_string());
+    let mut error = use_signal(|| None::<String>);
+
+    use_future(move || async move {
+        loop {
+            routes.set(current_proxy_routes().await);
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+        }
+    });
+
+    let handle_toggle = move |_| {
+        if running_port.read().is_some() {
+            stop_proxy();
+            running_port.set(None);
+            return;
+        }
+        let Ok(port) = port_input.read().trim().parse::<u16>() else {
+            error.set(Some(format!("'{}' is not a valid port", port_input.read())));
+            return;
+        };
+        spawn(async move {
+            match start_proxy(ProxyConfig {
+                port,
+                ..ProxyConfig::default()
+            })
+            .await
+            {
+                Ok(addresses) => {
+                    error.set(None);
+                    running_port.set(addresses.first().map(|a| a.port()));
+                }
+                Err(e) => {
+                    eprintln!("[ProxyPanel] Failed to start reverse proxy: {:?}", e);
+                    error.set(Some(format!("Failed to start: {}", e)));
+                }
+            }
+        });
+    };
+
+    let port_suffix = match *running_port.read() {
+        Some(80) | None => String::new(),
+        Some(port) => format!(":{}", port),
+    };
+
+    rsx! {
+        div {
+            class: "mb-6 p-4 border border-gray-200 rounded-lg bg-white",
+            div {
+                class: "flex items-center justify-between mb-2",
+                span {
+                    class: "text-sm font-medium text-gray-700",
+                    "Host routing proxy"
+                }
+                div {
+                    class: "flex items-center gap-2",
+                    input {
+                        class: "w-20 px-2 py-1 text-sm border border-gray-300 rounded",
+                        r#type: "number",
+                        disabled: running_port.read().is_some(),
+                        value: "{port_input}",
+                        oninput: move |e| port_input.set(e.value()),
+                    }
+                    button {
+                        class: "px-3 py-1 text-sm bg-blue-500 text-white rounded hover:bg-blue-600",
+                        onclick: handle_toggle,
+                        if running_port.read().is_some() { "Stop proxy" } else { "Start proxy" }
+                    }
+                }
+            }
+            if let Some(msg) = error.read().as_ref() {
+                p {
+                    class: "mb-2 text-sm text-red-600",
+                    {msg.clone()}
+                }
+            }
+            if routes.read().is_empty() {
+                p {
+                    class: "text-sm text-gray-500",
+                    "No forwards to route yet"
+                }
+            }
+            div {
+                class: "font-mono text-xs",
+                for route in routes.read().iter() {
+                    div {
+                        key: "{route.host}",
+                        class: "flex gap-3 py-1 border-b border-gray-100",
+                        span {
+                            class: "flex-1 truncate text-gray-800",
+                            if running_port.read().is_some() {
+                                a {
+                                    class: "text-blue-600 hover:underline",
+                                    href: "http://{route.host}{port_suffix}/",
+                                    "http://{route.host}{port_suffix}"
+                                }
+                            } else {
+                                "{route.host}"
+                            }
+                        }
+                        span { class: "text-gray-500", "{route.target}" }
+                        span { class: "text-gray-400", "{route.context}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// Restore banner component
+//
+// Offers to restore port forwards saved by a previous session when the app launches
+
+#![allow(clippy::uninlined_format_args, clippy::redundant_clone)]
+
+use dioxus::prelude::*;
+use roro_core::api::kubernetes::get_or_init;
+use roro_core::{DesiredState, ForwardRecord};
+
+/// Restore banner component
+///
+/// Hidden when there is nothing to restore or once the user restores or dismisses.
+///
+/// # Note
+/// Dioxus requires component functions to use `PascalCase` naming convention.
+#[allow(non_snake_case)] // Required by Dioxus: component functions must use PascalCase
+pub fn RestoreBanner() -> Element {
+    let mut saved = use_signal(Vec::<ForwardRecord>::new);
+    let mut message = use_signal(|| None::<String>);
+    let mut restoring = use_signal(|| false);
+
+    use_future(move || async move {
+        match get_or_init("rancher-desktop").await {
+            Ok(manager) => match manager.saved_forwards().await {
+                Ok(records) => saved.set(
+                    records
+                        .into_iter()
+                        .filter(|r| r.desired_state == DesiredState::Running)
+                        .collect(),
+                ),
+                Err(e) => eprintln!("[RestoreBanner] Failed to load saved forwards: {:?}", e),
+            },
+            Err(e) => eprintln!("[RestoreBanner] Failed to initialize manager: {:?}", e),
+        }
+    });
+
+    let handle_restore = move |_| {
+        restoring.set(true);
+        spawn(async move {
+            let summary = match get_or_init("rancher-desktop").await {
+                Ok(manager) => match manager.restore_forwards().await {
+                    Ok(outcomes) => {
+                        let failed: Vec<String> = outcomes
+                            .iter()
+                            .filter_map(|(record, result)| {
+                                result
+                                    .as_ref()
+                                    .err()
+                                    .map(|e| format!("localhost:{}: {}", record.local_port, e))
+                            })
+                            .collect();
+                        let restored = outcomes.len() - failed.len();
+                        if failed.is_empty() {
+                            format!("Restored {} port forward(s)", restored)
+                        } else {
+                            format!(
+                                "Restored {} port forward(s); failed: {}",
+                                restored,
+                                failed.join("; ")
+                            )
+                        }
+                    }
+                    Err(e) => format!("Failed to restore: {:?}", e),
+                },
+                Err(e) => format!("Failed to initialize: {:?}", e),
+            };
+            println!("[RestoreBanner] {}", summary);
+            saved.set(Vec::new());
+            restoring.set(false);
+            message.set(Some(summary));
+        });
+    };
+
+    let count = saved.read().len();
+
+    rsx! {
+        if count > 0 {
+            div {
+                class: "mb-6 p-4 border border-blue-200 rounded-lg bg-blue-50 flex items-center justify-between",
+                p {
+                    class: "text-sm text-blue-800",
+                    "{count} port forward(s) from your last session can be restored"
+                }
+                div {
+                    class: "flex items-center gap-2",
+                    button {
+                        class: "px-3 py-1 bg-blue-500 text-white rounded hover:bg-blue-600",
+                        disabled: *restoring.read(),
+                        onclick: handle_restore,
+                        if *restoring.read() { "Restoring..." } else { "Restore" }
+                    }
+                    button {
+                        class: "px-3 py-1 bg-gray-200 text-gray-700 rounded hover:bg-gray-300",
+                        onclick: move |_| saved.set(Vec::new()),
+                        "Dismiss"
+                    }
+                }
+            }
+        }
+        if let Some(msg) = message.read().as_ref() {
+            div {
+                class: "mb-6 p-2 bg-gray-100 border border-gray-200 rounded text-sm text-gray-700",
+                {msg.clone()}
+            }
+        }
+    }
+}
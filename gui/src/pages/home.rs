@@ -2,7 +2,7 @@
 //
 // Main home page that displays port forwarding items and other content
 
-use crate::components::PodList;
+use crate::components::{PodList, ProxyPanel, RestoreBanner};
 use dioxus::prelude::*;
 
 #[derive(Props, PartialEq, Clone)]
@@ -45,6 +45,8 @@ pub fn Home(props: HomeProps) -> Element {
                         }
                     }
                 }
+                RestoreBanner {}
+                ProxyPanel {}
                 PodList {}
             }
         }
@@ -0,0 +1,56 @@
+// Traffic capture files
+// This module writes captured HTTP traffic, already converted to a HAR document, to disk.
+// Captures default to ~/.roro/captures/<name>.har.
+
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+
+use crate::errors::PersistenceError;
+use crate::forwards::get_state_path;
+
+/// Default path for a capture named `name`: ~/.roro/captures/<name>.har
+///
+/// # Errors
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub fn default_capture_path(name: &str) -> Result<PathBuf, PersistenceError> {
+    let file_name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Ok(get_state_path("captures")?.join(format!("{file_name}.har")))
+}
+
+/// Write a HAR document to `path`, creating its directory if needed
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the document cannot be serialized or written
+pub async fn save_capture_to(path: &Path, har: &serde_json::Value) -> Result<(), PersistenceError> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).await.map_err(|e| {
+            PersistenceError::Serialization(format!(
+                "Failed to create directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+    }
+
+    let contents = serde_json::to_string_pretty(har).map_err(|e| {
+        PersistenceError::Serialization(format!("Failed to serialize capture: {e}"))
+    })?;
+
+    fs::write(path, contents).await.map_err(|e| {
+        PersistenceError::Serialization(format!(
+            "Failed to write capture file {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
@@ -0,0 +1,175 @@
+// Port forward state module
+// This module persists requested port forwards to ~/.roro/forwards.json and the local
+// ports chosen for each instance to ~/.roro/ports.json so both survive restarts.
+
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::fs;
+
+use crate::errors::PersistenceError;
+use crate::models::{ForwardRecord, PortAllocation};
+
+/// Resolve a state file under `~/.roro`
+///
+/// # Errors
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub(crate) fn get_state_path(file_name: &str) -> Result<PathBuf, PersistenceError> {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map_err(|_| {
+            PersistenceError::InvalidInput(
+                "Cannot determine home directory. HOME or USERPROFILE environment variable must be set.".to_string(),
+            )
+        })?;
+
+    Ok(PathBuf::from(home).join(".roro").join(file_name))
+}
+
+/// Get the port forward state file path as a string
+///
+/// # Errors
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub fn get_forwards_path_string() -> Result<String, PersistenceError> {
+    get_state_path("forwards.json").map(|p| p.display().to_string())
+}
+
+/// Load persisted port forwards from ~/.roro/forwards.json
+///
+/// A missing file means no forwards have been saved yet and yields an empty list.
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the file cannot be read or parsed
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub async fn load_forward_records() -> Result<Vec<ForwardRecord>, PersistenceError> {
+    load_forward_records_from(&get_state_path("forwards.json")?).await
+}
+
+/// Save port forwards to ~/.roro/forwards.json, replacing its contents
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the file cannot be written
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub async fn save_forward_records(records: &[ForwardRecord]) -> Result<(), PersistenceError> {
+    save_forward_records_to(&get_state_path("forwards.json")?, records).await
+}
+
+/// Load persisted port forwards from `path`
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the file cannot be read or parsed
+pub async fn load_forward_records_from(
+    path: &Path,
+) -> Result<Vec<ForwardRecord>, PersistenceError> {
+    load_list(path).await
+}
+
+/// Save port forwards to `path`, replacing its contents
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the state cannot be serialized or written
+pub async fn save_forward_records_to(
+    path: &Path,
+    records: &[ForwardRecord],
+) -> Result<(), PersistenceError> {
+    save_list(path, records).await
+}
+
+/// Load remembered local port allocations from ~/.roro/ports.json
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the file cannot be read or parsed
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub async fn load_port_allocations() -> Result<Vec<PortAllocation>, PersistenceError> {
+    load_port_allocations_from(&get_state_path("ports.json")?).await
+}
+
+/// Save local port allocations to ~/.roro/ports.json, replacing its contents
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the file cannot be written
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub async fn save_port_allocations(allocations: &[PortAllocation]) -> Result<(), PersistenceError> {
+    save_port_allocations_to(&get_state_path("ports.json")?, allocations).await
+}
+
+/// Load remembered local port allocations from `path`
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the file cannot be read or parsed
+pub async fn load_port_allocations_from(
+    path: &Path,
+) -> Result<Vec<PortAllocation>, PersistenceError> {
+    load_list(path).await
+}
+
+/// Save local port allocations to `path`, replacing its contents
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the state cannot be serialized or written
+pub async fn save_port_allocations_to(
+    path: &Path,
+    allocations: &[PortAllocation],
+) -> Result<(), PersistenceError> {
+    save_list(path, allocations).await
+}
+
+/// Load a JSON list, treating a missing file as empty
+async fn load_list<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, PersistenceError> {
+    let contents = match fs::read_to_string(path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(PersistenceError::Serialization(format!(
+                "Failed to read state file {}: {}",
+                path.display(),
+                e
+            )));
+        }
+    };
+
+    serde_json::from_str(&contents).map_err(|e| {
+        PersistenceError::Serialization(format!(
+            "Failed to parse state file {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Save a JSON list
+///
+/// The list is written to a temporary file and renamed into place so a crash mid-write
+/// never leaves a truncated file behind.
+async fn save_list<T: Serialize>(path: &Path, items: &[T]) -> Result<(), PersistenceError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await.map_err(|e| {
+            PersistenceError::Serialization(format!(
+                "Failed to create directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+    }
+
+    let json_content = serde_json::to_string_pretty(items)
+        .map_err(|e| PersistenceError::Serialization(format!("Failed to serialize state: {e}")))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json_content).await.map_err(|e| {
+        PersistenceError::Serialization(format!(
+            "Failed to write state file {}: {}",
+            tmp_path.display(),
+            e
+        ))
+    })?;
+
+    fs::rename(&tmp_path, path).await.map_err(|e| {
+        PersistenceError::Serialization(format!(
+            "Failed to replace state file {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
@@ -0,0 +1,124 @@
+// Hosts file block management
+// This module maintains a marked block of entries in the system hosts file, one block per
+// name (e.g. kube context), leaving the rest of the file untouched.
+
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+
+use crate::errors::PersistenceError;
+
+const BLOCK_BEGIN: &str = "# BEGIN roro-kube";
+const BLOCK_END: &str = "# END roro-kube";
+
+/// The system hosts file
+#[must_use]
+pub fn default_hosts_path() -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(r"C:\Windows\System32\drivers\etc\hosts")
+    } else {
+        PathBuf::from("/etc/hosts")
+    }
+}
+
+/// Replace the block called `name` in hosts file `contents` with `entries`
+///
+/// `entries` are `(address, host name)` pairs; consecutive pairs sharing an address are
+/// written on one line. Without entries the block is removed. A new block is appended at
+/// the end of the file.
+#[must_use]
+pub fn replace_hosts_block(contents: &str, name: &str, entries: &[(String, String)]) -> String {
+    let begin = format!("{BLOCK_BEGIN} {name}");
+    let end = format!("{BLOCK_END} {name}");
+
+    let mut lines: Vec<&str> = Vec::new();
+    let mut insert_at = None;
+    let mut in_block = false;
+    for line in contents.lines() {
+        if line.trim() == begin {
+            in_block = true;
+            insert_at.get_or_insert(lines.len());
+        } else if in_block && line.trim() == end {
+            in_block = false;
+        } else if !in_block {
+            lines.push(line);
+        }
+    }
+
+    let mut block = Vec::new();
+    if !entries.is_empty() {
+        block.push(begin);
+        let mut current: Option<(&str, Vec<&str>)> = None;
+        for (address, host) in entries {
+            match &mut current {
+                Some((current_address, hosts)) if current_address == address => hosts.push(host),
+                _ => {
+                    if let Some((address, hosts)) = current.take() {
+                        block.push(format!("{address} {}", hosts.join(" ")));
+                    }
+                    current = Some((address, vec![host]));
+                }
+            }
+        }
+        if let Some((address, hosts)) = current {
+            block.push(format!("{address} {}", hosts.join(" ")));
+        }
+        block.push(end);
+    }
+
+    let mut output: Vec<String> = lines.iter().map(ToString::to_string).collect();
+    let at = insert_at.unwrap_or(output.len());
+    output.splice(at..at, block);
+    if output.is_empty() {
+        return String::new();
+    }
+    let mut result = output.join("\n");
+    result.push('\n');
+    result
+}
+
+/// Write `entries` into the block called `name` in the hosts file at `path`
+///
+/// The file is written to a temporary file next to it and renamed into place, so readers
+/// never see it half-written. Writing the system hosts file usually needs administrator
+/// rights.
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the file cannot be read or written
+pub async fn apply_hosts_block(
+    path: &Path,
+    name: &str,
+    entries: &[(String, String)],
+) -> Result<(), PersistenceError> {
+    let contents = match fs::read_to_string(path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(PersistenceError::Serialization(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+
+    let updated = replace_hosts_block(&contents, name, entries);
+    if updated == contents {
+        return Ok(());
+    }
+
+    // Replace the file a symlinked hosts file points at, keeping its permissions
+    let path = fs::canonicalize(path)
+        .await
+        .unwrap_or_else(|_| path.to_path_buf());
+    let tmp_path = path.with_extension("roro.tmp");
+    fs::write(&tmp_path, updated).await.map_err(|e| {
+        PersistenceError::Serialization(format!("Failed to write {}: {}", tmp_path.display(), e))
+    })?;
+    if let Ok(metadata) = fs::metadata(&path).await {
+        let _ = fs::set_permissions(&tmp_path, metadata.permissions()).await;
+    }
+    fs::rename(&tmp_path, &path).await.map_err(|e| {
+        PersistenceError::Serialization(format!("Failed to replace {}: {}", path.display(), e))
+    })
+}
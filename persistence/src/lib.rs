@@ -1,16 +1,30 @@
 // Persistence layer crate
 // This crate handles data storage and retrieval operations.
 
+pub mod captures;
 pub mod config;
 pub mod errors;
+pub mod forwards;
 pub mod git;
+pub mod hosts;
 pub mod models;
 pub mod store;
+pub mod tls;
 
+pub use captures::{default_capture_path, save_capture_to};
 pub use config::{get_config_path_string, load_workstation_config};
 pub use errors::PersistenceError;
+pub use forwards::{
+    get_forwards_path_string, load_forward_records, load_forward_records_from,
+    load_port_allocations, load_port_allocations_from, save_forward_records,
+    save_forward_records_to, save_port_allocations, save_port_allocations_to,
+};
 pub use git::{clone_repository, fetch_latest, repository_exists, sync_repository};
+pub use hosts::{apply_hosts_block, default_hosts_path, replace_hosts_block};
 pub use store::Store;
+pub use tls::{
+    get_tls_ca_path, get_tls_dir, load_tls_ca, load_tls_ca_from, save_ca_certificate_to,
+    save_tls_ca, save_tls_ca_to,
+};
 
-// Model re-exports will be added when models are implemented
-// pub use models::*;
+pub use models::{DesiredState, ForwardRecord, ForwardRecordTarget, PortAllocation};
@@ -11,4 +11,4 @@ pub mod state;
 // Re-exports will be added when models are implemented
 // pub use definition::*;
 // pub use execution::*;
-// pub use state::*;
+pub use state::{DesiredState, ForwardRecord, ForwardRecordTarget, PortAllocation};
@@ -1,4 +1,90 @@
 // State model
-// This module will contain the State entity model.
+// This module contains the persisted record of requested port forwards.
 
-// Placeholder - to be implemented in future tasks
+use roro_domain::{ForwardTransport, HealthCheckConfig, PodSelection, PortValue, ResourceKind};
+use serde::{Deserialize, Serialize};
+
+/// A requested port forward, persisted so it can be restored after a restart
+///
+/// `pod` is the pod the forward last used; pod names change across restarts, so it is
+/// only a hint and restoring re-resolves it through `target` or `selector`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForwardRecord {
+    /// Forward id at the time the record was written
+    pub id: String,
+    /// Kubernetes context the forward was started in
+    pub context: String,
+    pub instance_id: String,
+    pub namespace: String,
+    pub pod: String,
+    pub remote_port: u16,
+    pub local_port: u16,
+    /// Resource the forward was requested against
+    #[serde(default)]
+    pub target: ForwardRecordTarget,
+    /// Label selector matching the pods that can serve the forward
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<HealthCheckConfig>,
+    /// Local bind address; omitted for the default localhost binding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<String>,
+    /// Forward group the forward was started in, restored together with its other members
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    /// Whether the local listener terminates TLS; omitted when it doesn't
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tls: bool,
+    /// Whether the forward opens its tunnel only when a connection arrives
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lazy: bool,
+    /// Seconds a lazy forward stays connected without connections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout: Option<u64>,
+    /// How connections reach the cluster; omitted for the automatic choice
+    #[serde(default, skip_serializing_if = "ForwardTransport::is_auto")]
+    pub transport: ForwardTransport,
+    pub desired_state: DesiredState,
+}
+
+/// Persisted form of the resource a forward targets
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ForwardRecordTarget {
+    #[default]
+    Pod,
+    Service {
+        name: String,
+        port: PortValue,
+    },
+    /// A workload's pods, picked by `selection` or by `StatefulSet` ordinal
+    #[serde(rename_all = "camelCase")]
+    Workload {
+        resource: ResourceKind,
+        name: String,
+        #[serde(default)]
+        selection: PodSelection,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ordinal: Option<u32>,
+    },
+}
+
+/// Whether a forward should be running after a restart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DesiredState {
+    Running,
+    Stopped,
+}
+
+/// Local port chosen for a forward of an instance, remembered so it stays stable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortAllocation {
+    pub instance_id: String,
+    /// Identifies the forward within the instance (e.g., "service/api:http")
+    pub forward: String,
+    pub local_port: u16,
+}
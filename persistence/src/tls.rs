@@ -0,0 +1,148 @@
+// Local certificate authority files
+// This module stores the certificate authority used for local TLS termination under
+// ~/.roro/tls: the certificate in ca.pem and its private key in ca-key.pem.
+
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use crate::errors::PersistenceError;
+use crate::forwards::get_state_path;
+
+const CA_CERT_FILE: &str = "ca.pem";
+const CA_KEY_FILE: &str = "ca-key.pem";
+
+/// The `~/.roro/tls` directory
+///
+/// # Errors
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub fn get_tls_dir() -> Result<PathBuf, PersistenceError> {
+    get_state_path("tls")
+}
+
+/// Path of the CA certificate developers add to their trust store
+///
+/// # Errors
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub fn get_tls_ca_path() -> Result<PathBuf, PersistenceError> {
+    Ok(get_tls_dir()?.join(CA_CERT_FILE))
+}
+
+/// Load the CA certificate and key PEMs from ~/.roro/tls
+///
+/// Returns `None` if the CA has not been created yet.
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if only one of the files exists or they cannot be read
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub async fn load_tls_ca() -> Result<Option<(String, String)>, PersistenceError> {
+    load_tls_ca_from(&get_tls_dir()?).await
+}
+
+/// Save the CA certificate and key PEMs to ~/.roro/tls
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the files cannot be written
+/// * `PersistenceError::InvalidInput` if the home directory cannot be determined
+pub async fn save_tls_ca(cert_pem: &str, key_pem: &str) -> Result<(), PersistenceError> {
+    save_tls_ca_to(&get_tls_dir()?, cert_pem, key_pem).await
+}
+
+/// Load the CA certificate and key PEMs from `dir`
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if only one of the files exists or they cannot be read
+pub async fn load_tls_ca_from(dir: &Path) -> Result<Option<(String, String)>, PersistenceError> {
+    let cert = read_optional(&dir.join(CA_CERT_FILE)).await?;
+    let key = read_optional(&dir.join(CA_KEY_FILE)).await?;
+    match (cert, key) {
+        (Some(cert), Some(key)) => Ok(Some((cert, key))),
+        (None, None) => Ok(None),
+        _ => Err(PersistenceError::Serialization(format!(
+            "Incomplete CA in {}: expected both {CA_CERT_FILE} and {CA_KEY_FILE}",
+            dir.display()
+        ))),
+    }
+}
+
+/// Save the CA certificate and key PEMs to `dir`
+///
+/// The key is written first, to a temporary file that on Unix is created readable only by
+/// the current user and then renamed into place, so it is never briefly readable by others
+/// or left half-written.
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the files cannot be written
+pub async fn save_tls_ca_to(
+    dir: &Path,
+    cert_pem: &str,
+    key_pem: &str,
+) -> Result<(), PersistenceError> {
+    fs::create_dir_all(dir).await.map_err(|e| {
+        PersistenceError::Serialization(format!(
+            "Failed to create directory {}: {}",
+            dir.display(),
+            e
+        ))
+    })?;
+
+    write_key_file(&dir.join(CA_KEY_FILE), key_pem).await?;
+    write_file(&dir.join(CA_CERT_FILE), cert_pem).await
+}
+
+/// Write the CA certificate PEM to `path`, e.g. to import it into a trust store elsewhere
+///
+/// # Errors
+/// * `PersistenceError::Serialization` if the file cannot be written
+pub async fn save_ca_certificate_to(path: &Path, cert_pem: &str) -> Result<(), PersistenceError> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).await.map_err(|e| {
+            PersistenceError::Serialization(format!(
+                "Failed to create directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+    }
+    write_file(path, cert_pem).await
+}
+
+async fn read_optional(path: &Path) -> Result<Option<String>, PersistenceError> {
+    match fs::read_to_string(path).await {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(PersistenceError::Serialization(format!(
+            "Failed to read {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+async fn write_file(path: &Path, contents: &str) -> Result<(), PersistenceError> {
+    fs::write(path, contents).await.map_err(|e| {
+        PersistenceError::Serialization(format!("Failed to write {}: {}", path.display(), e))
+    })
+}
+
+async fn write_key_file(path: &Path, contents: &str) -> Result<(), PersistenceError> {
+    let tmp_path = path.with_extension("pem.tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let write_error = |e: std::io::Error| {
+        PersistenceError::Serialization(format!("Failed to write {}: {}", tmp_path.display(), e))
+    };
+    let mut file = options.open(&tmp_path).await.map_err(write_error)?;
+    file.write_all(contents.as_bytes())
+        .await
+        .map_err(write_error)?;
+    file.sync_all().await.map_err(write_error)?;
+    drop(file);
+
+    fs::rename(&tmp_path, path).await.map_err(|e| {
+        PersistenceError::Serialization(format!("Failed to replace {}: {}", path.display(), e))
+    })
+}
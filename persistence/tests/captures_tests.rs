@@ -0,0 +1,28 @@
+// Capture file tests
+//
+// Tests for writing HAR captures to disk.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_persistence::{default_capture_path, save_capture_to};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_save_capture_creates_directory() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("captures").join("api.har");
+    let har = serde_json::json!({ "log": { "version": "1.2", "entries": [] } });
+
+    save_capture_to(&path, &har).await.unwrap();
+
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved, har);
+}
+
+#[test]
+fn test_default_capture_path_sanitises_name() {
+    let path = default_capture_path("dev/api:8080").unwrap();
+
+    assert!(path.ends_with("captures/dev_api_8080.har"));
+}
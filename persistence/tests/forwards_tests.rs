@@ -0,0 +1,166 @@
+// Port forward state tests
+//
+// Tests for saving and loading persisted port forward records.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_domain::{ForwardTransport, PodSelection, PortValue, ResourceKind};
+use roro_persistence::{
+    load_forward_records_from, load_port_allocations_from, save_forward_records_to,
+    save_port_allocations_to, DesiredState, ForwardRecord, ForwardRecordTarget, PortAllocation,
+};
+use tempfile::TempDir;
+
+fn record(id: &str, target: ForwardRecordTarget) -> ForwardRecord {
+    ForwardRecord {
+        id: id.to_string(),
+        context: "rancher-desktop".to_string(),
+        instance_id: "dev".to_string(),
+        namespace: "default".to_string(),
+        pod: "api-7d9f-aaaaa".to_string(),
+        remote_port: 8080,
+        local_port: 18080,
+        target,
+        selector: Some("app=api".to_string()),
+        health_check: None,
+        bind_address: None,
+        group_id: None,
+        tls: false,
+        lazy: false,
+        idle_timeout: None,
+        transport: ForwardTransport::Auto,
+        desired_state: DesiredState::Running,
+    }
+}
+
+#[tokio::test]
+async fn test_load_missing_state_is_empty() {
+    let dir = TempDir::new().unwrap();
+
+    let records = load_forward_records_from(&dir.path().join("forwards.json"))
+        .await
+        .unwrap();
+
+    assert!(records.is_empty());
+}
+
+#[tokio::test]
+async fn test_save_and_load_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("state").join("forwards.json");
+    let records = vec![
+        record("dev-api-7d9f-aaaaa-18080", ForwardRecordTarget::Pod),
+        record(
+            "dev-api-7d9f-aaaaa-18081",
+            ForwardRecordTarget::Service {
+                name: "api".to_string(),
+                port: PortValue::Named("http".to_string()),
+            },
+        ),
+    ];
+
+    save_forward_records_to(&path, &records).await.unwrap();
+    let loaded = load_forward_records_from(&path).await.unwrap();
+
+    assert_eq!(loaded, records);
+    assert!(!path.with_extension("json.tmp").exists());
+}
+
+#[tokio::test]
+async fn test_load_rejects_corrupt_state() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("forwards.json");
+    tokio::fs::write(&path, "not json").await.unwrap();
+
+    assert!(load_forward_records_from(&path).await.is_err());
+}
+
+#[tokio::test]
+async fn test_port_allocations_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("ports.json");
+    let allocations = vec![PortAllocation {
+        instance_id: "my-app-dev".to_string(),
+        forward: "service/api:http".to_string(),
+        local_port: 30042,
+    }];
+
+    save_port_allocations_to(&path, &allocations).await.unwrap();
+
+    assert_eq!(
+        load_port_allocations_from(&path).await.unwrap(),
+        allocations
+    );
+}
+
+#[test]
+fn test_record_json_shape() {
+    let json = serde_json::to_value(record("id", ForwardRecordTarget::Pod)).unwrap();
+
+    assert_eq!(json["instanceId"], "dev");
+    assert_eq!(json["target"]["kind"], "pod");
+    assert_eq!(json["desiredState"], "running");
+    assert!(json.get("healthCheck").is_none());
+    assert!(json.get("groupId").is_none());
+    assert!(json.get("lazy").is_none());
+    assert!(json.get("idleTimeout").is_none());
+}
+
+#[test]
+fn test_lazy_record_round_trip() {
+    let mut lazy = record("id", ForwardRecordTarget::Pod);
+    lazy.lazy = true;
+    lazy.idle_timeout = Some(300);
+
+    let json = serde_json::to_value(&lazy).unwrap();
+    assert_eq!(json["lazy"], true);
+    assert_eq!(json["idleTimeout"], 300);
+
+    let parsed: ForwardRecord = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, lazy);
+}
+
+#[test]
+fn test_transport_is_saved_only_when_chosen() {
+    let automatic = serde_json::to_value(record("id", ForwardRecordTarget::Pod)).unwrap();
+    assert!(automatic.get("transport").is_none());
+
+    let mut proxied = record("id", ForwardRecordTarget::Pod);
+    proxied.transport = ForwardTransport::ServiceProxy;
+    let json = serde_json::to_value(&proxied).unwrap();
+    assert_eq!(json["transport"], "serviceProxy");
+
+    let parsed: ForwardRecord = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, proxied);
+}
+
+#[test]
+fn test_record_group_id_round_trip() {
+    let mut grouped = record("id", ForwardRecordTarget::Pod);
+    grouped.group_id = Some("dev-api-group-18080-19090".to_string());
+
+    let json = serde_json::to_value(&grouped).unwrap();
+    assert_eq!(json["groupId"], "dev-api-group-18080-19090");
+
+    let parsed: ForwardRecord = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, grouped);
+}
+
+#[test]
+fn test_workload_target_json_shape() {
+    let target = ForwardRecordTarget::Workload {
+        resource: ResourceKind::StatefulSet,
+        name: "db".to_string(),
+        selection: PodSelection::FirstReady,
+        ordinal: Some(0),
+    };
+    let json = serde_json::to_value(record("id", target.clone())).unwrap();
+
+    assert_eq!(json["target"]["kind"], "workload");
+    assert_eq!(json["target"]["resource"], "statefulset");
+    assert_eq!(json["target"]["selection"], "firstReady");
+    assert_eq!(json["target"]["ordinal"], 0);
+
+    let parsed: ForwardRecord = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.target, target);
+}
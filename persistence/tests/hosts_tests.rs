@@ -0,0 +1,141 @@
+// Hosts file block tests
+//
+// Tests for writing and removing marked blocks in a hosts file.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_persistence::{apply_hosts_block, replace_hosts_block};
+use tempfile::TempDir;
+
+const SYSTEM_HOSTS: &str = "127.0.0.1 localhost\n::1 localhost\n";
+
+fn entries(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(address, host)| ((*address).to_string(), (*host).to_string()))
+        .collect()
+}
+
+#[test]
+fn test_block_is_appended_with_one_line_per_address() {
+    let updated = replace_hosts_block(
+        SYSTEM_HOSTS,
+        "rancher-desktop",
+        &entries(&[
+            ("127.1.0.1", "postgres"),
+            ("127.1.0.1", "postgres.db"),
+            ("127.1.0.2", "redis"),
+        ]),
+    );
+
+    assert_eq!(
+        updated,
+        "127.0.0.1 localhost\n::1 localhost\n\
+         # BEGIN roro-kube rancher-desktop\n\
+         127.1.0.1 postgres postgres.db\n\
+         127.1.0.2 redis\n\
+         # END roro-kube rancher-desktop\n"
+    );
+}
+
+#[test]
+fn test_block_is_replaced_in_place() {
+    let with_block = replace_hosts_block(
+        SYSTEM_HOSTS,
+        "rancher-desktop",
+        &entries(&[("127.1.0.1", "postgres")]),
+    );
+    let with_trailer = format!("{with_block}10.0.0.5 nas\n");
+
+    let updated = replace_hosts_block(
+        &with_trailer,
+        "rancher-desktop",
+        &entries(&[("127.1.0.2", "redis")]),
+    );
+
+    assert!(!updated.contains("postgres"));
+    assert!(updated.contains("127.1.0.2 redis\n# END roro-kube rancher-desktop\n10.0.0.5 nas\n"));
+}
+
+#[test]
+fn test_blocks_of_other_contexts_are_kept() {
+    let staging = replace_hosts_block(SYSTEM_HOSTS, "staging", &entries(&[("127.1.0.1", "api")]));
+
+    let updated = replace_hosts_block(&staging, "rancher-desktop", &[]);
+
+    assert_eq!(updated, staging);
+}
+
+#[test]
+fn test_empty_entries_remove_the_block() {
+    let with_block = replace_hosts_block(
+        SYSTEM_HOSTS,
+        "rancher-desktop",
+        &entries(&[("127.1.0.1", "postgres")]),
+    );
+
+    assert_eq!(
+        replace_hosts_block(&with_block, "rancher-desktop", &[]),
+        SYSTEM_HOSTS
+    );
+}
+
+#[tokio::test]
+async fn test_apply_hosts_block_writes_the_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("hosts");
+    std::fs::write(&path, SYSTEM_HOSTS).unwrap();
+
+    apply_hosts_block(
+        &path,
+        "rancher-desktop",
+        &entries(&[("127.1.0.1", "postgres")]),
+    )
+    .await
+    .unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.starts_with(SYSTEM_HOSTS));
+    assert!(written.contains("127.1.0.1 postgres\n"));
+
+    apply_hosts_block(&path, "rancher-desktop", &[])
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), SYSTEM_HOSTS);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_apply_hosts_block_replaces_a_symlinked_file_atomically() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let target = dir.path().join("hosts.real");
+    std::fs::write(&target, SYSTEM_HOSTS).unwrap();
+    std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o644)).unwrap();
+    let link = dir.path().join("hosts");
+    std::os::unix::fs::symlink(&target, &link).unwrap();
+
+    apply_hosts_block(
+        &link,
+        "rancher-desktop",
+        &entries(&[("127.1.0.1", "redis")]),
+    )
+    .await
+    .unwrap();
+
+    assert!(std::fs::symlink_metadata(&link)
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert!(std::fs::read_to_string(&target)
+        .unwrap()
+        .contains("127.1.0.1 redis\n"));
+    let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o644);
+    let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty());
+}
@@ -0,0 +1,77 @@
+// Local CA file tests
+//
+// Tests for storing the local TLS certificate authority.
+
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+
+use roro_persistence::{load_tls_ca_from, save_ca_certificate_to, save_tls_ca_to};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_missing_ca_loads_as_none() {
+    let dir = TempDir::new().unwrap();
+
+    assert!(load_tls_ca_from(dir.path()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_ca_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let tls_dir = dir.path().join("tls");
+
+    save_tls_ca_to(&tls_dir, "cert", "key").await.unwrap();
+
+    let loaded = load_tls_ca_from(&tls_dir).await.unwrap();
+    assert_eq!(loaded, Some(("cert".to_string(), "key".to_string())));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_ca_key_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    save_tls_ca_to(dir.path(), "cert", "key").await.unwrap();
+
+    let mode = std::fs::metadata(dir.path().join("ca-key.pem"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_replacing_a_readable_key_leaves_it_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let key_path = dir.path().join("ca-key.pem");
+    std::fs::write(&key_path, "old key").unwrap();
+    std::fs::set_permissions(&key_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    save_tls_ca_to(dir.path(), "cert", "new key").await.unwrap();
+
+    let mode = std::fs::metadata(&key_path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert_eq!(std::fs::read_to_string(&key_path).unwrap(), "new key");
+    assert!(!dir.path().join("ca-key.pem.tmp").exists());
+}
+
+#[tokio::test]
+async fn test_incomplete_ca_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("ca.pem"), "cert").unwrap();
+
+    assert!(load_tls_ca_from(dir.path()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_export_certificate() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("exported").join("roro-ca.pem");
+
+    save_ca_certificate_to(&path, "cert").await.unwrap();
+
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "cert");
+}